@@ -0,0 +1,24 @@
+use std::env;
+
+fn main() {
+    let target = env::var("TARGET").unwrap();
+
+    // The Cortex-M0/M0+ port is picked based on the compilation target. The cfg is declared
+    // for every target, so that `#[cfg(armv6m)]` is not reported as unexpected.
+    println!("cargo:rustc-check-cfg=cfg(armv6m)");
+    if target.starts_with("thumbv6m-") {
+        println!("cargo:rustc-cfg=armv6m");
+    }
+
+    // Saving the FPU context needs the FPU instructions of the hard-float targets.
+    if env::var_os("CARGO_FEATURE_FPU").is_some() && !target.ends_with("eabihf") {
+        panic!("The `fpu` feature requires a hard-float target like `thumbv7em-none-eabihf`.");
+    }
+
+    // The MPU support programs the PMSAv7 MPU of ARMv7-M.
+    if env::var_os("CARGO_FEATURE_MPU").is_some() && !target.starts_with("thumbv7") {
+        panic!("The `mpu` feature requires an ARMv7-M target like `thumbv7em-none-eabi`.");
+    }
+
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,11 @@
+MEMORY
+{
+  /* nRF51822 as found on the BBC micro:bit */
+  FLASH (rx) : ORIGIN = 0x00000000, LENGTH = 256K
+  RAM (xrw) : ORIGIN = 0x20000000, LENGTH = 16K
+}
+
+/* This is where the call stack will be allocated. */
+/* The stack is of the full descending type. */
+/* NOTE Do NOT modify `_stack_start` unless you know what you are doing */
+_stack_start = ORIGIN(RAM) + LENGTH(RAM);
@@ -0,0 +1,64 @@
+#![no_std]
+#![no_main]
+
+extern crate panic_halt;
+
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+
+use harsark::events::{self, EventAction};
+use harsark::helpers::TaskMask;
+use harsark::primitives::*;
+use harsark::tasks::*;
+use harsark::timer;
+
+const TASK1: u32 = 1;
+const TASK2: u32 = 2;
+
+const STACK_SIZE: usize = 128;
+
+static sem2: Semaphore = Semaphore::new(TaskMask::<1>::generate([TASK2]));
+static res1: Resource<[u32; 2]> = Resource::new([1, 2], TaskMask::<2>::generate([TASK1, TASK2]));
+
+static mut stack1: [u32; STACK_SIZE] = [0; STACK_SIZE];
+static mut stack2: [u32; STACK_SIZE] = [0; STACK_SIZE];
+
+#[entry]
+fn main() -> ! {
+    let mut peripherals = cortex_m::Peripherals::take().unwrap();
+
+    init(|_| Ok(())).unwrap();
+
+    create_task(TASK1, unsafe { &mut stack1 }, |cxt| loop {
+        hprintln!("TASK 1: Enter").unwrap();
+        res1.acquire(cxt, |res| {
+            hprintln!("TASK 1 : res1 : {:?}", res).unwrap();
+            sem2.signal_and_release(0);
+        })
+        .unwrap();
+        hprintln!("TASK 1: End").unwrap();
+        task_exit();
+    })
+    .unwrap();
+
+    create_task(TASK2, unsafe { &mut stack2 }, |cxt| loop {
+        hprintln!("TASK 2: Enter").unwrap();
+        res1.acquire(cxt, |res| {
+            hprintln!("TASK 2 : res1 : {:?}", res).unwrap();
+        })
+        .unwrap();
+        hprintln!("TASK 2: End").unwrap();
+        task_exit();
+    })
+    .unwrap();
+
+    events::new(
+        true,
+        timer::Duration::from_ticks(5),
+        EventAction::Release(TaskMask::<1>::generate([TASK1])),
+    )
+    .unwrap();
+
+    timer::start_timer(&mut peripherals, 16_000);
+    start_kernel()
+}
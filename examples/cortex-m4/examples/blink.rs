@@ -12,7 +12,7 @@ use cortex_m_semihosting::hprintln;
 use cortex_m_rt::entry;
 use stm32f4::stm32f407::Peripherals;
 
-use harsark::events;
+use harsark::events::{self, EventAction};
 use harsark::helpers::TaskMask;
 use harsark::primitives::*;
 use harsark::spawn;
@@ -54,8 +54,8 @@ fn peripherals_configure(peripherals: &mut Peripherals) {
 fn main() -> ! {
     let mut cortex_peripherals = cortex_m::Peripherals::take().unwrap();
 
-    let event1 = events::new(true, 5, || release(TaskMask::generate([TASK1])));
-    let event2 = events::new(true, 4, || release(TaskMask::generate([TASK2])));
+    let event1 = events::new(true, timer::Duration::from_ticks(5), EventAction::Release(TaskMask::generate([TASK1])));
+    let event2 = events::new(true, timer::Duration::from_ticks(4), EventAction::Release(TaskMask::generate([TASK2])));
 
     spawn!(
         TASK1,
@@ -8,7 +8,7 @@ use core::cell::RefCell;
 use cortex_m_rt::entry;
 use cortex_m_semihosting::hprintln;
 
-use harsark::events;
+use harsark::events::{self, EventAction};
 use harsark::helpers::TaskMask;
 use harsark::primitives::*;
 use harsark::spawn;
@@ -32,15 +32,21 @@ fn main() -> ! {
         [9, 10],
     );
 
-    let event1 = events::new(true, 3, || {
-        msg1.broadcast([1, 2]);
-    });
-    let event2 = events::new(true, 2, || {
-        sem2.signal_and_release(TaskMask::generate([TASK2]));
-    });
-    let event2 = events::new(true, 6, || {
-        release(TaskMask::generate([TASK1]));
-    });
+    let event1 = events::new(
+        true,
+        timer::Duration::from_ticks(3),
+        EventAction::broadcast(&msg1, &[1, 2]),
+    );
+    let event2 = events::new(
+        true,
+        timer::Duration::from_ticks(2),
+        EventAction::Signal(&sem2, TaskMask::generate([TASK2])),
+    );
+    let event2 = events::new(
+        true,
+        timer::Duration::from_ticks(6),
+        EventAction::Release(TaskMask::generate([TASK1])),
+    );
 
     spawn!(
         TASK1,
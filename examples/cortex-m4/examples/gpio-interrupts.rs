@@ -9,12 +9,11 @@ extern crate stm32f4;
 use core::cell::RefCell;
 use cortex_m_semihosting::hprintln;
 
-use cortex_m::peripheral::NVIC;
 use cortex_m_rt::entry;
-use stm32f4::stm32f407::interrupt;
 use stm32f4::stm32f407::{self, Peripherals};
 
 use harsark::helpers::TaskMask;
+use harsark::interrupts::{self, EventAction};
 use harsark::primitives::*;
 use harsark::spawn;
 use harsark::tasks::*;
@@ -74,12 +73,6 @@ fn peripherals_configure(peripherals: &mut Peripherals) {
     // trigger the external interrupts 3 and 4 on rising-edge
     exti.rtsr.modify(|_, w| w.tr3().set_bit().tr4().set_bit());
 
-    // enable the interrupts
-    unsafe {
-        NVIC::unmask(stm32f407::Interrupt::EXTI3);
-        NVIC::unmask(stm32f407::Interrupt::EXTI4);
-    }
-
     // clear pin 6 config
     gpioa
         .otyper
@@ -94,16 +87,6 @@ fn peripherals_configure(peripherals: &mut Peripherals) {
         .write(|w| w.pupdr6().pull_up().pupdr7().pull_up());
 }
 
-#[interrupt]
-fn EXTI3() {
-    perif_pr3.signal_and_release(TaskMask::generate([TASK3]));
-}
-
-#[interrupt]
-fn EXTI4() {
-    perif_pr4.signal_and_release(TaskMask::generate([TASK3]));
-}
-
 #[entry]
 fn main() -> ! {
     spawn!(
@@ -168,5 +151,16 @@ fn main() -> ! {
             peripherals_configure(perf);
         })
     });
+    // The Kernel takes EXTI3 and EXTI4, signals the semaphores and releases TASK3.
+    interrupts::bind(
+        stm32f407::Interrupt::EXTI3 as u16,
+        EventAction::Signal(&perif_pr3, TaskMask::generate([TASK3])),
+    )
+    .unwrap();
+    interrupts::bind(
+        stm32f407::Interrupt::EXTI4 as u16,
+        EventAction::Signal(&perif_pr4, TaskMask::generate([TASK3])),
+    )
+    .unwrap();
     start_kernel()
 }
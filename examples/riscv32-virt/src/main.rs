@@ -0,0 +1,85 @@
+#![no_std]
+#![no_main]
+
+extern crate panic_halt;
+
+use core::fmt::{self, Write};
+
+use riscv_rt::entry;
+
+use harsark::events::{self, EventAction};
+use harsark::helpers::TaskMask;
+use harsark::primitives::*;
+use harsark::tasks::*;
+use harsark::timer;
+use harsark::timer::Peripherals;
+
+const TASK1: u32 = 1;
+const TASK2: u32 = 2;
+
+const STACK_SIZE: usize = 256;
+
+static sem2: Semaphore = Semaphore::new(TaskMask::<1>::generate([TASK2]));
+static res1: Resource<[u32; 2]> = Resource::new([1, 2], TaskMask::<2>::generate([TASK1, TASK2]));
+
+static mut stack1: [u32; STACK_SIZE] = [0; STACK_SIZE];
+static mut stack2: [u32; STACK_SIZE] = [0; STACK_SIZE];
+
+/// The NS16550 UART of the QEMU `virt` machine.
+struct Uart;
+
+impl Write for Uart {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            unsafe { core::ptr::write_volatile(0x1000_0000 as *mut u8, byte) };
+        }
+        Ok(())
+    }
+}
+
+macro_rules! println {
+    ($($arg:tt)*) => {
+        writeln!(Uart, $($arg)*).unwrap()
+    };
+}
+
+#[entry]
+fn main() -> ! {
+    let mut peripherals = Peripherals::take().unwrap();
+
+    init(|_| Ok(())).unwrap();
+
+    create_task(TASK1, unsafe { &mut stack1 }, |cxt| loop {
+        println!("TASK 1: Enter");
+        res1.acquire(cxt, |res| {
+            println!("TASK 1 : res1 : {:?}", res);
+            sem2.signal_and_release(0);
+        })
+        .unwrap();
+        println!("TASK 1: End");
+        task_exit();
+    })
+    .unwrap();
+
+    create_task(TASK2, unsafe { &mut stack2 }, |cxt| loop {
+        println!("TASK 2: Enter");
+        res1.acquire(cxt, |res| {
+            println!("TASK 2 : res1 : {:?}", res);
+        })
+        .unwrap();
+        println!("TASK 2: End");
+        task_exit();
+    })
+    .unwrap();
+
+    events::new(
+        true,
+        timer::Duration::from_ticks(5),
+        EventAction::Release(TaskMask::<1>::generate([TASK1])),
+    )
+    .unwrap();
+
+    // The machine timer of `virt` runs at 10 MHz, i.e. a tick every millisecond.
+    timer::start_timer(&mut peripherals, 10_000);
+    start_kernel()
+}
@@ -2,10 +2,13 @@
 
 #[cfg(all(
     any(feature = "tasks_32",feature="default"),
-    not(any(feature="tasks_16",feature="tasks_8"))
+    not(any(feature="tasks_16",feature="tasks_8",feature="tasks_64"))
 ))]
 pub const MAX_TASKS: usize = 32;
 
+#[cfg(all(feature = "tasks_64",not(feature="tasks_32")))]
+pub const MAX_TASKS: usize = 64;
+
 #[cfg(all(feature = "tasks_16",not(feature="tasks_32")))]
 pub const MAX_TASKS: usize = 16;
 
@@ -33,4 +36,48 @@ pub const EVENT_COUNT: usize = 16;
 #[cfg(feature = "events_64")]
 pub const EVENT_COUNT: usize = 64;
 
-pub const MAX_LOGS: usize = 128;
\ No newline at end of file
+pub const MAX_LOGS: usize = 128;
+
+/// Number of Kernel ticks per second, the rate the `tick_interval` passed to `timer::start_timer`
+/// has to yield. Used to convert between ticks and real time units. Selected by the
+/// `tick_rate_*` features, 1 kHz by default.
+#[cfg(any(
+    feature = "tick_rate_1000",
+    not(any(feature = "tick_rate_100", feature = "tick_rate_10000"))
+))]
+pub const TICK_RATE_HZ: u64 = 1_000;
+
+#[cfg(all(feature = "tick_rate_100", not(feature = "tick_rate_1000")))]
+pub const TICK_RATE_HZ: u64 = 100;
+
+#[cfg(all(feature = "tick_rate_10000", not(feature = "tick_rate_1000")))]
+pub const TICK_RATE_HZ: u64 = 10_000;
+
+/// The pattern task stacks are painted with on creation.
+pub const STACK_PAINT: u32 = 0xDEAD_BEEF;
+
+/// Fewest ticks the idle task suppresses the tick for in tickless mode, shorter idle periods
+/// are slept through with the tick running.
+pub const TICKLESS_MIN_TICKS: u32 = 2;
+
+/// Number of Kernel objects which can be registered for use by unprivileged tasks.
+pub const MAX_KERNEL_OBJECTS: usize = 32;
+
+/// Number of software timers which can be created.
+#[cfg(feature = "timer")]
+pub const MAX_SOFT_TIMERS: usize = 16;
+
+/// Default number of ticks a task runs before the next ready task of the same priority takes its turn.
+pub const TIME_SLICE: u32 = 10;
+
+#[cfg(feature = "mpu")]
+pub const MAX_SHARED_REGIONS: usize = 16;
+
+/// MPU regions available for shared regions on every context switch, the remaining two
+/// cover the code and the stack of the running task.
+#[cfg(feature = "mpu")]
+pub const MPU_TASK_REGIONS: usize = 6;
+
+/// Number of interrupts which can be bound to actions, at most 32.
+#[cfg(feature = "interrupts")]
+pub const MAX_IRQ_BINDINGS: usize = 16;
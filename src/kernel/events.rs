@@ -4,60 +4,99 @@
 
 use core::cell::RefCell;
 
-use crate::utils::arch::{critical_section,Mutex};
-
+use crate::kernel::syscall::{decode, syscall, Syscall};
+use crate::kernel::timer::get_time;
 use crate::priv_execute;
+use crate::system::action::EventAction;
 use crate::system::event::*;
+use crate::system::time::Duration;
 use crate::utils::arch::is_privileged;
+use crate::utils::arch::{critical_section, Mutex};
 use crate::KernelError;
-use crate::kernel::timer::get_time;
 
 /// Global Instance of EventManager
-static event_manager: Mutex<RefCell<EventTable>> = Mutex::new(RefCell::new(EventTable::new()));
+static EventManager: Mutex<RefCell<EventTable>> = Mutex::new(RefCell::new(EventTable::new()));
 
-/// Sweeps all events in event table and updates their counter, if counter has expired
-/// then it dispatches the event and resets the counter.
+/// Sweeps all events in event table and executes the actions of the ones which are due, within
+/// the critical section of the sweep.
 pub fn sweep_event_table() {
     critical_section(|cs_token| {
-        event_manager
+        EventManager
             .borrow(cs_token)
             .borrow_mut()
-            .sweep(get_time());
+            .sweep(cs_token, get_time().ticks());
     })
 }
 
-/// This function is used to enable events.
-pub fn enable(event_id: EventId) -> Result<(),KernelError> {
+/// Returns the number of ticks until the next tick on which an event is dispatched, if any event is enabled.
+pub(crate) fn get_next_dispatch(curr_time: u64) -> Option<u64> {
     critical_section(|cs_token| {
-        event_manager
+        EventManager
             .borrow(cs_token)
-            .borrow_mut()
-            .enable(event_id)
+            .borrow()
+            .get_next_dispatch(curr_time)
     })
 }
+
+/// This function is used to enable events.
+pub fn enable(event_id: EventId) -> Result<(), KernelError> {
+    if !is_privileged() {
+        return decode(syscall(Syscall::EventEnable, [event_id, 0, 0]));
+    }
+    critical_section(|cs_token| EventManager.borrow(cs_token).borrow_mut().enable(event_id))
+}
+
 /// This function is used to disable events.
-pub fn disable(event_id: EventId) -> Result<(),KernelError> {
-    critical_section(|cs_token| {
-        event_manager
-            .borrow(cs_token)
-            .borrow_mut()
-            .disable(event_id)
-    })
+pub fn disable(event_id: EventId) -> Result<(), KernelError> {
+    if !is_privileged() {
+        return decode(syscall(Syscall::EventDisable, [event_id, 0, 0]));
+    }
+    critical_section(|cs_token| EventManager.borrow(cs_token).borrow_mut().disable(event_id))
 }
 
-/// Creates new Events.
+/// Creates new Events. The action of an enabled event is executed every `threshold`, which must
+/// not be zero, from the SysTick handler with interrupts disabled.
 pub fn new(
     is_enabled: bool,
-    threshold: u32,
-    handler: fn() -> (),
+    threshold: Duration,
+    action: EventAction,
+) -> Result<EventId, KernelError> {
+    new_with_offset(is_enabled, threshold, Duration::ZERO, action)
+}
+
+/// Creates new Events whose action is first executed at `offset`, i.e. `offset` after the timer
+/// was started, and every `threshold` after that. This staggers events of the same rate.
+pub fn new_with_offset(
+    is_enabled: bool,
+    threshold: Duration,
+    offset: Duration,
+    action: EventAction,
 ) -> Result<EventId, KernelError> {
     priv_execute!({
         critical_section(|cs_token| {
-            event_manager.borrow(cs_token).borrow_mut().create(
-                is_enabled,
-                threshold,
-                handler,
-            )
+            EventManager
+                .borrow(cs_token)
+                .borrow_mut()
+                .create(is_enabled, threshold, offset, action)
+        })
+    })
+}
+
+/// Changes the period of an event to `threshold`, which must not be zero. The event keeps its offset.
+pub fn set_threshold(event_id: EventId, threshold: Duration) -> Result<(), KernelError> {
+    priv_execute!({
+        critical_section(|cs_token| {
+            EventManager
+                .borrow(cs_token)
+                .borrow_mut()
+                .set_threshold(event_id, threshold)
         })
     })
-}
\ No newline at end of file
+}
+
+/// Deletes an event. Its EventId is free to be reused by a new event.
+pub fn delete(event_id: EventId) -> Result<(), KernelError> {
+    priv_execute!({
+        critical_section(|cs_token| EventManager.borrow(cs_token).borrow_mut().delete(event_id))
+    })
+}
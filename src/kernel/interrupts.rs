@@ -0,0 +1,104 @@
+//! # Interrupt Binding Module
+//!
+//! Defines Kernel routines which bind interrupts to actions. A bound interrupt is taken by the
+//! default interrupt handler of the port, which executes its action, counts it and records a log
+//! entry. The cycles from its entry until the first task it released runs are recorded as the
+//! hand-off latency of the interrupt. The other interrupts and exceptions taken by the default
+//! interrupt handler are passed on to the handler set by the application through
+//! `set_default_handler`.
+
+use core::cell::RefCell;
+
+use crate::kernel::tasks::{schedule, TaskManager};
+use crate::priv_execute;
+use crate::system::action::EventAction;
+use crate::system::interrupt::*;
+use crate::system::scheduler::TaskId;
+use crate::utils::arch::{critical_section, disable_irq, enable_irq, get_cycles, Mutex};
+use crate::KernelError;
+
+#[cfg(feature = "system_logger")]
+use {crate::kernel::logging, crate::system::system_logger::LogEventType};
+
+/// Global Instance of the interrupt bindings.
+static IrqManager: Mutex<RefCell<IrqTable>> = Mutex::new(RefCell::new(IrqTable::new()));
+
+/// Binds interrupt `irqn` to `action` and enables it. From then on the interrupt is handled by the
+/// Kernel, hence the application must not define a handler of its own for it.
+pub fn bind(irqn: IrqNumber, action: EventAction) -> Result<(), KernelError> {
+    priv_execute!({
+        critical_section(|cs_token| IrqManager.borrow(cs_token).borrow_mut().bind(irqn, action))?;
+        enable_irq(irqn);
+        Ok(())
+    })
+}
+
+/// Disables interrupt `irqn` and removes its binding.
+pub fn unbind(irqn: IrqNumber) -> Result<(), KernelError> {
+    priv_execute!({
+        disable_irq(irqn);
+        critical_section(|cs_token| IrqManager.borrow(cs_token).borrow_mut().unbind(irqn))
+    })
+}
+
+/// Sets the handler of the interrupts which are not bound and of the system exceptions without a
+/// handler of their own, in place of the default interrupt handler of the application. It is
+/// called with the interrupt number, which is negative for system exceptions. If no handler is
+/// set, such an interrupt panics.
+pub fn set_default_handler(handler: fn(i16)) {
+    critical_section(|cs_token| {
+        IrqManager
+            .borrow(cs_token)
+            .borrow_mut()
+            .set_default_handler(handler)
+    })
+}
+
+/// Returns the raise count and hand-off latencies of interrupt `irqn`.
+pub fn stats(irqn: IrqNumber) -> Result<IrqStats, KernelError> {
+    critical_section(|cs_token| IrqManager.borrow(cs_token).borrow().get_stats(irqn))
+}
+
+/// Body of the default interrupt handler. Executes the action interrupt `irqn` is bound to and
+/// calls `schedule()` so as to dispatch a task it released. An interrupt which is not bound, or
+/// a system exception if `irqn` is negative, is passed on to the default handler of the
+/// application.
+pub(crate) fn dispatch_irq(irqn: i16) {
+    let entry = get_cycles();
+    let (action, default_handler) = critical_section(|cs_token| {
+        let table = &mut IrqManager.borrow(cs_token).borrow_mut();
+        let action = if irqn >= 0 {
+            table.raise(irqn as IrqNumber, entry)
+        } else {
+            None
+        };
+        (action, table.get_default_handler())
+    });
+    let action = match (action, default_handler) {
+        (Some(action), _) => action,
+        (None, Some(handler)) => return handler(irqn),
+        (None, None) => panic!("Unbound interrupt {}", irqn),
+    };
+    #[cfg(feature = "system_logger")]
+    {
+        if logging::get_interrupt() {
+            logging::report(LogEventType::Interrupt(irqn as IrqNumber));
+        }
+    }
+    let is_preemptive = critical_section(|cs_token| {
+        action.execute(cs_token);
+        TaskManager.borrow(cs_token).borrow().is_preemptive
+    });
+    schedule(is_preemptive);
+}
+
+/// Records the hand-off latency of the interrupts which released task `tid`, which starts
+/// running at cycle `now`. Called during the context switch.
+pub(crate) fn complete_handoffs(tid: TaskId, now: u32) {
+    critical_section(|cs_token| {
+        let table = &mut IrqManager.borrow(cs_token).borrow_mut();
+        if table.has_pending() {
+            table.complete_handoffs(tid, now);
+        }
+    })
+}
@@ -1,12 +1,8 @@
 use core::cell::RefCell;
 
 use crate::kernel::timer::get_time;
-use crate::priv_execute;
-use crate::system::scheduler::*;
 use crate::system::system_logger::*;
-use crate::utils::arch::is_privileged;
 use crate::utils::arch::{critical_section, Mutex};
-use crate::KernelError;
 
 static Logger: Mutex<RefCell<SystemLogger>> = Mutex::new(RefCell::new(SystemLogger::new()));
 
@@ -42,7 +38,16 @@ pub fn set_all(val: bool) {
         Logger.borrow(cs_token).borrow_mut().message_recieve_log = val;
         Logger.borrow(cs_token).borrow_mut().semaphore_signal_log = val;
         Logger.borrow(cs_token).borrow_mut().semaphore_reset_log = val;
-        Logger.borrow(cs_token).borrow_mut().timer_event_log = val;
+        Logger.borrow(cs_token).borrow_mut().deadline_expired_log = val;
+        Logger.borrow(cs_token).borrow_mut().priority_change_log = val;
+        #[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
+        {
+            Logger.borrow(cs_token).borrow_mut().timer_event_log = val;
+        }
+        #[cfg(feature = "interrupts")]
+        {
+            Logger.borrow(cs_token).borrow_mut().interrupt_log = val;
+        }
     })
 }
 
@@ -106,12 +111,32 @@ pub fn set_semaphore_reset(val: bool) {
     })
 }
 
+pub fn set_deadline_expired(val: bool) {
+    critical_section(|cs_token| {
+        Logger.borrow(cs_token).borrow_mut().deadline_expired_log = val;
+    })
+}
+
+pub fn set_priority_change(val: bool) {
+    critical_section(|cs_token| {
+        Logger.borrow(cs_token).borrow_mut().priority_change_log = val;
+    })
+}
+
+#[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
 pub fn set_timer_event(val: bool) {
     critical_section(|cs_token| {
         Logger.borrow(cs_token).borrow_mut().timer_event_log = val;
     })
 }
 
+#[cfg(feature = "interrupts")]
+pub fn set_interrupt(val: bool) {
+    critical_section(|cs_token| {
+        Logger.borrow(cs_token).borrow_mut().interrupt_log = val;
+    })
+}
+
 pub fn get_release() -> bool {
     critical_section(|cs_token| Logger.borrow(cs_token).borrow_mut().release_log)
 }
@@ -152,6 +177,20 @@ pub fn get_semaphore_reset() -> bool {
     critical_section(|cs_token| Logger.borrow(cs_token).borrow_mut().semaphore_reset_log)
 }
 
+pub fn get_deadline_expired() -> bool {
+    critical_section(|cs_token| Logger.borrow(cs_token).borrow_mut().deadline_expired_log)
+}
+
+pub fn get_priority_change() -> bool {
+    critical_section(|cs_token| Logger.borrow(cs_token).borrow_mut().priority_change_log)
+}
+
+#[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
 pub fn get_timer_event() -> bool {
     critical_section(|cs_token| Logger.borrow(cs_token).borrow_mut().timer_event_log)
 }
+
+#[cfg(feature = "interrupts")]
+pub fn get_interrupt() -> bool {
+    critical_section(|cs_token| Logger.borrow(cs_token).borrow_mut().interrupt_log)
+}
@@ -2,6 +2,8 @@
 
 pub mod tasks;
 
+pub mod syscall;
+
 #[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
 pub mod events;
 
@@ -12,4 +14,13 @@ pub mod logging;
 pub mod task_monitor;
 
 #[cfg(feature="timer")]
-pub mod timer;
\ No newline at end of file
+pub mod timer;
+
+#[cfg(feature="timer")]
+pub mod soft_timer;
+
+#[cfg(feature="mpu")]
+pub mod mpu;
+
+#[cfg(feature="interrupts")]
+pub mod interrupts;
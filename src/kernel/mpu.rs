@@ -0,0 +1,120 @@
+//! # Memory Protection module
+//!
+//! Defines the Kernel routines which isolate the memory of unprivileged tasks through the MPU.
+//! While such a task runs, it can only read and execute the code region and access its own stack
+//! and the regions shared with it. Privileged code keeps the default memory map. A task violating
+//! its permissions is faulted: it is never scheduled again and the fault handler is called with
+//! its TaskId, the rest of the system keeps running.
+
+use core::cell::RefCell;
+use core::mem::size_of;
+
+use crate::kernel::tasks::{preempt, TaskManager};
+use crate::priv_execute;
+use crate::system::mpu::{MemoryRegion, RegionTable};
+use crate::system::scheduler::{BooleanVector, TaskId};
+use crate::utils::arch::{critical_section, enable_mpu, set_mpu_region, Mutex};
+use crate::KernelError;
+
+/// MPU region covering the stack of the running task.
+const STACK_REGION: usize = 1;
+/// First MPU region covering a shared region, the ones below are the code and stack regions.
+const SHARED_REGIONS: usize = 2;
+
+/// Global instance of the region table.
+static RegionManager: Mutex<RefCell<RegionTable>> = Mutex::new(RefCell::new(RegionTable::new()));
+
+/// Enables the MPU and the MemManage fault.
+pub(crate) fn init() {
+    enable_mpu();
+}
+
+/// Records `stack` as the stack region of task `tid`.
+pub(crate) fn set_stack(tid: TaskId, stack: &[u32]) -> Result<(), KernelError> {
+    let region = MemoryRegion::new(stack.as_ptr() as usize, stack.len() * size_of::<u32>())?;
+    critical_section(|cs_token| {
+        RegionManager
+            .borrow(cs_token)
+            .borrow_mut()
+            .set_stack(tid, region)
+    });
+    Ok(())
+}
+
+/// Drops the regions of the deleted task `tid`, so that they are not inherited by a new task taking its TaskId.
+pub(crate) fn remove_task(tid: TaskId) {
+    critical_section(|cs_token| {
+        RegionManager
+            .borrow(cs_token)
+            .borrow_mut()
+            .remove_task(tid)
+    });
+}
+
+/// Shares the memory backing `object` with the tasks in `tasks_mask`. The region covers the
+/// object rounded up to a power of two of at least 32 bytes, and the object has to be aligned to
+/// that size, e.g. through a `#[repr(align(N))]` wrapper.
+pub fn share<T>(tasks_mask: BooleanVector, object: &'static T) -> Result<(), KernelError> {
+    let size = size_of::<T>().next_power_of_two().max(32);
+    share_region(tasks_mask, object as *const T as usize, size)
+}
+
+/// Shares `size` bytes from `base` with the tasks in `tasks_mask`. `size` has to be a power of
+/// two of at least 32 bytes, and `base` has to be aligned to it.
+pub fn share_region(
+    tasks_mask: BooleanVector,
+    base: usize,
+    size: usize,
+) -> Result<(), KernelError> {
+    priv_execute!({
+        let region = MemoryRegion::new(base, size)?;
+        critical_section(|cs_token| {
+            RegionManager
+                .borrow(cs_token)
+                .borrow_mut()
+                .share(region, tasks_mask)
+        })
+    })
+}
+
+/// Sets the handler which is called with the TaskId of a task that violated its memory access permissions.
+pub fn set_fault_handler(handler: fn(TaskId)) {
+    critical_section(|cs_token| {
+        RegionManager
+            .borrow(cs_token)
+            .borrow_mut()
+            .set_handler(handler);
+    })
+}
+
+/// Programs the MPU with the regions of task `tid`. Called during the context switch, before the
+/// context of the task is loaded.
+pub(crate) fn load_regions(tid: usize) {
+    critical_section(|cs_token| {
+        let (stack, shared) = RegionManager
+            .borrow(cs_token)
+            .borrow()
+            .get_regions(tid as TaskId);
+        set_mpu_region(STACK_REGION, stack);
+        for (i, region) in shared.iter().enumerate() {
+            set_mpu_region(SHARED_REGIONS + i, *region);
+        }
+    })
+}
+
+/// Body of the MemManage handler for faults raised by a task. The running task is faulted and
+/// reported to the fault handler, then the next task is scheduled.
+pub(crate) fn task_fault() {
+    let (tid, handler) = critical_section(|cs_token| {
+        let scheduler = &mut TaskManager.borrow(cs_token).borrow_mut();
+        let tid = scheduler.curr_tid;
+        scheduler.active_tasks &= !(1 << tid);
+        scheduler.faulted_tasks |= 1 << tid;
+        let handler = RegionManager.borrow(cs_token).borrow().get_handler();
+        (tid as TaskId, handler)
+    });
+    if let Some(handler) = handler {
+        handler(tid);
+    }
+    preempt();
+}
@@ -0,0 +1,105 @@
+//! # Software Timer Module
+//!
+//! Defines Kernel routines for software timers. The handler of a timer is called from the
+//! SysTick handler on the tick it expires, a one-shot timer then stops while an auto-reload timer
+//! starts over with its period.
+
+use core::cell::RefCell;
+
+use crate::kernel::syscall::{decode, decode_flag, syscall, Syscall};
+use crate::priv_execute;
+use crate::system::soft_timer::*;
+use crate::system::time::Duration;
+use crate::utils::arch::is_privileged;
+use crate::utils::arch::{critical_section, Mutex};
+use crate::KernelError;
+
+/// Global Instance of the timer list.
+static TimerManager: Mutex<RefCell<TimerList>> = Mutex::new(RefCell::new(TimerList::new()));
+
+/// Counts down the running timers by a tick and calls the handlers of the ones which expired.
+/// Each handler is called outside the critical section, so that it can start or stop timers.
+pub(crate) fn sweep_timers() {
+    critical_section(|cs_token| TimerManager.borrow(cs_token).borrow_mut().tick());
+    while let Some(handler) =
+        critical_section(|cs_token| TimerManager.borrow(cs_token).borrow_mut().pop_expired())
+    {
+        handler();
+    }
+}
+
+/// Counts down `ticks` ticks which passed while the tick was suppressed.
+pub(crate) fn skip_ticks(ticks: u64) {
+    critical_section(|cs_token| TimerManager.borrow(cs_token).borrow_mut().skip(ticks));
+}
+
+/// Returns the number of ticks until the next timer expires, if any timer is running.
+pub(crate) fn get_next_expiry() -> Option<u64> {
+    critical_section(|cs_token| TimerManager.borrow(cs_token).borrow().ticks_until_next())
+}
+
+/// Creates a new timer which calls `handler` each time it expires, `period` after it was started.
+/// `period` must not be zero. The timer is stopped until it is started.
+pub fn create(mode: TimerMode, period: Duration, handler: fn()) -> Result<TimerId, KernelError> {
+    priv_execute!({
+        critical_section(|cs_token| {
+            TimerManager
+                .borrow(cs_token)
+                .borrow_mut()
+                .create(mode, period, handler)
+        })
+    })
+}
+
+/// Deletes timer `timer_id`, stopping it if it is running. Its TimerId is free to be reused by a
+/// new timer.
+pub fn delete(timer_id: TimerId) -> Result<(), KernelError> {
+    priv_execute!({
+        critical_section(|cs_token| TimerManager.borrow(cs_token).borrow_mut().delete(timer_id))
+    })
+}
+
+/// Starts timer `timer_id`, it expires one period from now. A running timer is left as it is.
+pub fn start(timer_id: TimerId) -> Result<(), KernelError> {
+    if !is_privileged() {
+        return decode(syscall(Syscall::TimerStart, [timer_id, 0, 0]));
+    }
+    critical_section(|cs_token| TimerManager.borrow(cs_token).borrow_mut().start(timer_id))
+}
+
+/// Stops timer `timer_id`, it does not expire until it is started again.
+pub fn stop(timer_id: TimerId) -> Result<(), KernelError> {
+    if !is_privileged() {
+        return decode(syscall(Syscall::TimerStop, [timer_id, 0, 0]));
+    }
+    critical_section(|cs_token| TimerManager.borrow(cs_token).borrow_mut().stop(timer_id))
+}
+
+/// Starts timer `timer_id` over, it expires one period from now whether it was running or not.
+pub fn restart(timer_id: TimerId) -> Result<(), KernelError> {
+    if !is_privileged() {
+        return decode(syscall(Syscall::TimerRestart, [timer_id, 0, 0]));
+    }
+    critical_section(|cs_token| TimerManager.borrow(cs_token).borrow_mut().restart(timer_id))
+}
+
+/// Sets the period of timer `timer_id`, which must not be zero. A running timer is started over
+/// with the new period, a stopped one uses it once it is started.
+pub fn set_period(timer_id: TimerId, period: Duration) -> Result<(), KernelError> {
+    priv_execute!({
+        critical_section(|cs_token| {
+            TimerManager
+                .borrow(cs_token)
+                .borrow_mut()
+                .set_period(timer_id, period)
+        })
+    })
+}
+
+/// Returns whether timer `timer_id` is running.
+pub fn is_active(timer_id: TimerId) -> Result<bool, KernelError> {
+    if !is_privileged() {
+        return decode_flag(syscall(Syscall::TimerIsActive, [timer_id, 0, 0]));
+    }
+    critical_section(|cs_token| TimerManager.borrow(cs_token).borrow_mut().is_active(timer_id))
+}
@@ -0,0 +1,652 @@
+//! # System call gateway
+//!
+//! Tasks created with `create_unprivileged_task` run in unprivileged Thread mode, where they can
+//! neither mask interrupts nor access the System Control Block. Hence the Kernel routines they
+//! use are not executed directly, instead they raise a system call. The `SVCall` handler looks the
+//! routine up in `SYSCALL_TABLE` by its number and executes it in privileged mode.
+//!
+//! Arguments and return values are passed as machine words, a `Result<(), KernelError>` is
+//! returned as zero on success or as the error's code. Kernel objects are passed by address, and
+//! are only acted on if they were registered in `ObjectRegistry` by privileged code beforehand.
+//! What the Kernel needs to know about an object, like the tasks sharing a resource or the
+//! routines of a generic message, is taken from its registry entry rather than from memory the
+//! task can write, hence the Kernel never calls code at an address passed by a task. Values are
+//! only read from or written to the stack of the calling task.
+
+use core::cell::RefCell;
+
+use crate::kernel::tasks::{get_curr_tid, preempt, release, resume, suspend, task_exit, TaskManager};
+#[cfg(feature = "timer")]
+use crate::{
+    kernel::{soft_timer, tasks::sleep_until, timer::get_time},
+    system::time::Instant,
+};
+use crate::priv_execute;
+use crate::system::counting_semaphore::CountingSemaphore;
+use crate::system::event_group::{EventGroup, AUTO_CLEAR, WAIT_ALL};
+use crate::system::queue::BLOCK;
+use crate::system::registry::{KernelObject, ObjectTable};
+use crate::system::resource::{lock_resource, unlock_resource};
+use crate::system::scheduler::{BooleanVector, TaskId};
+use crate::system::semaphore::Semaphore;
+use crate::utils::arch::{self, critical_section, Mutex};
+use crate::KernelError;
+
+#[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
+use crate::kernel::events;
+
+/// Numbers of the system calls, i.e. their position in `SYSCALL_TABLE`.
+#[repr(usize)]
+#[derive(Clone, Copy)]
+pub(crate) enum Syscall {
+    Release = 0,
+    TaskExit = 1,
+    Preempt = 2,
+    ResourceLock = 3,
+    ResourceUnlock = 4,
+    MessageBroadcast = 5,
+    EventEnable = 6,
+    EventDisable = 7,
+    Sleep = 8,
+    Suspend = 9,
+    Resume = 10,
+    TimerStart = 11,
+    TimerStop = 12,
+    TimerRestart = 13,
+    SemaphoreSignal = 14,
+    SemaphoreTest = 15,
+    MessageReceive = 16,
+    GetCurrTid = 17,
+    GetTime = 18,
+    TimerIsActive = 19,
+    EventGroupSet = 20,
+    EventGroupClear = 21,
+    EventGroupGet = 22,
+    EventGroupWait = 23,
+    EventGroupWaitEnd = 24,
+    CountingSemaphoreCount = 25,
+    CountingSemaphoreTryAcquire = 26,
+    CountingSemaphoreAcquire = 27,
+    CountingSemaphoreAcquireEnd = 28,
+    CountingSemaphoreRelease = 29,
+    QueueLen = 30,
+    QueueSend = 31,
+    QueueReceive = 32,
+    QueueWaitEnd = 33,
+}
+
+type SyscallHandler = fn(args: [usize; 3]) -> usize;
+
+/// The routines behind the system calls, indexed by their number.
+static SYSCALL_TABLE: [SyscallHandler; 34] = [
+    sys_release,
+    sys_task_exit,
+    sys_preempt,
+    sys_resource_lock,
+    sys_resource_unlock,
+    sys_message_broadcast,
+    sys_event_enable,
+    sys_event_disable,
+    sys_sleep,
+    sys_suspend,
+    sys_resume,
+    sys_timer_start,
+    sys_timer_stop,
+    sys_timer_restart,
+    sys_semaphore_signal,
+    sys_semaphore_test,
+    sys_message_receive,
+    sys_get_curr_tid,
+    sys_get_time,
+    sys_timer_is_active,
+    sys_event_group_set,
+    sys_event_group_clear,
+    sys_event_group_get,
+    sys_event_group_wait,
+    sys_event_group_wait_end,
+    sys_counting_semaphore_count,
+    sys_counting_semaphore_try_acquire,
+    sys_counting_semaphore_acquire,
+    sys_counting_semaphore_acquire_end,
+    sys_counting_semaphore_release,
+    sys_queue_len,
+    sys_queue_send,
+    sys_queue_receive,
+    sys_queue_wait_end,
+];
+
+/// The arguments of a blocking call on a Kernel object which do not fit in the words of a system
+/// call, passed on the stack of the calling task. A blocking call is split in two system calls,
+/// as the task can only be switched out once the `SVCall` handler returned: the first one returns
+/// the result right away or makes the task wait, in which case the task raises the second one
+/// once it runs again to collect the result of its wait.
+#[repr(C)]
+pub(crate) struct BlockingArgs {
+    /// The argument of the call, overwritten by its result.
+    pub value: usize,
+    /// The options of the call, as defined by the object.
+    pub options: usize,
+    /// The time the call gives up at, `NO_TIMEOUT` if it waits for ever.
+    pub timeout_time: u64,
+}
+
+/// The `timeout_time` of a call which waits for ever.
+pub(crate) const NO_TIMEOUT: u64 = u64::MAX;
+
+impl BlockingArgs {
+    pub(crate) fn new(value: usize, options: usize, timeout_time: Option<u64>) -> Self {
+        Self {
+            value,
+            options,
+            timeout_time: timeout_time.unwrap_or(NO_TIMEOUT),
+        }
+    }
+
+    fn get_timeout_time(&self) -> Option<u64> {
+        Some(self.timeout_time).filter(|&timeout_time| timeout_time != NO_TIMEOUT)
+    }
+}
+
+/// Global instance of the registry of the objects unprivileged tasks may pass to system calls.
+static ObjectRegistry: Mutex<RefCell<ObjectTable>> = Mutex::new(RefCell::new(ObjectTable::new()));
+
+/// Registers `object` at `address`, so that unprivileged tasks can pass it to system calls.
+/// Only privileged code can register objects.
+pub(crate) fn register(address: usize, object: KernelObject) -> Result<(), KernelError> {
+    priv_execute!({
+        critical_section(|cs_token| {
+            ObjectRegistry
+                .borrow(cs_token)
+                .borrow_mut()
+                .register(address, object)
+        })
+    })
+}
+
+/// Returns the object registered at `address`.
+fn get_object(address: usize) -> Option<KernelObject> {
+    critical_section(|cs_token| ObjectRegistry.borrow(cs_token).borrow().get(address))
+}
+
+/// Returns whether the `size` bytes at `address` lie on the stack of the running task. On the
+/// host simulation tasks run on the stacks of their threads, which the Kernel does not know of.
+fn is_task_memory(address: usize, size: usize) -> bool {
+    if cfg!(feature = "std") {
+        return true;
+    }
+    critical_section(|cs_token| {
+        let handler = TaskManager.borrow(cs_token).borrow();
+        match handler.task_control_blocks[handler.curr_tid].as_ref() {
+            Some(tcb) => tcb.is_on_stack(address, size),
+            None => false,
+        }
+    })
+}
+
+/// Returns whether a value of `size` bytes aligned to `align` can be read from or written to
+/// `address`, i.e. the address is aligned and the value lies on the stack of the running task.
+fn is_task_value(address: usize, size: usize, align: usize) -> bool {
+    address % align == 0 && is_task_memory(address, size)
+}
+
+/// Returns the arguments of a blocking call at `address`, if they lie on the stack of the running
+/// task.
+fn get_blocking_args(address: usize) -> Option<&'static mut BlockingArgs> {
+    let size = core::mem::size_of::<BlockingArgs>();
+    if !is_task_value(address, size, core::mem::align_of::<BlockingArgs>()) {
+        return None;
+    }
+    Some(unsafe { &mut *(address as *mut BlockingArgs) })
+}
+
+/// Raises the system call `call` with `args` and returns its result.
+pub(crate) fn syscall(call: Syscall, args: [usize; 3]) -> usize {
+    arch::syscall(call as usize, args)
+}
+
+/// Body of the `SVCall` handler. Executes system call `number` with `args` and returns its result.
+pub(crate) fn dispatch(number: usize, args: [usize; 3]) -> usize {
+    match SYSCALL_TABLE.get(number) {
+        Some(handler) => handler(args),
+        None => encode(Err(KernelError::NotFound)),
+    }
+}
+
+/// Encodes the result of a routine as the return value of a system call.
+pub(crate) fn encode(res: Result<(), KernelError>) -> usize {
+    match res {
+        Ok(()) => 0,
+        Err(KernelError::NotFound) => 1,
+        Err(KernelError::StackTooSmall) => 2,
+        Err(KernelError::LimitExceeded) => 3,
+        Err(KernelError::AccessDenied) => 4,
+        Err(KernelError::Empty) => 5,
+        Err(KernelError::Exists) => 6,
+        Err(KernelError::InvalidRegion) => 7,
+        Err(KernelError::InvalidArgument) => 8,
+        Err(KernelError::TimedOut) => 9,
+    }
+}
+
+/// Decodes the return value of a system call encoded by `encode`.
+pub(crate) fn decode(res: usize) -> Result<(), KernelError> {
+    match res {
+        0 => Ok(()),
+        1 => Err(KernelError::NotFound),
+        2 => Err(KernelError::StackTooSmall),
+        3 => Err(KernelError::LimitExceeded),
+        4 => Err(KernelError::AccessDenied),
+        5 => Err(KernelError::Empty),
+        6 => Err(KernelError::Exists),
+        7 => Err(KernelError::InvalidRegion),
+        9 => Err(KernelError::TimedOut),
+        _ => Err(KernelError::InvalidArgument),
+    }
+}
+
+/// Encodes a routine which returns a flag, a set flag is returned as success and a cleared one
+/// as the code of `Empty`.
+pub(crate) fn encode_flag(res: Result<bool, KernelError>) -> usize {
+    encode(res.and_then(|flag| if flag { Ok(()) } else { Err(KernelError::Empty) }))
+}
+
+/// Decodes the return value of a system call encoded by `encode_flag`.
+pub(crate) fn decode_flag(res: usize) -> Result<bool, KernelError> {
+    match decode(res) {
+        Ok(()) => Ok(true),
+        Err(KernelError::Empty) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Splits `mask` into the two words passed to a system call, as it may be wider than a word.
+pub(crate) fn split_mask(mask: BooleanVector) -> [usize; 2] {
+    let mask = u64::from(mask);
+    [(mask & u64::from(u32::MAX)) as usize, (mask >> 32) as usize]
+}
+
+/// Joins the two words of a mask split by `split_mask`.
+pub(crate) fn join_mask(lo: usize, hi: usize) -> BooleanVector {
+    (lo as u64 | (hi as u64) << 32) as BooleanVector
+}
+
+/// Splits a 64 bit tick count into the two words passed to a system call.
+pub(crate) fn split_ticks(ticks: u64) -> [usize; 2] {
+    [ticks as u32 as usize, (ticks >> 32) as usize]
+}
+
+/// Joins the two words of a tick count split by `split_ticks`.
+pub(crate) fn join_ticks(lo: usize, hi: usize) -> u64 {
+    lo as u32 as u64 | (hi as u64) << 32
+}
+
+fn sys_release(args: [usize; 3]) -> usize {
+    release(join_mask(args[0], args[1]));
+    0
+}
+
+fn sys_task_exit(_args: [usize; 3]) -> usize {
+    task_exit();
+    0
+}
+
+fn sys_preempt(_args: [usize; 3]) -> usize {
+    preempt();
+    0
+}
+
+fn sys_suspend(args: [usize; 3]) -> usize {
+    encode(suspend(args[0] as TaskId))
+}
+
+fn sys_resume(args: [usize; 3]) -> usize {
+    encode(resume(args[0] as TaskId))
+}
+
+fn sys_get_curr_tid(_args: [usize; 3]) -> usize {
+    get_curr_tid() as usize
+}
+
+/// The ceiling is computed from the tasks the resource was registered with, so that a task can
+/// not raise the system ceiling beyond the resources it shares.
+fn sys_resource_lock(args: [usize; 3]) -> usize {
+    match get_object(args[0]) {
+        Some(KernelObject::Resource(tasks_mask)) => {
+            encode(lock_resource(tasks_mask, get_curr_tid()))
+        }
+        _ => encode(Err(KernelError::NotFound)),
+    }
+}
+
+fn sys_resource_unlock(args: [usize; 3]) -> usize {
+    match get_object(args[0]) {
+        Some(KernelObject::Resource(tasks_mask)) => {
+            encode(unlock_resource(tasks_mask, get_curr_tid()))
+        }
+        _ => encode(Err(KernelError::NotFound)),
+    }
+}
+
+/// Returns the semaphore registered at `address`.
+fn get_semaphore(address: usize) -> Option<&'static Semaphore> {
+    match get_object(address) {
+        Some(KernelObject::Semaphore) => Some(unsafe { &*(address as *const Semaphore) }),
+        _ => None,
+    }
+}
+
+fn sys_semaphore_signal(args: [usize; 3]) -> usize {
+    match get_semaphore(args[0]) {
+        Some(semaphore) => {
+            semaphore.signal_and_release(join_mask(args[1], args[2]));
+            0
+        }
+        None => encode(Err(KernelError::NotFound)),
+    }
+}
+
+/// The flag tested is the one of the running task, whatever Context the task holds.
+fn sys_semaphore_test(args: [usize; 3]) -> usize {
+    match get_semaphore(args[0]) {
+        Some(semaphore) => encode_flag(Ok(semaphore.test_and_reset_for(get_curr_tid()))),
+        None => encode(Err(KernelError::NotFound)),
+    }
+}
+
+/// As the table can not be generic over the message type, the message is broadcast through the
+/// routine of its type recorded on registration. The task passes the message and the value to be
+/// broadcast, which is moved into the message.
+fn sys_message_broadcast(args: [usize; 3]) -> usize {
+    match get_object(args[0]) {
+        Some(KernelObject::Message(ops)) if is_task_value(args[1], ops.size, ops.align) => {
+            unsafe { (ops.broadcast)(args[0], args[1]) };
+            0
+        }
+        Some(KernelObject::Message(_)) => encode(Err(KernelError::InvalidArgument)),
+        _ => encode(Err(KernelError::NotFound)),
+    }
+}
+
+/// The task passes the message and the address the value is written to if it was received.
+fn sys_message_receive(args: [usize; 3]) -> usize {
+    match get_object(args[0]) {
+        Some(KernelObject::Message(ops)) if is_task_value(args[1], ops.size, ops.align) => {
+            encode_flag(Ok(unsafe { (ops.receive)(args[0], args[1]) }))
+        }
+        Some(KernelObject::Message(_)) => encode(Err(KernelError::InvalidArgument)),
+        _ => encode(Err(KernelError::NotFound)),
+    }
+}
+
+/// Returns the event group registered at `address`.
+fn get_event_group(address: usize) -> Option<&'static EventGroup> {
+    match get_object(address) {
+        Some(KernelObject::EventGroup) => Some(unsafe { &*(address as *const EventGroup) }),
+        _ => None,
+    }
+}
+
+fn sys_event_group_set(args: [usize; 3]) -> usize {
+    match get_event_group(args[0]) {
+        Some(group) => {
+            group.set(args[1] as u32);
+            0
+        }
+        None => encode(Err(KernelError::NotFound)),
+    }
+}
+
+fn sys_event_group_clear(args: [usize; 3]) -> usize {
+    match get_event_group(args[0]) {
+        Some(group) => {
+            group.clear(args[1] as u32);
+            0
+        }
+        None => encode(Err(KernelError::NotFound)),
+    }
+}
+
+/// Returns the flags of the group, none are set in a group which is not registered.
+fn sys_event_group_get(args: [usize; 3]) -> usize {
+    get_event_group(args[0]).map_or(0, |group| group.get() as usize)
+}
+
+/// Returns success along with the flags if the wait is satisfied right away, else makes the task
+/// wait and returns `Empty`.
+fn sys_event_group_wait(args: [usize; 3]) -> usize {
+    let (group, wait) = match (get_event_group(args[0]), get_blocking_args(args[1])) {
+        (Some(group), Some(wait)) => (group, wait),
+        (Some(_), None) => return encode(Err(KernelError::InvalidArgument)),
+        _ => return encode(Err(KernelError::NotFound)),
+    };
+    let res = group.begin_wait(
+        wait.value as u32,
+        wait.options & WAIT_ALL != 0,
+        wait.options & AUTO_CLEAR != 0,
+        wait.get_timeout_time(),
+    );
+    match res {
+        Ok(Some(flags)) => {
+            wait.value = flags as usize;
+            encode_flag(Ok(true))
+        }
+        Ok(None) => {
+            preempt();
+            encode_flag(Ok(false))
+        }
+        Err(err) => encode(Err(err)),
+    }
+}
+
+fn sys_event_group_wait_end(args: [usize; 3]) -> usize {
+    let (group, wait) = match (get_event_group(args[0]), get_blocking_args(args[1])) {
+        (Some(group), Some(wait)) => (group, wait),
+        (Some(_), None) => return encode(Err(KernelError::InvalidArgument)),
+        _ => return encode(Err(KernelError::NotFound)),
+    };
+    encode(group.end_wait().map(|flags| wait.value = flags as usize))
+}
+
+/// Returns the counting semaphore registered at `address`.
+fn get_counting_semaphore(address: usize) -> Option<&'static CountingSemaphore> {
+    match get_object(address) {
+        Some(KernelObject::CountingSemaphore) => {
+            Some(unsafe { &*(address as *const CountingSemaphore) })
+        }
+        _ => None,
+    }
+}
+
+/// Returns the tokens left in the semaphore, none are left in one which is not registered.
+fn sys_counting_semaphore_count(args: [usize; 3]) -> usize {
+    get_counting_semaphore(args[0]).map_or(0, |semaphore| semaphore.count() as usize)
+}
+
+fn sys_counting_semaphore_try_acquire(args: [usize; 3]) -> usize {
+    match get_counting_semaphore(args[0]) {
+        Some(semaphore) => encode_flag(Ok(semaphore.try_acquire())),
+        None => encode(Err(KernelError::NotFound)),
+    }
+}
+
+/// Returns success if a token was taken right away, else makes the task wait until the time in
+/// `args[1]` and `args[2]` and returns `Empty`.
+fn sys_counting_semaphore_acquire(args: [usize; 3]) -> usize {
+    let semaphore = match get_counting_semaphore(args[0]) {
+        Some(semaphore) => semaphore,
+        None => return encode(Err(KernelError::NotFound)),
+    };
+    let timeout_time = Some(join_ticks(args[1], args[2])).filter(|&time| time != NO_TIMEOUT);
+    match semaphore.begin_acquire(timeout_time) {
+        Ok(true) => encode_flag(Ok(true)),
+        Ok(false) => {
+            preempt();
+            encode_flag(Ok(false))
+        }
+        Err(err) => encode(Err(err)),
+    }
+}
+
+fn sys_counting_semaphore_acquire_end(args: [usize; 3]) -> usize {
+    match get_counting_semaphore(args[0]) {
+        Some(semaphore) => encode(semaphore.end_acquire()),
+        None => encode(Err(KernelError::NotFound)),
+    }
+}
+
+fn sys_counting_semaphore_release(args: [usize; 3]) -> usize {
+    match get_counting_semaphore(args[0]) {
+        Some(semaphore) => encode(semaphore.release()),
+        None => encode(Err(KernelError::NotFound)),
+    }
+}
+
+/// Returns the number of messages held by the queue, none are held by one which is not
+/// registered.
+fn sys_queue_len(args: [usize; 3]) -> usize {
+    match get_object(args[0]) {
+        Some(KernelObject::Queue(ops)) => unsafe { (ops.len)(args[0]) },
+        _ => 0,
+    }
+}
+
+/// As the table can not be generic over the message type, the message is sent through the
+/// routine of its type recorded on registration. The task passes the queue and the arguments of
+/// the send, whose value is the address of the message, which is moved into the queue. Returns
+/// success if it was sent right away, else makes the task wait for room and returns `Empty`.
+fn sys_queue_send(args: [usize; 3]) -> usize {
+    match (get_object(args[0]), get_blocking_args(args[1])) {
+        (Some(KernelObject::Queue(ops)), Some(send))
+            if is_task_value(send.value, ops.size, ops.align) =>
+        {
+            let block = send.options & BLOCK != 0;
+            let res = unsafe { (ops.send)(args[0], send.value, block, send.get_timeout_time()) };
+            wait_on_queue(res)
+        }
+        (Some(KernelObject::Queue(_)), _) => encode(Err(KernelError::InvalidArgument)),
+        _ => encode(Err(KernelError::NotFound)),
+    }
+}
+
+/// The task passes the queue and the arguments of the receive, whose value is the address the
+/// message is written to. Returns success if a message was received right away, else makes the
+/// task wait for one and returns `Empty`.
+fn sys_queue_receive(args: [usize; 3]) -> usize {
+    match (get_object(args[0]), get_blocking_args(args[1])) {
+        (Some(KernelObject::Queue(ops)), Some(receive))
+            if is_task_value(receive.value, ops.size, ops.align) =>
+        {
+            let block = receive.options & BLOCK != 0;
+            let timeout_time = receive.get_timeout_time();
+            let res = unsafe { (ops.receive)(args[0], receive.value, block, timeout_time) };
+            wait_on_queue(res)
+        }
+        (Some(KernelObject::Queue(_)), _) => encode(Err(KernelError::InvalidArgument)),
+        _ => encode(Err(KernelError::NotFound)),
+    }
+}
+
+/// Encodes the result of a send or receive, switching the task out if it has to wait.
+fn wait_on_queue(res: Result<bool, KernelError>) -> usize {
+    if let Ok(false) = res {
+        preempt();
+    }
+    encode_flag(res)
+}
+
+fn sys_queue_wait_end(args: [usize; 3]) -> usize {
+    match get_object(args[0]) {
+        Some(KernelObject::Queue(ops)) => {
+            unsafe { (ops.end_wait)(args[0]) };
+            0
+        }
+        _ => encode(Err(KernelError::NotFound)),
+    }
+}
+
+#[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
+fn sys_event_enable(args: [usize; 3]) -> usize {
+    encode(events::enable(args[0] as _))
+}
+
+#[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
+fn sys_event_disable(args: [usize; 3]) -> usize {
+    encode(events::disable(args[0] as _))
+}
+
+#[cfg(not(any(feature = "events_32", feature = "events_16", feature = "events_64")))]
+fn sys_event_enable(_args: [usize; 3]) -> usize {
+    encode(Err(KernelError::NotFound))
+}
+
+#[cfg(not(any(feature = "events_32", feature = "events_16", feature = "events_64")))]
+fn sys_event_disable(_args: [usize; 3]) -> usize {
+    encode(Err(KernelError::NotFound))
+}
+
+/// Returns the low word of the time for `args[0]` zero, else the high word.
+#[cfg(feature = "timer")]
+fn sys_get_time(args: [usize; 3]) -> usize {
+    let [time_lo, time_hi] = split_ticks(get_time().ticks());
+    if args[0] == 0 {
+        time_lo
+    } else {
+        time_hi
+    }
+}
+
+#[cfg(not(feature = "timer"))]
+fn sys_get_time(_args: [usize; 3]) -> usize {
+    0
+}
+
+#[cfg(feature = "timer")]
+fn sys_sleep(args: [usize; 3]) -> usize {
+    sleep_until(Instant::from_ticks(join_ticks(args[0], args[1])));
+    0
+}
+
+#[cfg(not(feature = "timer"))]
+fn sys_sleep(_args: [usize; 3]) -> usize {
+    encode(Err(KernelError::NotFound))
+}
+
+#[cfg(feature = "timer")]
+fn sys_timer_start(args: [usize; 3]) -> usize {
+    encode(soft_timer::start(args[0]))
+}
+
+#[cfg(feature = "timer")]
+fn sys_timer_stop(args: [usize; 3]) -> usize {
+    encode(soft_timer::stop(args[0]))
+}
+
+#[cfg(feature = "timer")]
+fn sys_timer_restart(args: [usize; 3]) -> usize {
+    encode(soft_timer::restart(args[0]))
+}
+
+#[cfg(feature = "timer")]
+fn sys_timer_is_active(args: [usize; 3]) -> usize {
+    encode_flag(soft_timer::is_active(args[0]))
+}
+
+#[cfg(not(feature = "timer"))]
+fn sys_timer_is_active(_args: [usize; 3]) -> usize {
+    encode(Err(KernelError::NotFound))
+}
+
+#[cfg(not(feature = "timer"))]
+fn sys_timer_start(_args: [usize; 3]) -> usize {
+    encode(Err(KernelError::NotFound))
+}
+
+#[cfg(not(feature = "timer"))]
+fn sys_timer_stop(_args: [usize; 3]) -> usize {
+    encode(Err(KernelError::NotFound))
+}
+
+#[cfg(not(feature = "timer"))]
+fn sys_timer_restart(_args: [usize; 3]) -> usize {
+    encode(Err(KernelError::NotFound))
+}
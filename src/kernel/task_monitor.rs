@@ -1,22 +1,29 @@
 use core::cell::RefCell;
 
 use crate::kernel::timer::get_time;
-use crate::priv_execute;
 use crate::system::scheduler::*;
 use crate::system::task_monitor::TaskMonitor;
-use crate::utils::arch::is_privileged;
+use crate::system::time::Duration;
+use crate::utils::arch::get_msb;
 use crate::utils::arch::{critical_section, Mutex};
-use crate::KernelError;
+
+#[cfg(feature = "system_logger")]
+use crate::kernel::logging;
+#[cfg(feature = "system_logger")]
+use crate::system::system_logger::LogEventType;
 
 static TASK_MONITOR: Mutex<RefCell<TaskMonitor>> = Mutex::new(RefCell::new(TaskMonitor::new()));
 
-pub fn set_deadline(tid: TaskId, deadline: u32) {
+/// Sets the deadline of task `tid` to `deadline` from now, returns the absolute deadline in ticks.
+pub fn set_deadline(tid: TaskId, deadline: Duration) -> u64 {
+    let deadline = (get_time() + deadline).ticks();
     critical_section(|cs_token| {
         TASK_MONITOR
             .borrow(cs_token)
             .borrow_mut()
-            .set_deadline(tid, get_time() + deadline);
-    })
+            .set_deadline(tid, deadline);
+    });
+    deadline
 }
 
 pub fn set_handler(handler: fn()) {
@@ -37,11 +44,34 @@ pub fn clear_deadline(tid: TaskId) {
     })
 }
 
-pub fn sweep_deadlines() {
+/// Returns the number of ticks until the earliest pending deadline, if there is one.
+pub(crate) fn get_next_deadline(curr_time: u64) -> Option<u64> {
     critical_section(|cs_token| {
         TASK_MONITOR
             .borrow(cs_token)
-            .borrow_mut()
-            .sweep_deadlines(get_time());
+            .borrow()
+            .get_next_deadline(curr_time)
     })
 }
+
+/// Called on every tick. Reports the tasks whose deadline has expired and calls the deadline exceed
+/// handler for each of them. Each miss is reported once, the Scheduler keeps the deadline.
+pub fn sweep_deadlines() {
+    let curr_time = get_time();
+    let (mut expired, handler) = critical_section(|cs_token| {
+        let monitor = &mut TASK_MONITOR.borrow(cs_token).borrow_mut();
+        (monitor.sweep_deadlines(curr_time.ticks()), monitor.get_handler())
+    });
+    while let Some(tid) = get_msb(expired) {
+        expired &= !(1 << tid);
+        #[cfg(feature = "system_logger")]
+        {
+            if logging::get_deadline_expired() {
+                logging::report(LogEventType::DeadlineExpired(tid as TaskId, curr_time));
+            }
+        }
+        if let Some(handler) = handler {
+            handler();
+        }
+    }
+}
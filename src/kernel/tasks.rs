@@ -4,12 +4,25 @@
 
 use core::cell::RefCell;
 
+use crate::kernel::syscall::{decode, split_mask, syscall, Syscall};
+#[cfg(feature = "interrupts")]
+use crate::kernel::interrupts;
+#[cfg(feature = "mpu")]
+use crate::kernel::mpu;
 use crate::priv_execute;
+use crate::system::resource::is_any_locked;
 use crate::system::scheduler::*;
-use crate::utils::arch::is_privileged;
+use crate::utils::arch::{get_cycles, is_privileged, start_cycle_counter};
 use crate::utils::arch::{critical_section, set_pendsv, Mutex};
 use crate::KernelError;
 
+#[cfg(feature = "task_monitor")]
+use crate::kernel::task_monitor::clear_deadline;
+#[cfg(feature = "timer")]
+use crate::{
+    kernel::{syscall::split_ticks, timer::get_time},
+    system::time::{Duration, Instant},
+};
 #[cfg(feature = "system_logger")]
 use crate::kernel::logging;
 #[cfg(feature = "system_logger")]
@@ -22,8 +35,22 @@ pub(crate) static TaskManager: Mutex<RefCell<Scheduler>> =
 
 /// Initializes the Kernel scheduler and creates the idle task, a task that puts the CPU to sleep in a loop.
 /// The idle task is created with zero priority; hence, it is only executed when no other task is in Ready state.
-pub fn init(init_handler: fn(cxt: &Context) -> Result<(), KernelError>) -> Result<(), KernelError> {
-    critical_section(|cs_token| TaskManager.borrow(cs_token).borrow_mut().init(init_handler))
+/// With the `task_monitor` feature, `policy` selects between fixed priority and earliest deadline first scheduling.
+pub fn init(
+    #[cfg(feature = "task_monitor")] policy: SchedulingPolicy,
+    init_handler: fn(cxt: &Context) -> Result<(), KernelError>,
+) -> Result<(), KernelError> {
+    #[cfg(feature = "mpu")]
+    mpu::init();
+    start_cycle_counter();
+    critical_section(|cs_token| {
+        let handler = &mut TaskManager.borrow(cs_token).borrow_mut();
+        #[cfg(feature = "task_monitor")]
+        {
+            handler.policy = policy;
+        }
+        handler.init(init_handler)
+    })
 }
 
 /// Starts the Kernel scheduler, which starts scheduling tasks on the CPU.
@@ -35,47 +62,161 @@ pub fn start_kernel() -> ! {
     }
 }
 
-#[cfg(feature = "task_monitor")]
-/// Create a new task with the configuration set as arguments passed.
+/// Create a new task with the configuration set as arguments passed and return its TaskId.
+/// The TaskId equals `priority` unless another task already holds it, in which case the lowest
+/// free TaskId is assigned. Tasks of the same priority are scheduled round-robin. Priority zero
+/// is reserved for the idle task.
+/// With the `task_monitor` feature, `deadline` is the deadline of the task relative to each of
+/// its releases, zero if it has none.
+/// With the `fpu` feature, `uses_fpu` declares whether the task uses the floating point unit.
+/// Such tasks start with a clean floating point context and need a larger stack to hold it.
 pub fn create_task(
-    priority: TaskId,
-    deadline: u32,
+    priority: Priority,
+    #[cfg(feature = "task_monitor")] deadline: Duration,
     stack: &mut [u32],
-    handler_fn: fn(int) -> !,
-) -> Result<(), KernelError> {
+    #[cfg(feature = "fpu")] uses_fpu: bool,
+    handler_fn: fn(ContextType) -> !,
+) -> Result<TaskId, KernelError> {
+    #[cfg(not(feature = "fpu"))]
+    let uses_fpu = false;
     priv_execute!({
         critical_section(|cs_token| {
             TaskManager.borrow(cs_token).borrow_mut().create_task(
                 priority as usize,
+                #[cfg(feature = "task_monitor")]
                 deadline,
                 stack,
+                uses_fpu,
+                true,
                 handler_fn,
             )
         })
     })
 }
 
-#[cfg(not(feature = "task_monitor"))]
-/// Create a new task with the configuration set as arguments passed.
-pub fn create_task(
-    priority: TaskId,
+/// Create a new task which runs in unprivileged Thread mode, otherwise same as `create_task`.
+/// Such a task can not access the System Control Block or mask interrupts, the Kernel routines
+/// it uses are executed on its behalf through system calls. Unprivileged execution is only
+/// supported on ARMv7-M and the host simulation, on the other ports the task runs privileged.
+pub fn create_unprivileged_task(
+    priority: Priority,
+    #[cfg(feature = "task_monitor")] deadline: Duration,
     stack: &mut [u32],
+    #[cfg(feature = "fpu")] uses_fpu: bool,
     handler_fn: fn(ContextType) -> !,
-) -> Result<(), KernelError> {
+) -> Result<TaskId, KernelError> {
+    #[cfg(not(feature = "fpu"))]
+    let uses_fpu = false;
     priv_execute!({
         critical_section(|cs_token| {
             TaskManager.borrow(cs_token).borrow_mut().create_task(
                 priority as usize,
+                #[cfg(feature = "task_monitor")]
+                deadline,
                 stack,
+                uses_fpu,
+                false,
                 handler_fn,
             )
         })
     })
 }
+/// Create a new periodic task and return its TaskId. The task is released every `period`,
+/// first `offset` after its creation. The release times are fixed relative to the first
+/// one, so they do not drift however late the task runs. A release which finds the task still
+/// active is counted as an overrun, see `get_overruns`. With the `task_monitor` feature, each
+/// release is given `deadline` (zero for none), and missing it raises the deadline exceed
+/// handler. The remaining arguments are the ones of `create_task`.
+#[cfg(feature = "timer")]
+pub fn create_periodic_task(
+    priority: Priority,
+    period: Duration,
+    offset: Duration,
+    deadline: Duration,
+    stack: &mut [u32],
+    #[cfg(feature = "fpu")] uses_fpu: bool,
+    handler_fn: fn(ContextType) -> !,
+) -> Result<TaskId, KernelError> {
+    #[cfg(not(feature = "fpu"))]
+    let uses_fpu = false;
+    create_periodic(
+        priority, period, offset, deadline, stack, uses_fpu, true, handler_fn,
+    )
+}
+
+/// Create a new periodic task which runs in unprivileged Thread mode, otherwise same as
+/// `create_periodic_task`. See `create_unprivileged_task` for what such a task can not do.
+#[cfg(feature = "timer")]
+pub fn create_unprivileged_periodic_task(
+    priority: Priority,
+    period: Duration,
+    offset: Duration,
+    deadline: Duration,
+    stack: &mut [u32],
+    #[cfg(feature = "fpu")] uses_fpu: bool,
+    handler_fn: fn(ContextType) -> !,
+) -> Result<TaskId, KernelError> {
+    #[cfg(not(feature = "fpu"))]
+    let uses_fpu = false;
+    create_periodic(
+        priority, period, offset, deadline, stack, uses_fpu, false, handler_fn,
+    )
+}
+
+/// Creates a periodic task, privileged or not, for `create_periodic_task` and
+/// `create_unprivileged_periodic_task`.
+#[cfg(feature = "timer")]
+#[allow(clippy::too_many_arguments)]
+fn create_periodic(
+    priority: Priority,
+    period: Duration,
+    offset: Duration,
+    deadline: Duration,
+    stack: &mut [u32],
+    uses_fpu: bool,
+    privileged: bool,
+    handler_fn: fn(ContextType) -> !,
+) -> Result<TaskId, KernelError> {
+    #[cfg(not(feature = "task_monitor"))]
+    let _ = deadline;
+    if period.is_zero() {
+        return Err(KernelError::InvalidArgument);
+    }
+    priv_execute!({
+        let first_release = get_time() + offset;
+        critical_section(|cs_token| {
+            let handler = &mut TaskManager.borrow(cs_token).borrow_mut();
+            let tid = handler.create_task(
+                priority as usize,
+                #[cfg(feature = "task_monitor")]
+                deadline,
+                stack,
+                uses_fpu,
+                privileged,
+                handler_fn,
+            )?;
+            handler.set_period(tid as usize, period.ticks(), first_release.ticks());
+            Ok(tid)
+        })
+    })
+}
+
+/// Returns the number of releases of the periodic task `tid` which found it still active, i.e.
+/// the number of activations it missed.
+#[cfg(feature = "timer")]
+pub fn get_overruns(tid: TaskId) -> u32 {
+    critical_section(|cs_token| {
+        TaskManager
+            .borrow(cs_token)
+            .borrow()
+            .get_overruns(tid as usize)
+    })
+}
+
 /// This function is called from both privileged and unprivileged context.
 /// Hence if the function is called from privileged context, then `preempt()` is called.
-/// Else, the `svc_call()` is executed, this function creates the SVC exception.
-/// And the SVC handler calls schedule again. Thus, the permission level is raised to privileged via the exception.
+/// Else, `preempt()` raises a system call, which creates the SVC exception.
+/// And the SVC handler calls `preempt()` again. Thus, the permission level is raised to privileged via the exception.
 pub fn schedule(is_preemptive: bool) {
     if is_preemptive {
         preempt();
@@ -83,48 +224,78 @@ pub fn schedule(is_preemptive: bool) {
 }
 
 pub fn preempt() {
+    if !is_privileged() {
+        syscall(Syscall::Preempt, [0; 3]);
+        return;
+    }
     set_pendsv();
 }
 
-/// Returns the TaskId of the currently running task in the kernel.
-pub fn get_curr_tid() -> TaskId {
-    critical_section(|cs_token| TaskManager.borrow(cs_token).borrow().curr_tid as TaskId)
-}
-
-/// The Kernel blocks the tasks mentioned in `tasks_mask`.
-pub fn block_tasks(tasks_mask: BooleanVector) {
-    #[cfg(feature = "system_logger")]
-    {
-        if logging::get_block_tasks() {
-            logging::report(LogEventType::BlockTasks(tasks_mask));
+/// Body of the PendSV handler, which does the actual context switch in the Kernel. The run time
+/// since the previous call is accounted to the running task. If the next task differs from it,
+/// the context of the running task is saved and the one of the next task loaded, which starts a
+/// new time slice. With the `mpu` feature, the regions of the next task are programmed first, and
+/// with the `interrupts` feature, the hand-offs of the interrupts which released it complete.
+#[inline(always)]
+pub(crate) fn context_switch() {
+    let overflowed = critical_section(|cs_token| {
+        let handler = &mut TaskManager.borrow(cs_token).borrow_mut();
+        let curr_tid: usize = handler.curr_tid;
+        let cycles = get_cycles();
+        handler.account_run_time(cycles);
+        // A task which overflowed its stack is not scheduled again, except for the idle task as
+        // there would be nothing left to run.
+        let overflowed = match handler.task_control_blocks[curr_tid].as_ref() {
+            Some(curr_task) if handler.started && !curr_task.check_canary() => {
+                if curr_tid != 0 {
+                    handler.suspended_tasks |= 1 << curr_tid;
+                }
+                Some((curr_tid as TaskId, handler.overflow_handler))
+            }
+            _ => None,
+        };
+        let next_tid: usize = handler.get_next_tid();
+        if curr_tid != next_tid || (!handler.started) {
+            handler.account_switch(curr_tid, next_tid);
+            if handler.started {
+                // The context of a deleted task is dropped.
+                if let Some(curr_task) = handler.task_control_blocks[curr_tid].as_ref() {
+                    curr_task.save_context();
+                }
+            } else {
+                handler.started = true;
+            }
+            #[cfg(feature = "interrupts")]
+            interrupts::complete_handoffs(next_tid as TaskId, cycles);
+            #[cfg(feature = "mpu")]
+            mpu::load_regions(next_tid);
+            let next_task = handler.task_control_blocks[next_tid].as_ref().unwrap();
+            next_task.load_context();
+            handler.curr_tid = next_tid;
+            handler.start_slice(next_tid);
         }
+        overflowed
+    });
+    if let Some((tid, Some(handler))) = overflowed {
+        handler(tid);
     }
-    critical_section(|cs_token| {
-        TaskManager
-            .borrow(cs_token)
-            .borrow_mut()
-            .block_tasks(tasks_mask)
-    })
 }
 
-/// The Kernel unblocks the tasks mentioned in tasks_mask.
-pub fn unblock_tasks(tasks_mask: BooleanVector) {
-    #[cfg(feature = "system_logger")]
-    {
-        if logging::get_unblock_tasks() {
-            logging::report(LogEventType::UnblockTasks(tasks_mask));
-        }
+/// Returns the TaskId of the currently running task in the kernel.
+pub fn get_curr_tid() -> TaskId {
+    if !is_privileged() {
+        return syscall(Syscall::GetCurrTid, [0; 3]) as TaskId;
     }
-    critical_section(|cs_token| {
-        TaskManager
-            .borrow(cs_token)
-            .borrow_mut()
-            .unblock_tasks(tasks_mask)
-    })
+    critical_section(|cs_token| TaskManager.borrow(cs_token).borrow().curr_tid as TaskId)
 }
 
 /// The `task_exit` function is called just after a task finishes execution. It marks the current running task as finished and then schedules the next high priority task.
+/// With the `task_monitor` feature, the deadline of the task is cleared as it has been met.
 pub fn task_exit() {
+    if !is_privileged() {
+        syscall(Syscall::TaskExit, [0; 3]);
+        return;
+    }
     let is_preemptive = critical_section(|cs_token| {
         let handler = &mut TaskManager.borrow(cs_token).borrow_mut();
         let curr_tid = handler.curr_tid;
@@ -134,13 +305,136 @@ pub fn task_exit() {
                 logging::report(LogEventType::TaskExit(curr_tid as TaskId));
             }
         }
-        handler.active_tasks &= !(1 << curr_tid as u32);
+        handler.active_tasks &= !(1 << curr_tid);
+        #[cfg(feature = "task_monitor")]
+        {
+            handler.clear_deadline(curr_tid);
+            clear_deadline(curr_tid as TaskId);
+        }
         handler.is_preemptive
     });
     schedule(is_preemptive);
 }
+/// Suspends task `tid`. It is not scheduled until `resume` is called, independent of whether it
+/// is released, blocked by a resource or sleeping. A task can suspend itself.
+pub fn suspend(tid: TaskId) -> Result<(), KernelError> {
+    if !is_privileged() {
+        return decode(syscall(Syscall::Suspend, [tid as usize, 0, 0]));
+    }
+    let is_curr = critical_section(|cs_token| {
+        let handler = &mut TaskManager.borrow(cs_token).borrow_mut();
+        handler.suspend(tid as usize)?;
+        Ok(handler.curr_tid == tid as usize)
+    })?;
+    if is_curr {
+        preempt();
+    }
+    Ok(())
+}
+
+/// Resumes the suspended task `tid`, it is scheduled again once it is in ready state.
+pub fn resume(tid: TaskId) -> Result<(), KernelError> {
+    if !is_privileged() {
+        return decode(syscall(Syscall::Resume, [tid as usize, 0, 0]));
+    }
+    let is_preemptive = critical_section(|cs_token| {
+        let handler = &mut TaskManager.borrow(cs_token).borrow_mut();
+        handler.resume(tid as usize)?;
+        Ok(handler.is_preemptive)
+    })?;
+    schedule(is_preemptive);
+    Ok(())
+}
+
+/// Deletes task `tid`, freeing its TaskId for a new task. A task can delete itself, in which case
+/// this does not return. The deletion is rejected with `AccessDenied` while any resource is
+/// locked, as the ceilings on the stack of locked resources were computed with the task.
+/// A task waiting on a primitive stops waiting. The semaphores, messages and resources referring
+/// to the TaskId keep doing so, hence a new task taking the TaskId is signalled, receives and
+/// locks as the deleted one did.
+pub fn delete_task(tid: TaskId) -> Result<(), KernelError> {
+    priv_execute!({
+        let is_curr = critical_section(|cs_token| {
+            if is_any_locked() {
+                return Err(KernelError::AccessDenied);
+            }
+            let handler = &mut TaskManager.borrow(cs_token).borrow_mut();
+            handler.delete_task(tid as usize)?;
+            Ok(handler.curr_tid == tid as usize)
+        })?;
+        #[cfg(feature = "task_monitor")]
+        clear_deadline(tid);
+        #[cfg(feature = "mpu")]
+        mpu::remove_task(tid);
+        if is_curr {
+            preempt();
+        }
+        Ok(())
+    })
+}
+
+/// Changes the priority of task `tid` to `priority`. The task keeps its TaskId, so the semaphores,
+/// messages and resources referring to it need no update, and resource ceilings follow the new
+/// priority. The change is rejected with `AccessDenied` while any resource is locked, as the
+/// tasks blocked by the locked resources were picked by the current priorities.
+pub fn set_priority(tid: TaskId, priority: Priority) -> Result<(), KernelError> {
+    priv_execute!({
+        let is_preemptive = critical_section(|cs_token| {
+            if is_any_locked() {
+                return Err(KernelError::AccessDenied);
+            }
+            let handler = &mut TaskManager.borrow(cs_token).borrow_mut();
+            handler.set_priority(tid as usize, priority as usize)?;
+            #[cfg(feature = "system_logger")]
+            {
+                if logging::get_priority_change() {
+                    logging::report(LogEventType::PriorityChange(tid, priority));
+                }
+            }
+            Ok(handler.is_preemptive)
+        })?;
+        schedule(is_preemptive);
+        Ok(())
+    })
+}
+
+/// Blocks the currently running task for `duration`, other tasks run meanwhile.
+#[cfg(feature = "timer")]
+pub fn sleep(duration: Duration) {
+    sleep_until(get_time() + duration);
+}
+
+/// Blocks the currently running task until the Kernel timer reaches `time`, as returned by
+/// `timer::get_time`. Returns right away if `time` has already been reached, or if it is called
+/// from the idle task, which can not sleep.
+#[cfg(feature = "timer")]
+pub fn sleep_until(time: Instant) {
+    if !is_privileged() {
+        let [time_lo, time_hi] = split_ticks(time.ticks());
+        syscall(Syscall::Sleep, [time_lo, time_hi, 0]);
+        return;
+    }
+    let slept = critical_section(|cs_token| {
+        let handler = &mut TaskManager.borrow(cs_token).borrow_mut();
+        let curr_tid = handler.curr_tid;
+        if curr_tid == 0 || get_time() >= time {
+            return false;
+        }
+        handler.sleep(curr_tid, time.ticks());
+        true
+    });
+    if slept {
+        preempt();
+    }
+}
+
 /// The Kernel releases the tasks in the `task_mask`, these tasks transition from the waiting to the ready state.
 pub fn release(tasks_mask: BooleanVector) {
+    if !is_privileged() {
+        let [mask_lo, mask_hi] = split_mask(tasks_mask);
+        syscall(Syscall::Release, [mask_lo, mask_hi, 0]);
+        return;
+    }
     #[cfg(feature = "system_logger")]
     {
         if logging::get_release() {
@@ -166,6 +460,51 @@ pub fn enable_preemption() {
     })
 }
 
+/// Sets the number of ticks a task runs before the next ready task of the same priority takes
+/// its turn. Zero disables round-robin scheduling, then a task keeps running until it blocks or exits.
+pub fn set_time_slice(ticks: u32) {
+    critical_section(|cs_token| {
+        TaskManager.borrow(cs_token).borrow_mut().time_slice = ticks;
+    })
+}
+
+/// Returns the largest number of words of its stack task `tid` has used so far, as told by the
+/// pattern the stack was painted with on creation.
+pub fn stack_high_water_mark(tid: TaskId) -> Result<usize, KernelError> {
+    critical_section(|cs_token| {
+        let handler = TaskManager.borrow(cs_token).borrow();
+        match handler.task_control_blocks.get(tid as usize) {
+            Some(Some(tcb)) => Ok(tcb.get_high_water_mark()),
+            _ => Err(KernelError::NotFound),
+        }
+    })
+}
+
+/// Returns the run time, context switch and preemption counts of task `tid`. The time spent in
+/// the idle task is that of TaskId zero.
+pub fn stats(tid: TaskId) -> Result<TaskStats, KernelError> {
+    critical_section(|cs_token| {
+        TaskManager
+            .borrow(cs_token)
+            .borrow()
+            .get_stats(tid as usize)
+    })
+}
+
+/// Returns the CPU utilisation in percent, i.e. the share of the run time not spent in the idle task.
+pub fn utilisation() -> u32 {
+    critical_section(|cs_token| TaskManager.borrow(cs_token).borrow().get_utilisation())
+}
+
+/// Sets the handler which is called with the TaskId of a task whose stack overflowed. The
+/// overflow is detected on the next context switch, by the canary at the bottom of the stack
+/// being overwritten, and the task is suspended.
+pub fn set_stack_overflow_handler(handler: fn(TaskId)) {
+    critical_section(|cs_token| {
+        TaskManager.borrow(cs_token).borrow_mut().overflow_handler = Some(handler);
+    })
+}
+
 /// Disable preemptive scheduling
 pub fn disable_preemption() {
     critical_section(|cs_token| {
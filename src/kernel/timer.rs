@@ -1,13 +1,40 @@
 //! Manages the kernel timer.
 use core::cell::RefCell;
 
-use crate::priv_execute;
-use crate::system::scheduler::*;
-use crate::utils::arch::is_privileged;
-use crate::utils::arch::{critical_section, Mutex, Peripherals, SystClkSource};
-use crate::KernelError;
+use crate::config::TICKLESS_MIN_TICKS;
+use crate::kernel::soft_timer::{get_next_expiry, skip_ticks, sweep_timers};
+use crate::kernel::syscall::{join_ticks, syscall, Syscall};
+use crate::kernel::tasks::{schedule, TaskManager};
+use crate::system::time::Instant;
+use crate::utils::arch::{critical_section, is_privileged, Mutex, Peripherals, SystClkSource};
+use crate::utils::arch::{resume_ticks, suppress_ticks, wait_for_interrupt};
 
-static SystemTimer: Mutex<RefCell<u32>> = Mutex::new(RefCell::new(0));
+#[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
+use crate::kernel::events::{get_next_dispatch, sweep_event_table};
+
+#[cfg(feature = "task_monitor")]
+use crate::kernel::task_monitor::{get_next_deadline, sweep_deadlines};
+
+#[cfg(feature = "system_logger")]
+use {crate::kernel::logging, crate::system::system_logger::LogEventType};
+
+/// The number of ticks since the timer was started.
+static SystemTimer: Mutex<RefCell<u64>> = Mutex::new(RefCell::new(0));
+
+/// Configuration of the tickless idle mode.
+struct Tickless {
+    enabled: bool,
+    /// Called with the number of ticks the CPU is about to sleep for.
+    pre_sleep: Option<fn(u32)>,
+    /// Called with the number of ticks the CPU has slept for.
+    post_sleep: Option<fn(u32)>,
+}
+
+static TicklessIdle: Mutex<RefCell<Tickless>> = Mutex::new(RefCell::new(Tickless {
+    enabled: false,
+    pre_sleep: None,
+    post_sleep: None,
+}));
 
 // TODO: on timer expire raise an event or make a log entry
 
@@ -18,12 +45,157 @@ pub fn update_time() {
     })
 }
 
-pub fn get_time() -> u32 {
-    critical_section(|cs_token| return *SystemTimer.borrow(cs_token).borrow())
+/// Body of the SysTick handler. It dispatches the due events, updates the time, runs the expired
+/// software timers, releases the due periodic tasks, wakes up the sleeping tasks whose wake-up
+/// time has been reached and accounts the tick to the time slice of the running task. Finally
+/// `schedule()` dispatches any higher priority task, or the next task of the same priority.
+pub(crate) fn sys_tick() {
+    #[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
+    sweep_event_table();
+
+    update_time();
+
+    sweep_timers();
+
+    #[cfg(feature = "task_monitor")]
+    sweep_deadlines();
+
+    let curr_time = get_time().ticks();
+    let (released, is_preemptive) = critical_section(|cs_token| {
+        let handler = &mut TaskManager.borrow(cs_token).borrow_mut();
+        let released = handler.release_periodic(curr_time);
+        handler.wake_tasks(curr_time);
+        handler.tick_slice();
+        (released, handler.is_preemptive)
+    });
+    #[cfg(not(feature = "system_logger"))]
+    let _ = released;
+    #[cfg(feature = "system_logger")]
+    {
+        if released != 0 && logging::get_release() {
+            logging::report(LogEventType::ReleaseTasks(released));
+        }
+    }
+    schedule(is_preemptive);
+}
+
+/// Adds `ticks` ticks, which passed while the tick was suppressed, to the time.
+fn advance_time(ticks: u32) {
+    critical_section(|cs_token| {
+        let time = &mut *SystemTimer.borrow(cs_token).borrow_mut();
+        *time += ticks as u64;
+    })
+}
+
+/// Returns the number of ticks until the next tick with work to do: dispatching an event,
+/// expiring a software timer, checking a deadline, waking up a sleeping task or releasing a periodic task.
+fn get_next_work(curr_time: u64) -> Option<u64> {
+    let next = critical_section(|cs_token| {
+        TaskManager
+            .borrow(cs_token)
+            .borrow()
+            .get_next_wake_up(curr_time)
+    });
+    let next = earliest(next, get_next_expiry());
+    #[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
+    let next = earliest(next, get_next_dispatch(curr_time));
+    #[cfg(feature = "task_monitor")]
+    let next = earliest(next, get_next_deadline(curr_time));
+    next
+}
+
+fn earliest(ticks: Option<u64>, other: Option<u64>) -> Option<u64> {
+    match (ticks, other) {
+        (Some(ticks), Some(other)) => Some(ticks.min(other)),
+        (ticks, other) => ticks.or(other),
+    }
+}
+
+/// Body of the idle task. In tickless mode, when no other task is ready, the tick is suppressed
+/// until the next tick with work to do and the CPU sleeps, the ticks slept through are added to
+/// the time on wake-up. Otherwise the CPU sleeps until the next interrupt.
+pub(crate) fn idle() {
+    let slept = critical_section(|cs_token| {
+        let (enabled, pre_sleep, post_sleep) = {
+            let tickless = TicklessIdle.borrow(cs_token).borrow();
+            (tickless.enabled, tickless.pre_sleep, tickless.post_sleep)
+        };
+        if !enabled {
+            return false;
+        }
+        let is_idle = TaskManager.borrow(cs_token).borrow().get_ready_tasks() & !1 == 0;
+        if !is_idle {
+            return false;
+        }
+        let ticks = match get_next_work(get_time().ticks()) {
+            Some(ticks) => ticks.min(u32::MAX as u64) as u32,
+            None => u32::MAX,
+        };
+        if ticks < TICKLESS_MIN_TICKS {
+            return false;
+        }
+        let ticks = suppress_ticks(ticks);
+        if ticks == 0 {
+            return false;
+        }
+        if let Some(pre_sleep) = pre_sleep {
+            pre_sleep(ticks);
+        }
+        // Interrupts stay disabled, a pending one still wakes the CPU and is taken on leaving the critical section.
+        wait_for_interrupt();
+        let passed = resume_ticks();
+        advance_time(passed);
+        skip_ticks(passed as u64);
+        if let Some(post_sleep) = post_sleep {
+            post_sleep(passed);
+        }
+        true
+    });
+    if !slept {
+        wait_for_interrupt();
+    }
+}
+
+/// Enables or disables the tickless idle mode. In tickless mode, the idle task stops the tick
+/// while the CPU sleeps until the next event, software timer, deadline, wake-up or periodic
+/// release is due, or until any other interrupt. Ports whose timer can not be reprogrammed keep ticking.
+pub fn set_tickless(enabled: bool) {
+    critical_section(|cs_token| {
+        TicklessIdle.borrow(cs_token).borrow_mut().enabled = enabled;
+    })
+}
+
+/// Sets the hooks called around a tickless sleep. `pre_sleep` is called with the number of ticks
+/// the CPU is going to sleep for, right before it sleeps, and can select a deeper sleep mode.
+/// `post_sleep` is called with the number of ticks which have passed, right after it woke up.
+/// Both are called with interrupts disabled.
+pub fn set_sleep_hooks(pre_sleep: fn(u32), post_sleep: fn(u32)) {
+    critical_section(|cs_token| {
+        let tickless = &mut TicklessIdle.borrow(cs_token).borrow_mut();
+        tickless.pre_sleep = Some(pre_sleep);
+        tickless.post_sleep = Some(post_sleep);
+    })
+}
+
+/// Returns the current time, i.e. the number of ticks since the timer was started.
+/// An unprivileged task reads the two words of the time in separate system calls, the low word
+/// is read again if a tick carried into the high word in between.
+pub fn get_time() -> Instant {
+    if !is_privileged() {
+        loop {
+            let time_hi = syscall(Syscall::GetTime, [1, 0, 0]);
+            let time_lo = syscall(Syscall::GetTime, [0, 0, 0]);
+            if syscall(Syscall::GetTime, [1, 0, 0]) == time_hi {
+                return Instant::from_ticks(join_ticks(time_lo, time_hi));
+            }
+        }
+    }
+    critical_section(|cs_token| Instant::from_ticks(*SystemTimer.borrow(cs_token).borrow()))
 }
 
 /// Starts the Kernel timer. Timing event manager, logging and task monitor
-/// are heavily dependent on the timer.
+/// are heavily dependent on the timer. `tick_interval` is the number of core clock cycles per
+/// tick, and has to yield `TICK_RATE_HZ` ticks per second for `Duration` conversions to hold.
 pub fn start_timer(peripherals: &mut Peripherals, tick_interval: u32) {
     let syst = &mut peripherals.SYST;
     syst.set_clock_source(SystClkSource::Core);
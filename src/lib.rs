@@ -1,5 +1,5 @@
-//! A safe and lightweight real-time Kernel written in Rust. Currently the kernel runs on cortex-m3/m4
-//! based microcontrollers, work is in progress on extending this to other platforms.
+//! A safe and lightweight real-time Kernel written in Rust. Currently the kernel runs on cortex-m0/m0+/m3/m4
+//! and rv32imac based microcontrollers, work is in progress on extending this to other platforms.
 //!
 //! ## Usage
 //!
@@ -10,23 +10,29 @@
 //! harsark = { version = "0.3.5" }
 //! ```
 
-#![no_std]
-#![feature(asm)]
-#![feature(const_fn)]
-#![feature(lang_items)]
-#![feature(const_if_match)]
-#![feature(const_loop)]
-#![feature(const_generics)]
+#![cfg_attr(not(feature = "std"), no_std)]
+// The gates of the toolchain the targets are built with, since stabilized or removed. The host
+// port is built with the toolchain pinned in rust-toolchain.toml, which rejects them.
+#![cfg_attr(not(feature = "std"), feature(asm))]
+#![cfg_attr(not(feature = "std"), feature(const_fn))]
+#![cfg_attr(not(feature = "std"), feature(lang_items))]
+#![cfg_attr(not(feature = "std"), feature(const_if_match))]
+#![cfg_attr(not(feature = "std"), feature(const_loop))]
+#![cfg_attr(not(feature = "std"), feature(const_generics))]
 // #![feature(const_fn_fn_ptr_basics)]
 #![feature(negative_impls)]
+#![cfg_attr(target_arch = "riscv32", feature(global_asm))]
 
-#[cfg(feature = "alloc")]
+#[cfg(all(feature = "alloc", not(feature = "std"), target_arch = "arm"))]
 pub extern crate alloc;
-#[cfg(feature = "alloc")]
+#[cfg(all(feature = "alloc", not(feature = "std"), target_arch = "arm"))]
 extern crate alloc_cortex_m;
 
+#[cfg(all(not(feature = "std"), target_arch = "arm"))]
 #[allow(non_upper_case_globals)]
 extern crate cortex_m_rt;
+#[cfg(all(not(feature = "std"), target_arch = "riscv32"))]
+extern crate riscv_rt;
 
 mod config;
 mod kernel;
@@ -45,19 +51,69 @@ pub mod helpers {
 /// Kernel routines which assist in Event management.
 #[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
 pub mod events {
+    pub use crate::kernel::events::delete;
     pub use crate::kernel::events::disable;
     pub use crate::kernel::events::enable;
     pub use crate::kernel::events::new;
+    pub use crate::kernel::events::new_with_offset;
+    pub use crate::kernel::events::set_threshold;
+    pub use crate::system::action::{BroadcastAction, EventAction, PostAction};
+    pub use crate::system::event::EventId;
 }
 
 /// Kernel timer management.
 #[cfg(feature = "timer")]
 pub mod timer {
+    pub use crate::config::TICK_RATE_HZ;
+    pub use crate::kernel::timer::get_time;
+    pub use crate::kernel::timer::set_sleep_hooks;
+    pub use crate::kernel::timer::set_tickless;
     pub use crate::kernel::timer::start_timer;
+    pub use crate::system::time::{Duration, Instant};
+    /// The core peripherals `start_timer` takes, the CLINT machine timer on RISC-V.
+    #[cfg(target_arch = "riscv32")]
+    pub use crate::utils::arch::Peripherals;
 }
+/// Kernel routines which manage one-shot and auto-reload software timers.
+#[cfg(feature = "timer")]
+pub mod soft_timer {
+    pub use crate::kernel::soft_timer::create;
+    pub use crate::kernel::soft_timer::delete;
+    pub use crate::kernel::soft_timer::is_active;
+    pub use crate::kernel::soft_timer::restart;
+    pub use crate::kernel::soft_timer::set_period;
+    pub use crate::kernel::soft_timer::start;
+    pub use crate::kernel::soft_timer::stop;
+    pub use crate::system::soft_timer::{TimerId, TimerMode};
+}
+
+/// Kernel routines which bind interrupts to actions, on Cortex-M. A bound interrupt is taken by
+/// the default interrupt handler defined by the Kernel, which passes the others on to the
+/// handler set by the application.
+#[cfg(feature = "interrupts")]
+pub mod interrupts {
+    pub use crate::kernel::interrupts::bind;
+    pub use crate::kernel::interrupts::set_default_handler;
+    pub use crate::kernel::interrupts::stats;
+    pub use crate::kernel::interrupts::unbind;
+    pub use crate::system::action::{BroadcastAction, EventAction, PostAction};
+    pub use crate::system::interrupt::{IrqNumber, IrqStats};
+}
+
+/// Kernel routines which isolate the memory of unprivileged tasks through the MPU.
+#[cfg(feature = "mpu")]
+pub mod mpu {
+    pub use crate::kernel::mpu::set_fault_handler;
+    pub use crate::kernel::mpu::share;
+    pub use crate::kernel::mpu::share_region;
+}
+
 /// Kernel primitives which assist application development.
 pub mod primitives {
+    pub use crate::system::counting_semaphore::CountingSemaphore;
+    pub use crate::system::event_group::{EventGroup, WaitMode};
     pub use crate::system::message::Message;
+    pub use crate::system::queue::{OverflowPolicy, Queue};
     pub use crate::system::resource::Resource;
     pub use crate::system::semaphore::Semaphore;
 }
@@ -67,14 +123,30 @@ pub mod tasks {
     #[cfg(feature = "task_monitor")]
     pub use crate::kernel::task_monitor::set_handler as set_deadline_exceed_handler;
     pub use crate::kernel::tasks::create_task;
+    pub use crate::kernel::tasks::create_unprivileged_task;
+    pub use crate::kernel::tasks::delete_task;
     pub use crate::kernel::tasks::disable_preemption;
     pub use crate::kernel::tasks::enable_preemption;
     pub use crate::kernel::tasks::get_curr_tid;
     pub use crate::kernel::tasks::init;
     pub use crate::kernel::tasks::release;
+    pub use crate::kernel::tasks::resume;
+    pub use crate::kernel::tasks::set_priority;
+    pub use crate::kernel::tasks::set_stack_overflow_handler;
+    pub use crate::kernel::tasks::set_time_slice;
+    pub use crate::kernel::tasks::stack_high_water_mark;
+    pub use crate::kernel::tasks::stats;
+    #[cfg(feature = "timer")]
+    pub use crate::kernel::tasks::{
+        create_periodic_task, create_unprivileged_periodic_task, get_overruns, sleep, sleep_until,
+    };
     pub use crate::kernel::tasks::start_kernel;
+    pub use crate::kernel::tasks::suspend;
     pub use crate::kernel::tasks::task_exit;
-    pub use crate::system::scheduler::{Context, ContextType};
+    pub use crate::kernel::tasks::utilisation;
+    #[cfg(feature = "task_monitor")]
+    pub use crate::system::scheduler::SchedulingPolicy;
+    pub use crate::system::scheduler::{Context, ContextType, Priority, TaskId, TaskStats};
 }
 
 #[cfg(feature = "system_logger")]
@@ -83,18 +155,40 @@ pub mod logging {
     pub use crate::kernel::logging::process;
     pub use crate::kernel::logging::set_all;
     pub use crate::kernel::logging::set_block_tasks;
+    pub use crate::kernel::logging::set_deadline_expired;
+    #[cfg(feature = "interrupts")]
+    pub use crate::kernel::logging::set_interrupt;
     pub use crate::kernel::logging::set_message_broadcast;
     pub use crate::kernel::logging::set_message_recieve;
+    pub use crate::kernel::logging::set_priority_change;
     pub use crate::kernel::logging::set_release;
     pub use crate::kernel::logging::set_resource_lock;
     pub use crate::kernel::logging::set_resource_unlock;
     pub use crate::kernel::logging::set_semaphore_reset;
     pub use crate::kernel::logging::set_semaphore_signal;
     pub use crate::kernel::logging::set_task_exit;
+    #[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
     pub use crate::kernel::logging::set_timer_event;
     pub use crate::kernel::logging::set_unblock_tasks;
     pub use crate::system::system_logger::LogEvent;
 }
 
-#[cfg(feature = "alloc")]
+#[cfg(all(feature = "alloc", not(feature = "std"), target_arch = "arm"))]
 pub use crate::utils::heap;
+
+/// Controls for the host simulation port, which replaces the Cortex-M port when the `std`
+/// feature is enabled. Tasks run on OS threads and interrupts are raised through these routines.
+/// Alternatively a mock `Port` is registered and the test drives the Kernel on its own thread.
+#[cfg(feature = "std")]
+pub mod sim {
+    pub use crate::system::scheduler::TaskControlBlock;
+    pub use crate::utils::arch::interrupt;
+    pub use crate::utils::arch::set_cycle_counter;
+    pub use crate::utils::arch::Peripherals;
+    pub use crate::utils::arch::Port;
+    pub use crate::utils::arch::{dispatch_syscall, register_port, run_pendsv};
+    #[cfg(feature = "timer")]
+    pub use crate::utils::arch::{run_systick, tick};
+    #[cfg(feature = "interrupts")]
+    pub use crate::utils::arch::irq;
+}
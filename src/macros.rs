@@ -1,60 +1,134 @@
 //! Macro Definitions
 
+use crate::kernel::tasks::create_task;
+use crate::system::scheduler::{ContextType, Priority, TaskId};
+use crate::KernelError;
+
+#[cfg(feature = "task_monitor")]
+use crate::system::time::Duration;
+
+#[doc(hidden)]
+pub use crate::utils::arch::is_privileged;
+
 /// The tasks must be looping infinitely and call `task_exit` whenever a particular task is done.
 /// This makes it complicated to create tasks and also might introduce undefined behavior if task_exit is not called.
-/// The `spawn` macro makes it easier to define tasks. It also defines a static variable of type TaskId,
-/// which corresponds to the task created.
+/// The `spawn` macro makes it easier to define tasks. It takes the priority of the task, the size
+/// of its stack in words and its body, a closure which is called with the task's context on
+/// every release. The stack is declared in the enclosing scope. Panics if the task can not be created.
+/// With the `fpu` feature, the task is assumed to use the floating point unit.
 ///
 /// ## Examples
 ///
-/// ```rust
-/// shared = 10;
-/// spawn!(task2, 2, stack1, shared, params, {
-///     hprintln!("{}", params);
-/// });
-/// spawn!(task3, 3, stack2, {
-///     hprintln!("Hello!");
-/// });
+/// ```rust,no_run
+/// use harsark::spawn;
+/// use harsark::tasks::*;
+///
+/// const STACK_SIZE: usize = 256;
+///
+/// spawn!(2, STACK_SIZE, (|cxt: ContextType| {
+///     let _tid = cxt.get_tid();
+/// }));
+/// spawn!(3, STACK_SIZE, (|_| {
+///     release(1 << 2);
+/// }));
+/// ```
+///
+/// With the `task_monitor` feature, the deadline of the task follows its priority:
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "task_monitor")]
+/// # {
+/// use harsark::spawn;
+/// use harsark::tasks::*;
+/// use harsark::timer::Duration;
+///
+/// const STACK_SIZE: usize = 256;
+///
+/// spawn!(2, Duration::from_millis(10), STACK_SIZE, (|_| {}));
+/// # }
 /// ```
 #[macro_export]
 macro_rules! spawn {
-    ($priority: expr, $stack_size: ident, $handler_fn: tt) => {
+    ($priority: expr, $stack_size: expr, $handler_fn: tt) => {
         let mut stack = [0; $stack_size];
-        create_task(
-            $priority,
-            unsafe { &mut stack },
-            |cxt: ContextType| loop {
+        $crate::macros::spawn_task($priority, &mut stack, |cxt: $crate::tasks::ContextType| loop {
             $handler_fn(cxt);
-                task_exit();
-            },
-        );
+            $crate::tasks::task_exit();
+        })
+        .unwrap();
     };
-    ($priority: expr, $deadline: expr, $stack_size: ident, $handler_fn: tt) => {
-        let mut stack = [0; $stack_size]
-        create_task(
+    ($priority: expr, $deadline: expr, $stack_size: expr, $handler_fn: tt) => {
+        let mut stack = [0; $stack_size];
+        $crate::macros::spawn_task_with_deadline(
             $priority,
             $deadline,
-            unsafe { &mut stack },
-            |cxt: ContextType| loop {
+            &mut stack,
+            |cxt: $crate::tasks::ContextType| loop {
                 $handler_fn(cxt);
-                task_exit();
+                $crate::tasks::task_exit();
             },
-        );
+        )
+        .unwrap();
     };
 }
 
-/// `priv_execute!` executes the code block only if the current context is in privileged mode.
+/// Creates the task of a `spawn!` without a deadline, whatever the signature `create_task` is
+/// compiled with.
+#[doc(hidden)]
+pub fn spawn_task(
+    priority: Priority,
+    stack: &mut [u32],
+    handler_fn: fn(ContextType) -> !,
+) -> Result<TaskId, KernelError> {
+    create_task(
+        priority,
+        #[cfg(feature = "task_monitor")]
+        Duration::ZERO,
+        stack,
+        #[cfg(feature = "fpu")]
+        true,
+        handler_fn,
+    )
+}
+
+/// Creates the task of a `spawn!` with a deadline.
+#[cfg(feature = "task_monitor")]
+#[doc(hidden)]
+pub fn spawn_task_with_deadline(
+    priority: Priority,
+    deadline: Duration,
+    stack: &mut [u32],
+    handler_fn: fn(ContextType) -> !,
+) -> Result<TaskId, KernelError> {
+    create_task(
+        priority,
+        deadline,
+        stack,
+        #[cfg(feature = "fpu")]
+        true,
+        handler_fn,
+    )
+}
+
+/// `priv_execute!` executes the code block only if the current context is in privileged mode,
+/// else it evaluates to `Err(KernelError::AccessDenied)`.
 /// ## Example
-/// ```rust
-/// priv_execute!({
-///     hprintln!("Privileged!");
-/// });
+/// ```rust,no_run
+/// use harsark::priv_execute;
+/// use harsark::KernelError;
+///
+/// fn configure() -> Result<(), KernelError> {
+///     priv_execute!({
+///         // Only reached from privileged code.
+///         Ok(())
+///     })
+/// }
 /// ```
 #[macro_export]
 macro_rules! priv_execute {
     ($handler: block) => {
-        match is_privileged() {
-            false => Err(KernelError::AccessDenied),
+        match $crate::macros::is_privileged() {
+            false => Err($crate::KernelError::AccessDenied),
             true => $handler,
         }
     };
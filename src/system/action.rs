@@ -0,0 +1,102 @@
+//! # Actions
+//!
+//! Defines what the Kernel does on behalf of the application when an event is dispatched or a
+//! bound interrupt is raised, so that common hand-offs to tasks need no user code in a handler.
+
+use crate::kernel::tasks::release;
+use crate::system::message::Message;
+use crate::system::queue::Queue;
+use crate::system::scheduler::BooleanVector;
+use crate::system::semaphore::Semaphore;
+use crate::utils::arch::CriticalSection;
+
+/// What an event or a bound interrupt does each time it is dispatched.
+#[derive(Clone, Copy)]
+pub enum EventAction {
+    /// Releases the tasks in the mask.
+    Release(BooleanVector),
+    /// Signals the semaphore with the flags in the mask and releases its tasks.
+    Signal(&'static Semaphore, BooleanVector),
+    /// Broadcasts a value through a message, created by `EventAction::broadcast`.
+    Broadcast(BroadcastAction),
+    /// Sends a value to a queue, created by `EventAction::post`.
+    Post(PostAction),
+    /// Calls the handler.
+    Handler(fn()),
+    /// Does nothing but leave a log entry of the dispatch, if logging is enabled.
+    Log,
+}
+
+/// A message and the value broadcast through it, with the message type erased.
+#[derive(Clone, Copy)]
+pub struct BroadcastAction {
+    broadcast: unsafe fn(&CriticalSection, usize, usize),
+    message: usize,
+    value: usize,
+    /// The tasks released by the message.
+    tasks: BooleanVector,
+}
+
+/// A queue and the value sent to it, with the queue type erased.
+#[derive(Clone, Copy)]
+pub struct PostAction {
+    post: unsafe fn(&CriticalSection, usize, usize),
+    queue: usize,
+    value: usize,
+    /// The tasks which receive from the queue.
+    tasks: BooleanVector,
+}
+
+impl EventAction {
+    /// Returns the action which broadcasts a clone of `value` through `message`.
+    pub fn broadcast<T: Sized + Clone + 'static>(message: &'static Message<T>, value: &'static T) -> Self {
+        EventAction::Broadcast(BroadcastAction {
+            broadcast: Message::<T>::broadcast_clone,
+            message: message as *const Message<T> as usize,
+            value: value as *const T as usize,
+            tasks: message.get_tasks(),
+        })
+    }
+
+    /// Returns the action which sends a clone of `value` to `queue`, the clone is dropped if the
+    /// queue is full under the `Reject` policy. `receivers` are the tasks which receive from the
+    /// queue, the hand-off latency of a bound interrupt is measured until one of them runs.
+    pub fn post<T: Clone + 'static, const N: usize>(
+        queue: &'static Queue<T, N>,
+        value: &'static T,
+        receivers: BooleanVector,
+    ) -> Self {
+        EventAction::Post(PostAction {
+            post: Queue::<T, N>::post_clone,
+            queue: queue as *const Queue<T, N> as usize,
+            value: value as *const T as usize,
+            tasks: receivers,
+        })
+    }
+
+    /// Returns the tasks the action releases.
+    pub(crate) fn get_tasks(&self) -> BooleanVector {
+        match *self {
+            EventAction::Release(tasks_mask) => tasks_mask,
+            EventAction::Signal(semaphore, _) => semaphore.tasks,
+            EventAction::Broadcast(action) => action.tasks,
+            EventAction::Post(action) => action.tasks,
+            EventAction::Handler(_) | EventAction::Log => 0,
+        }
+    }
+
+    pub(crate) fn execute(&self, cs: &CriticalSection) {
+        match *self {
+            EventAction::Release(tasks_mask) => release(tasks_mask),
+            EventAction::Signal(semaphore, tasks_mask) => {
+                semaphore.signal_and_release_with_cs(cs, tasks_mask)
+            }
+            EventAction::Broadcast(action) => unsafe {
+                (action.broadcast)(cs, action.message, action.value)
+            },
+            EventAction::Post(action) => unsafe { (action.post)(cs, action.queue, action.value) },
+            EventAction::Handler(handler) => handler(),
+            EventAction::Log => {}
+        }
+    }
+}
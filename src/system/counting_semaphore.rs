@@ -0,0 +1,201 @@
+//! # Counting Semaphore
+//!
+//! A classic counting semaphore. Tasks block on `acquire` while no token is left, they are held
+//! in the `waiting_tasks` vector of the Scheduler and tracked in a boolean vector of their own.
+//! A release hands its token straight to the highest priority waiting task, so that a task
+//! acquiring in the meantime can not take it away.
+
+use core::cell::RefCell;
+
+use crate::kernel::syscall::{
+    decode, decode_flag, register, split_ticks, syscall, Syscall, NO_TIMEOUT,
+};
+use crate::kernel::tasks::{preempt, schedule, TaskManager};
+use crate::system::registry::KernelObject;
+use crate::system::scheduler::BooleanVector;
+use crate::utils::arch::{critical_section, is_privileged};
+use crate::KernelError;
+
+#[cfg(feature = "timer")]
+use crate::{kernel::timer::get_time, system::time::Duration};
+
+pub struct CountingSemaphore {
+    /// The tokens left.
+    count: RefCell<u32>,
+    /// The most tokens the semaphore holds.
+    max_count: u32,
+    /// The tasks waiting for a token.
+    waiters: RefCell<BooleanVector>,
+    /// The tasks which have been handed a token, but have not returned from `acquire` yet.
+    granted: RefCell<BooleanVector>,
+}
+
+impl CountingSemaphore {
+    /// Initializes a new semaphore holding `count` of at most `max_count` tokens. Panics if
+    /// `count` exceeds `max_count`, at compile time for a semaphore in a static.
+    pub const fn new(count: u32, max_count: u32) -> Self {
+        assert!(count <= max_count, "count exceeds max_count");
+        Self {
+            count: RefCell::new(count),
+            max_count,
+            waiters: RefCell::new(0),
+            granted: RefCell::new(0),
+        }
+    }
+
+    /// Registers the semaphore with the Kernel, so that unprivileged tasks can use it. Only
+    /// privileged code can register semaphores.
+    pub fn register(&'static self) -> Result<(), KernelError> {
+        register(self.addr(), KernelObject::CountingSemaphore)
+    }
+
+    /// Returns the number of tokens left, none are left in a semaphore an unprivileged task can
+    /// not use.
+    pub fn count(&'static self) -> u32 {
+        if !is_privileged() {
+            return syscall(Syscall::CountingSemaphoreCount, [self.addr(), 0, 0]) as u32;
+        }
+        critical_section(|_| *self.count.borrow())
+    }
+
+    /// Takes a token if one is left, returns whether it did. Can be called from interrupt handlers.
+    pub fn try_acquire(&'static self) -> bool {
+        if !is_privileged() {
+            let res = syscall(Syscall::CountingSemaphoreTryAcquire, [self.addr(), 0, 0]);
+            return matches!(decode_flag(res), Ok(true));
+        }
+        critical_section(|_| self.take())
+    }
+
+    /// Takes a token, blocks the running task until one is released if none is left. Fails with
+    /// `AccessDenied` if the idle task would have to block, as it can not. Must not be called
+    /// from interrupt handlers.
+    pub fn acquire(&'static self) -> Result<(), KernelError> {
+        loop {
+            match self.acquire_until(None) {
+                Err(KernelError::TimedOut) => continue,
+                res => return res,
+            }
+        }
+    }
+
+    /// Like `acquire`, but gives up once `timeout` has passed without a token being released.
+    #[cfg(feature = "timer")]
+    pub fn acquire_timeout(&'static self, timeout: Duration) -> Result<(), KernelError> {
+        let timeout_time = (get_time() + timeout).ticks();
+        self.acquire_until(Some(timeout_time))
+    }
+
+    /// Takes a token right away if one is left, else blocks the running task until it is handed
+    /// one or its timeout at `timeout_time`. An unprivileged task can only acquire a registered
+    /// semaphore.
+    fn acquire_until(&'static self, timeout_time: Option<u64>) -> Result<(), KernelError> {
+        if !is_privileged() {
+            let [time_lo, time_hi] = split_ticks(timeout_time.unwrap_or(NO_TIMEOUT));
+            let args = [self.addr(), time_lo, time_hi];
+            if !decode_flag(syscall(Syscall::CountingSemaphoreAcquire, args))? {
+                decode(syscall(Syscall::CountingSemaphoreAcquireEnd, args))?;
+            }
+            return Ok(());
+        }
+        if !self.begin_acquire(timeout_time)? {
+            preempt();
+            self.end_acquire()?;
+        }
+        Ok(())
+    }
+
+    /// Takes a token if one is left, else makes the running task wait for one and returns false.
+    /// The task is switched out once it leaves the Kernel.
+    pub(crate) fn begin_acquire(
+        &'static self,
+        timeout_time: Option<u64>,
+    ) -> Result<bool, KernelError> {
+        critical_section(|cs_token| {
+            if self.take() {
+                return Ok(true);
+            }
+            let handler = &mut TaskManager.borrow(cs_token).borrow_mut();
+            let tid = handler.curr_tid;
+            if tid == 0 {
+                return Err(KernelError::AccessDenied);
+            }
+            *self.waiters.borrow_mut() |= 1 << tid;
+            *self.granted.borrow_mut() &= !(1 << tid);
+            match timeout_time {
+                #[cfg(feature = "timer")]
+                Some(timeout_time) => handler.wait_until(tid, self.addr(), timeout_time),
+                _ => handler.wait(tid, self.addr()),
+            }
+            Ok(false)
+        })
+    }
+
+    /// Returns whether the running task was handed a token once it runs again, fails with
+    /// `TimedOut` if it was woken up by its timeout instead.
+    pub(crate) fn end_acquire(&'static self) -> Result<(), KernelError> {
+        critical_section(|cs_token| {
+            let tid = TaskManager.borrow(cs_token).borrow().curr_tid;
+            let granted = &mut *self.granted.borrow_mut();
+            if *granted & (1 << tid) != 0 {
+                *granted &= !(1 << tid);
+                Ok(())
+            } else {
+                *self.waiters.borrow_mut() &= !(1 << tid);
+                Err(KernelError::TimedOut)
+            }
+        })
+    }
+
+    /// Releases a token. If tasks are waiting, the token is handed to the highest priority one
+    /// of them and it is woken up. Fails with `LimitExceeded` if the semaphore is full. Can be
+    /// called from interrupt handlers. An unprivileged task can only release a registered
+    /// semaphore.
+    pub fn release(&'static self) -> Result<(), KernelError> {
+        if !is_privileged() {
+            return decode(syscall(
+                Syscall::CountingSemaphoreRelease,
+                [self.addr(), 0, 0],
+            ));
+        }
+        critical_section(|cs_token| {
+            let waiters = &mut *self.waiters.borrow_mut();
+            let handler = &mut TaskManager.borrow(cs_token).borrow_mut();
+            *waiters = handler.get_waiters(self.addr(), *waiters);
+            match handler.get_highest(*waiters) {
+                Some(tid) => {
+                    *waiters &= !(1 << tid);
+                    *self.granted.borrow_mut() |= 1 << tid;
+                    handler.notify(1 << tid);
+                    schedule(handler.is_preemptive);
+                    Ok(())
+                }
+                None => {
+                    let count = &mut *self.count.borrow_mut();
+                    if *count >= self.max_count {
+                        return Err(KernelError::LimitExceeded);
+                    }
+                    *count += 1;
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    /// Takes a token if one is left, within a critical section.
+    fn take(&'static self) -> bool {
+        let count = &mut *self.count.borrow_mut();
+        if *count == 0 {
+            return false;
+        }
+        *count -= 1;
+        true
+    }
+
+    /// Identifies the semaphore to the Scheduler, which keeps what each task waits on.
+    fn addr(&'static self) -> usize {
+        self as *const Self as usize
+    }
+}
+
+unsafe impl Sync for CountingSemaphore {}
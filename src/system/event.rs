@@ -3,104 +3,154 @@
 //! Defines Data-structures to manage events.
 
 use crate::config::EVENT_COUNT;
+use crate::system::action::EventAction;
+use crate::system::time::Duration;
+use crate::utils::arch::CriticalSection;
 use crate::utils::errors::KernelError;
 
 #[cfg(feature = "system_logger")]
-use {
-    crate::system::system_logger::LogEventType,
-    crate::kernel::logging,
-};
+use {crate::kernel::logging, crate::system::system_logger::LogEventType};
 
 pub type EventId = usize;
 
 /// Event Descriptor
 #[derive(Clone, Copy)]
-pub struct Event
-{
+pub struct Event {
     event_id: EventId,
     /// Whether this event is currently enabled or not.
     is_enabled: bool,
-    /// This is the frequency (of time unit in which it belongs to) in which the Event should run.
-    threshold: u32,
-    handler: fn() -> (),
+    /// This is the period in which the Event should run.
+    threshold: Duration,
+    /// The time of the first dispatch, later ones follow every `threshold`.
+    offset: Duration,
+    action: EventAction,
 }
 
 impl Event {
-    /// Takes the EventId and executes the corresponding event handler.
-    pub fn dispatch_event(&mut self, curr_time: u32) {
-        if self.is_enabled {
-            if curr_time % self.threshold == 0 {
-                (self.handler)();
-                #[cfg(feature = "system_logger")] {
-                    if logging::get_timer_event() {
-                        logging::report(LogEventType::TimerEvent(self.event_id));
-                    }
+    /// Executes the action of the event if it is enabled and due at `curr_time`.
+    pub fn dispatch_event(&mut self, cs: &CriticalSection, curr_time: u64) {
+        if self.is_enabled && self.is_due(curr_time) {
+            self.action.execute(cs);
+            #[cfg(feature = "system_logger")]
+            {
+                if logging::get_timer_event() {
+                    logging::report(LogEventType::TimerEvent(self.event_id));
                 }
             }
         }
     }
+
+    /// Returns whether the event is dispatched at `curr_time`.
+    fn is_due(&self, curr_time: u64) -> bool {
+        let offset = self.offset.ticks();
+        curr_time >= offset && (curr_time - offset) % self.threshold.ticks() == 0
+    }
+
+    /// Returns the first time from `curr_time` on at which the event is dispatched.
+    fn next_dispatch(&self, curr_time: u64) -> u64 {
+        let offset = self.offset.ticks();
+        if curr_time < offset {
+            return offset;
+        }
+        let threshold = self.threshold.ticks();
+        curr_time + (threshold - (curr_time - offset) % threshold) % threshold
+    }
 }
 
 /// Holds and Implements all Event management and dispatch methods.
-pub struct EventTable 
-{
-    /// This array holds the Event descriptors of all events
+pub struct EventTable {
+    /// This array holds the Event descriptors of all events, deleted events leave their slot free for reuse.
     events: [Option<Event>; EVENT_COUNT],
-    /// Points to the current empty slot in the `event_table`.
-    curr: usize,
 }
 
-impl EventTable 
-{
+impl EventTable {
     /// Returns new instance of EventManager
     pub const fn new() -> Self {
         Self {
             events: [None; EVENT_COUNT],
-            curr: 0,
+        }
+    }
+
+    fn get(&mut self, event_id: EventId) -> Result<&mut Event, KernelError> {
+        match self.events.get_mut(event_id) {
+            Some(Some(event)) => Ok(event),
+            _ => Err(KernelError::NotFound),
         }
     }
 
     /// This function dispatches all events mentioned in the `EventIndexTable` corresponding to the `EventTableType`.
-    pub fn sweep(&mut self, curr_time: u32) {
-        for i in 0..self.curr {
-            if let Some(ref mut event) = self.events[i] {
-                event.dispatch_event(curr_time);
-            }
+    pub fn sweep(&mut self, cs: &CriticalSection, curr_time: u64) {
+        for event in self.events.iter_mut().flatten() {
+            event.dispatch_event(cs, curr_time);
         }
     }
 
+    /// Returns the number of ticks until the next tick on which an enabled event is dispatched,
+    /// `None` if there is no enabled event.
+    pub fn get_next_dispatch(&self, curr_time: u64) -> Option<u64> {
+        self.events
+            .iter()
+            .flatten()
+            .filter(|event| event.is_enabled)
+            .map(|event| event.next_dispatch(curr_time) - curr_time + 1)
+            .min()
+    }
+
     /// Enables an Event.
-    pub fn enable(&mut self, event_id: EventId) -> Result<(),KernelError> {
-        let event = &mut self.events[event_id].as_mut().ok_or(KernelError::NotFound)?;
-        event.is_enabled = true;
+    pub fn enable(&mut self, event_id: EventId) -> Result<(), KernelError> {
+        self.get(event_id)?.is_enabled = true;
         Ok(())
     }
 
     /// Disables an Event.
-    pub fn disable(&mut self, event_id: EventId) -> Result<(),KernelError> {
-        let event = &mut self.events[event_id].as_mut().ok_or(KernelError::NotFound)?;
-        event.is_enabled = false;
+    pub fn disable(&mut self, event_id: EventId) -> Result<(), KernelError> {
+        self.get(event_id)?.is_enabled = false;
+        Ok(())
+    }
+
+    /// Sets the period of an Event, its dispatches keep the phase given by its offset.
+    pub fn set_threshold(
+        &mut self,
+        event_id: EventId,
+        threshold: Duration,
+    ) -> Result<(), KernelError> {
+        if threshold.is_zero() {
+            return Err(KernelError::InvalidArgument);
+        }
+        self.get(event_id)?.threshold = threshold;
+        Ok(())
+    }
+
+    /// Deletes an Event, its EventId is handed out again by `create`.
+    pub fn delete(&mut self, event_id: EventId) -> Result<(), KernelError> {
+        self.get(event_id)?;
+        self.events[event_id] = None;
         Ok(())
     }
 
-    /// Creates a new event.
+    /// Creates a new event in the first free slot.
     pub fn create(
         &mut self,
         is_enabled: bool,
-        threshold: u32,
-        handler: fn() -> ()
+        threshold: Duration,
+        offset: Duration,
+        action: EventAction,
     ) -> Result<EventId, KernelError> {
-        let id = self.curr;
-        if id >= self.events.len() {
-            return Err(KernelError::LimitExceeded);
+        if threshold.is_zero() {
+            return Err(KernelError::InvalidArgument);
         }
+        let id = self
+            .events
+            .iter()
+            .position(|slot| slot.is_none())
+            .ok_or(KernelError::LimitExceeded)?;
         self.events[id] = Some(Event {
             event_id: id,
             is_enabled,
             threshold,
-            handler
+            offset,
+            action,
         });
-        self.curr += 1;
-        return Ok(id);
+        Ok(id)
     }
 }
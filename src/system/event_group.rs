@@ -0,0 +1,274 @@
+//! # Event Group
+//!
+//! A word of 32 event flags which tasks can block on until any or all of a set of them are set.
+//! Flags are set and cleared by tasks and interrupt handlers alike. Waiting tasks are held in the
+//! `waiting_tasks` vector of the Scheduler until the flags they wait for are set.
+
+use core::cell::RefCell;
+
+use crate::config::MAX_TASKS;
+use crate::kernel::syscall::{decode, decode_flag, register, syscall, BlockingArgs, Syscall};
+use crate::kernel::tasks::{preempt, schedule, TaskManager};
+use crate::system::registry::KernelObject;
+use crate::system::scheduler::BooleanVector;
+use crate::utils::arch::{critical_section, get_msb, is_privileged};
+use crate::KernelError;
+
+#[cfg(feature = "timer")]
+use crate::{kernel::timer::get_time, system::time::Duration};
+
+/// Whether a wait is satisfied by any or only by all of the flags it waits for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaitMode {
+    Any,
+    All,
+}
+
+/// Options of a wait passed to the `EventGroupWait` system call.
+pub(crate) const WAIT_ALL: usize = 1 << 0;
+pub(crate) const AUTO_CLEAR: usize = 1 << 1;
+
+struct Waiters {
+    /// The tasks waiting on the group.
+    tasks: BooleanVector,
+    /// The tasks which wait for all of their flags.
+    wait_all: BooleanVector,
+    /// The tasks whose flags are cleared when their wait is satisfied.
+    auto_clear: BooleanVector,
+    /// The tasks whose wait has been satisfied, but which have not returned from it yet.
+    satisfied: BooleanVector,
+    /// The flags each task waits for, replaced by the flags which satisfied the wait.
+    flags: [u32; MAX_TASKS],
+}
+
+/// Holds the flags of the group along with the tasks waiting on it.
+pub struct EventGroup {
+    flags: RefCell<u32>,
+    waiters: RefCell<Waiters>,
+}
+
+impl EventGroup {
+    /// Initializes a new event group with all flags cleared.
+    pub const fn new() -> Self {
+        Self {
+            flags: RefCell::new(0),
+            waiters: RefCell::new(Waiters {
+                tasks: 0,
+                wait_all: 0,
+                auto_clear: 0,
+                satisfied: 0,
+                flags: [0; MAX_TASKS],
+            }),
+        }
+    }
+
+    /// Registers the group with the Kernel, so that unprivileged tasks can use it. Only
+    /// privileged code can register event groups.
+    pub fn register(&'static self) -> Result<(), KernelError> {
+        register(self.addr(), KernelObject::EventGroup)
+    }
+
+    /// Returns the flags which are set.
+    pub fn get(&'static self) -> u32 {
+        if !is_privileged() {
+            return syscall(Syscall::EventGroupGet, [self.addr(), 0, 0]) as u32;
+        }
+        critical_section(|_| *self.flags.borrow())
+    }
+
+    /// Sets `flags` and wakes the tasks whose wait is satisfied by them. The flags of the woken
+    /// tasks which wait with auto-clear are cleared once all waits have been checked, hence a
+    /// single call can satisfy several tasks waiting for the same flags. Can be called from
+    /// interrupt handlers.
+    pub fn set(&'static self, flags: u32) {
+        if !is_privileged() {
+            syscall(Syscall::EventGroupSet, [self.addr(), flags as usize, 0]);
+            return;
+        }
+        critical_section(|cs_token| {
+            let curr_flags = &mut *self.flags.borrow_mut();
+            *curr_flags |= flags;
+            let waiters = &mut *self.waiters.borrow_mut();
+            let handler = &mut TaskManager.borrow(cs_token).borrow_mut();
+            waiters.tasks = handler.get_waiters(self.addr(), waiters.tasks);
+            let mut tasks = waiters.tasks;
+            let mut woken: BooleanVector = 0;
+            let mut clear = 0;
+            while let Some(tid) = get_msb(tasks) {
+                tasks &= !(1 << tid);
+                let wait_flags = waiters.flags[tid];
+                if !is_satisfied(*curr_flags, wait_flags, waiters.wait_all & (1 << tid) != 0) {
+                    continue;
+                }
+                if waiters.auto_clear & (1 << tid) != 0 {
+                    clear |= wait_flags;
+                }
+                waiters.flags[tid] = *curr_flags;
+                woken |= 1 << tid;
+            }
+            *curr_flags &= !clear;
+            if woken != 0 {
+                waiters.tasks &= !woken;
+                waiters.satisfied |= woken;
+                handler.notify(woken);
+                schedule(handler.is_preemptive);
+            }
+        })
+    }
+
+    /// Clears `flags`.
+    pub fn clear(&'static self, flags: u32) {
+        if !is_privileged() {
+            syscall(Syscall::EventGroupClear, [self.addr(), flags as usize, 0]);
+            return;
+        }
+        critical_section(|_| *self.flags.borrow_mut() &= !flags)
+    }
+
+    /// Blocks the running task until any or all of `flags` are set, as given by `mode`, and
+    /// returns the flags which were set at that moment. With `auto_clear`, `flags` are cleared
+    /// on return. Fails with `InvalidArgument` if `flags` is empty, and with `AccessDenied` if
+    /// the idle task would have to block, as it can not. Must not be called from interrupt
+    /// handlers.
+    pub fn wait(
+        &'static self,
+        flags: u32,
+        mode: WaitMode,
+        auto_clear: bool,
+    ) -> Result<u32, KernelError> {
+        loop {
+            match self.wait_for(flags, mode, auto_clear, None) {
+                Err(KernelError::TimedOut) => continue,
+                res => return res,
+            }
+        }
+    }
+
+    /// Like `wait`, but gives up once `timeout` has passed without the wait being satisfied.
+    #[cfg(feature = "timer")]
+    pub fn wait_timeout(
+        &'static self,
+        flags: u32,
+        mode: WaitMode,
+        auto_clear: bool,
+        timeout: Duration,
+    ) -> Result<u32, KernelError> {
+        let timeout_time = (get_time() + timeout).ticks();
+        self.wait_for(flags, mode, auto_clear, Some(timeout_time))
+    }
+
+    /// Returns the flags right away if the wait is satisfied, else blocks the running task until
+    /// it is woken up by `set` or its timeout at `timeout_time`. An unprivileged task can only
+    /// wait on a registered group.
+    fn wait_for(
+        &'static self,
+        flags: u32,
+        mode: WaitMode,
+        auto_clear: bool,
+        timeout_time: Option<u64>,
+    ) -> Result<u32, KernelError> {
+        let wait_all = mode == WaitMode::All;
+        if !is_privileged() {
+            let options = (wait_all as usize * WAIT_ALL) | (auto_clear as usize * AUTO_CLEAR);
+            let mut wait = BlockingArgs::new(flags as usize, options, timeout_time);
+            let args = [self.addr(), &mut wait as *mut BlockingArgs as usize, 0];
+            if !decode_flag(syscall(Syscall::EventGroupWait, args))? {
+                decode(syscall(Syscall::EventGroupWaitEnd, args))?;
+            }
+            return Ok(wait.value as u32);
+        }
+        match self.begin_wait(flags, wait_all, auto_clear, timeout_time)? {
+            Some(flags) => Ok(flags),
+            None => {
+                preempt();
+                self.end_wait()
+            }
+        }
+    }
+
+    /// Returns the flags if the wait is satisfied right away, else makes the running task wait
+    /// and returns `None`. The task is switched out once it leaves the Kernel.
+    pub(crate) fn begin_wait(
+        &'static self,
+        flags: u32,
+        wait_all: bool,
+        auto_clear: bool,
+        timeout_time: Option<u64>,
+    ) -> Result<Option<u32>, KernelError> {
+        if flags == 0 {
+            return Err(KernelError::InvalidArgument);
+        }
+        critical_section(|cs_token| {
+            let curr_flags = &mut *self.flags.borrow_mut();
+            if is_satisfied(*curr_flags, flags, wait_all) {
+                let res = *curr_flags;
+                if auto_clear {
+                    *curr_flags &= !flags;
+                }
+                return Ok(Some(res));
+            }
+            let handler = &mut TaskManager.borrow(cs_token).borrow_mut();
+            let tid = handler.curr_tid;
+            if tid == 0 {
+                return Err(KernelError::AccessDenied);
+            }
+            let waiters = &mut *self.waiters.borrow_mut();
+            waiters.tasks |= 1 << tid;
+            waiters.satisfied &= !(1 << tid);
+            set_bit(&mut waiters.wait_all, tid, wait_all);
+            set_bit(&mut waiters.auto_clear, tid, auto_clear);
+            waiters.flags[tid] = flags;
+            match timeout_time {
+                #[cfg(feature = "timer")]
+                Some(timeout_time) => handler.wait_until(tid, self.addr(), timeout_time),
+                _ => handler.wait(tid, self.addr()),
+            }
+            Ok(None)
+        })
+    }
+
+    /// Returns the flags which satisfied the wait of the running task once it runs again, fails
+    /// with `TimedOut` if it was woken up by its timeout instead.
+    pub(crate) fn end_wait(&'static self) -> Result<u32, KernelError> {
+        critical_section(|cs_token| {
+            let tid = TaskManager.borrow(cs_token).borrow().curr_tid;
+            let waiters = &mut *self.waiters.borrow_mut();
+            if waiters.satisfied & (1 << tid) != 0 {
+                waiters.satisfied &= !(1 << tid);
+                Ok(waiters.flags[tid])
+            } else {
+                waiters.tasks &= !(1 << tid);
+                Err(KernelError::TimedOut)
+            }
+        })
+    }
+
+    /// Identifies the group to the Scheduler, which keeps what each task waits on.
+    fn addr(&'static self) -> usize {
+        self as *const Self as usize
+    }
+}
+
+fn is_satisfied(flags: u32, wait_flags: u32, wait_all: bool) -> bool {
+    if wait_all {
+        flags & wait_flags == wait_flags
+    } else {
+        flags & wait_flags != 0
+    }
+}
+
+fn set_bit(vector: &mut BooleanVector, tid: usize, val: bool) {
+    if val {
+        *vector |= 1 << tid;
+    } else {
+        *vector &= !(1 << tid);
+    }
+}
+
+impl Default for EventGroup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl Sync for EventGroup {}
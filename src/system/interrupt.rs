@@ -0,0 +1,147 @@
+//! # Interrupt Bindings
+//! The Definition of Data-structures required for binding interrupts to actions.
+//! Each binding records how often its interrupt was raised and the hand-off latency, i.e. the
+//! cycles from the entry of the interrupt to the first of the tasks released by it running.
+
+use crate::config::MAX_IRQ_BINDINGS;
+use crate::system::action::EventAction;
+use crate::system::scheduler::{BooleanVector, TaskId};
+use crate::KernelError;
+
+/// Number of an interrupt as given to the NVIC, i.e. the exception number less 16.
+pub type IrqNumber = u16;
+
+// The outstanding hand-offs are kept in a `u32`, a bit per binding.
+const _: () = assert!(MAX_IRQ_BINDINGS <= 32, "MAX_IRQ_BINDINGS must be at most 32");
+
+/// Counters of a bound interrupt. Latencies are measured in the unit of `tasks::stats`, i.e. in
+/// CPU cycles on Cortex-M3/M4 and in ticks on the other ports.
+#[derive(Clone, Copy, Debug)]
+pub struct IrqStats {
+    /// The number of times the interrupt was raised.
+    pub count: u32,
+    /// The hand-off latency of the latest raise which released a task.
+    pub last_latency: u32,
+    /// The longest hand-off latency so far.
+    pub max_latency: u32,
+}
+
+impl IrqStats {
+    pub const fn new() -> Self {
+        Self {
+            count: 0,
+            last_latency: 0,
+            max_latency: 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct IrqBinding {
+    irqn: IrqNumber,
+    action: EventAction,
+    stats: IrqStats,
+    /// The cycle count at the entry of the raise whose hand-off has not completed yet.
+    entry: u32,
+}
+
+pub struct IrqTable {
+    bindings: [Option<IrqBinding>; MAX_IRQ_BINDINGS],
+    /// The bindings whose hand-off is outstanding, a bit per slot in `bindings`.
+    pending: u32,
+    /// Called for the interrupts which are not bound and for the system exceptions without a
+    /// handler of their own.
+    default_handler: Option<fn(i16)>,
+}
+
+impl IrqTable {
+    pub const fn new() -> Self {
+        Self {
+            bindings: [None; MAX_IRQ_BINDINGS],
+            pending: 0,
+            default_handler: None,
+        }
+    }
+
+    pub fn set_default_handler(&mut self, handler: fn(i16)) {
+        self.default_handler = Some(handler);
+    }
+
+    pub fn get_default_handler(&self) -> Option<fn(i16)> {
+        self.default_handler
+    }
+
+    fn find(&self, irqn: IrqNumber) -> Option<usize> {
+        self.bindings
+            .iter()
+            .position(|binding| matches!(binding, Some(binding) if binding.irqn == irqn))
+    }
+
+    /// Binds interrupt `irqn` to `action`, an interrupt can only be bound once.
+    pub fn bind(&mut self, irqn: IrqNumber, action: EventAction) -> Result<(), KernelError> {
+        if self.find(irqn).is_some() {
+            return Err(KernelError::Exists);
+        }
+        let slot = self
+            .bindings
+            .iter()
+            .position(|binding| binding.is_none())
+            .ok_or(KernelError::LimitExceeded)?;
+        self.bindings[slot] = Some(IrqBinding {
+            irqn,
+            action,
+            stats: IrqStats::new(),
+            entry: 0,
+        });
+        self.pending &= !(1 << slot);
+        Ok(())
+    }
+
+    pub fn unbind(&mut self, irqn: IrqNumber) -> Result<(), KernelError> {
+        let slot = self.find(irqn).ok_or(KernelError::NotFound)?;
+        self.bindings[slot] = None;
+        self.pending &= !(1 << slot);
+        Ok(())
+    }
+
+    pub fn get_stats(&self, irqn: IrqNumber) -> Result<IrqStats, KernelError> {
+        let slot = self.find(irqn).ok_or(KernelError::NotFound)?;
+        Ok(self.bindings[slot].as_ref().unwrap().stats)
+    }
+
+    /// Counts a raise of interrupt `irqn` which entered at cycle `entry` and returns the action
+    /// it is bound to. If the action releases tasks, the hand-off is outstanding until one of
+    /// them runs.
+    pub fn raise(&mut self, irqn: IrqNumber, entry: u32) -> Option<EventAction> {
+        let slot = self.find(irqn)?;
+        let binding = self.bindings[slot].as_mut().unwrap();
+        binding.stats.count = binding.stats.count.wrapping_add(1);
+        if binding.action.get_tasks() != 0 && self.pending & (1 << slot) == 0 {
+            binding.entry = entry;
+            self.pending |= 1 << slot;
+        }
+        Some(binding.action)
+    }
+
+    /// Completes the outstanding hand-offs to task `tid`, which starts running at cycle `now`.
+    pub fn complete_handoffs(&mut self, tid: TaskId, now: u32) {
+        let mut pending = self.pending;
+        while pending != 0 {
+            let slot = pending.trailing_zeros() as usize;
+            pending &= !(1 << slot);
+            let binding = self.bindings[slot].as_mut().unwrap();
+            let tasks: BooleanVector = binding.action.get_tasks();
+            if tasks & (1 << tid) != 0 {
+                let latency = now.wrapping_sub(binding.entry);
+                binding.stats.last_latency = latency;
+                binding.stats.max_latency = binding.stats.max_latency.max(latency);
+                self.pending &= !(1 << slot);
+            }
+        }
+    }
+
+    /// Returns whether any hand-off is outstanding.
+    pub fn has_pending(&self) -> bool {
+        self.pending != 0
+    }
+}
@@ -2,18 +2,23 @@
 //!
 
 use core::cell::RefCell;
+use core::mem::{align_of, size_of, ManuallyDrop, MaybeUninit};
+use core::ptr;
 
-use cortex_m::asm::bkpt;
-
+use crate::kernel::syscall::{decode_flag, register, syscall, Syscall};
+use crate::kernel::tasks::get_curr_tid;
+use crate::system::registry::{KernelObject, MessageOps};
+use crate::system::scheduler::TaskId;
 use crate::system::semaphore::Semaphore;
-use crate::tasks::get_curr_tid;
-use crate::utils::arch::critical_section;
+use crate::utils::arch::{critical_section, is_privileged, CriticalSection};
+use crate::KernelError;
 use crate::{system::scheduler::BooleanVector, tasks::Context};
 
 #[cfg(feature = "system_logger")]
 use {crate::kernel::logging, crate::system::system_logger::LogEventType};
 
 /// Holds metadata corresponding to a single message object.
+/// A message used by unprivileged tasks has to be registered first, see `register`.
 pub struct Message<T: Sized + Clone> {
     value: RefCell<T>,
     pub receivers: BooleanVector,
@@ -30,35 +35,121 @@ impl<T: Sized + Clone> Message<T> {
         }
     }
 
+    /// Registers the message with the Kernel, so that unprivileged tasks can broadcast and
+    /// receive it. The routines of its type are recorded along with it, as system calls can not
+    /// be generic over the message type. Only privileged code can register messages.
+    pub fn register(&'static self) -> Result<(), KernelError>
+    where
+        T: 'static,
+    {
+        let ops = MessageOps {
+            broadcast: Self::broadcast_raw,
+            receive: Self::receive_raw,
+            size: size_of::<T>(),
+            align: align_of::<T>(),
+        };
+        register(self as *const Self as usize, KernelObject::Message(ops))
+    }
+
     /// Broadcast the message to all reciever tasks
+    /// An unprivileged task can only broadcast a registered message, else the value is dropped.
     pub fn broadcast(&'static self, msg: T) {
-        critical_section(|cs| {
-            self.value.replace(msg);
-            self.semaphore
-                .signal_and_release_with_cs(cs, self.receivers);
-            #[cfg(feature = "system_logger")]
-            {
-                if logging::get_message_broadcast() {
-                    logging::report(LogEventType::MessageBroadcast(self.receivers));
-                }
+        if !is_privileged() {
+            let mut msg = ManuallyDrop::new(msg);
+            let res = syscall(
+                Syscall::MessageBroadcast,
+                [
+                    self as *const Self as usize,
+                    &*msg as *const T as usize,
+                    0,
+                ],
+            );
+            if res != 0 {
+                unsafe { ManuallyDrop::drop(&mut msg) };
             }
-        })
+            return;
+        }
+        critical_section(|cs| self.broadcast_with_cs(cs, msg))
+    }
+
+    fn broadcast_with_cs(&self, cs: &CriticalSection, msg: T) {
+        self.value.replace(msg);
+        self.semaphore
+            .signal_and_release_with_cs(cs, self.receivers);
+        #[cfg(feature = "system_logger")]
+        {
+            if logging::get_message_broadcast() {
+                logging::report(LogEventType::MessageBroadcast(self.receivers));
+            }
+        }
+    }
+
+    /// Returns the tasks released on a broadcast.
+    pub(crate) fn get_tasks(&self) -> BooleanVector {
+        self.semaphore.tasks
+    }
+
+    /// Broadcasts a clone of the value at `msg` through the message at `message`, the routine
+    /// behind a broadcast `EventAction`, which can not be generic over the message type.
+    pub(crate) unsafe fn broadcast_clone(cs: &CriticalSection, message: usize, msg: usize)
+    where
+        T: 'static,
+    {
+        let message: &'static Self = &*(message as *const Self);
+        message.broadcast_with_cs(cs, (*(msg as *const T)).clone());
+    }
+
+    /// Moves the value at `msg` into the message at `message` and broadcasts it, the routine
+    /// behind the `MessageBroadcast` system call.
+    unsafe fn broadcast_raw(message: usize, msg: usize) {
+        let message = &*(message as *const Self);
+        let msg = ptr::read(msg as *const T);
+        critical_section(|cs| message.broadcast_with_cs(cs, msg))
+    }
+
+    /// Writes a copy of the value of the message at `message` to `msg` if the running task
+    /// received it, the routine behind the `MessageReceive` system call.
+    unsafe fn receive_raw(message: usize, msg: usize) -> bool {
+        let message = &*(message as *const Self);
+        match message.receive_for(get_curr_tid()) {
+            Some(value) => {
+                ptr::write(msg as *mut T, value);
+                true
+            }
+            None => false,
+        }
     }
 
     /// Get a copy of the messsage on recieving a message
+    /// An unprivileged task can only receive a registered message.
     pub fn receive(&'static self, cxt: &Context) -> Option<T> {
-        critical_section(|_| match self.semaphore.unsafe_test_and_reset(cxt) {
-            Ok(res) if res == true => {
-                #[cfg(feature = "system_logger")]
-                {
-                    if logging::get_message_recieve() {
-                        logging::report(LogEventType::MessageRecieve(get_curr_tid() as u32));
-                    }
+        if !is_privileged() {
+            let mut msg = MaybeUninit::<T>::uninit();
+            let res = syscall(
+                Syscall::MessageReceive,
+                [self as *const Self as usize, msg.as_mut_ptr() as usize, 0],
+            );
+            return match decode_flag(res) {
+                Ok(true) => Some(unsafe { msg.assume_init() }),
+                _ => None,
+            };
+        }
+        self.receive_for(cxt.get_tid())
+    }
+
+    /// Returns a copy of the value if task `tid` received the message.
+    fn receive_for(&self, tid: TaskId) -> Option<T> {
+        critical_section(|_| {
+            if !self.semaphore.test_and_reset_for(tid) {
+                return None;
+            }
+            #[cfg(feature = "system_logger")]
+            {
+                if logging::get_message_recieve() {
+                    logging::report(LogEventType::MessageRecieve(tid as u32));
                 }
-                let x = self.value.borrow().clone();
-                return Some(x);
             }
-            _ => return None,
+            Some(self.value.borrow().clone())
         })
     }
 }
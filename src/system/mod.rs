@@ -3,14 +3,36 @@
 pub mod resource;
 pub mod message;
 pub mod semaphore;
+pub mod counting_semaphore;
+pub mod event_group;
+pub mod queue;
+pub mod registry;
 pub mod scheduler;
 mod pi_stack;
 
+#[cfg(feature="timer")]
+mod sleep_queue;
+
+#[cfg(feature="timer")]
+pub mod time;
+
+#[cfg(feature="timer")]
+pub mod soft_timer;
+
 #[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
 pub mod event;
 
+#[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64", feature = "interrupts"))]
+pub mod action;
+
+#[cfg(feature="interrupts")]
+pub mod interrupt;
+
 #[cfg(feature="system_logger")]
 pub mod system_logger;
 
 #[cfg(feature="task_monitor")]
-pub mod task_monitor;
\ No newline at end of file
+pub mod task_monitor;
+
+#[cfg(feature="mpu")]
+pub mod mpu;
\ No newline at end of file
@@ -0,0 +1,118 @@
+//! # Memory Protection
+//! The Definition of Data-structures required for isolating the memory of tasks.
+//! Each task is given a region covering its stack, further regions (e.g. the memory backing a
+//! `Resource` or `Message`) are shared with a set of tasks explicitly.
+
+use crate::config::{MAX_SHARED_REGIONS, MAX_TASKS, MPU_TASK_REGIONS};
+use crate::system::scheduler::{BooleanVector, TaskId};
+use crate::KernelError;
+
+/// A block of memory an MPU region can cover. The size is a power of two of at least 32 bytes
+/// and the base is aligned to the size.
+#[derive(Clone, Copy, Debug)]
+pub struct MemoryRegion {
+    pub base: usize,
+    pub size: usize,
+}
+
+impl MemoryRegion {
+    /// Returns the region covering `size` bytes from `base`, if an MPU region can cover exactly that.
+    pub fn new(base: usize, size: usize) -> Result<Self, KernelError> {
+        if size < 32 || !size.is_power_of_two() || base & (size - 1) != 0 {
+            return Err(KernelError::InvalidRegion);
+        }
+        Ok(Self { base, size })
+    }
+}
+
+/// A region shared with the tasks in `tasks_mask`.
+#[derive(Clone, Copy)]
+struct SharedRegion {
+    region: MemoryRegion,
+    tasks_mask: BooleanVector,
+}
+
+pub struct RegionTable {
+    /// The stack region of each task.
+    stacks: [Option<MemoryRegion>; MAX_TASKS],
+    /// The regions shared between tasks.
+    shared: [Option<SharedRegion>; MAX_SHARED_REGIONS],
+    /// Called with the TaskId of a task which violated its memory access permissions.
+    handler: Option<fn(TaskId)>,
+}
+
+impl RegionTable {
+    pub const fn new() -> Self {
+        Self {
+            stacks: [None; MAX_TASKS],
+            shared: [None; MAX_SHARED_REGIONS],
+            handler: None,
+        }
+    }
+
+    pub fn set_stack(&mut self, tid: TaskId, region: MemoryRegion) {
+        self.stacks[tid as usize] = Some(region);
+    }
+
+    /// Drops the stack region of task `tid` and removes it from the shared regions.
+    pub fn remove_task(&mut self, tid: TaskId) {
+        self.stacks[tid as usize] = None;
+        for shared in self.shared.iter_mut().flatten() {
+            shared.tasks_mask &= !(1 << tid);
+        }
+    }
+
+    pub fn set_handler(&mut self, handler: fn(TaskId)) {
+        self.handler = Some(handler);
+    }
+
+    pub fn get_handler(&self) -> Option<fn(TaskId)> {
+        self.handler
+    }
+
+    /// Shares `region` with the tasks in `tasks_mask`. Fails if a task would end up with more
+    /// shared regions than there are MPU regions left for them.
+    pub fn share(
+        &mut self,
+        region: MemoryRegion,
+        tasks_mask: BooleanVector,
+    ) -> Result<(), KernelError> {
+        for tid in 0..MAX_TASKS {
+            if tasks_mask & (1 << tid) != 0 && self.shared_count(tid as TaskId) == MPU_TASK_REGIONS {
+                return Err(KernelError::LimitExceeded);
+            }
+        }
+        match self.shared.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => {
+                *slot = Some(SharedRegion { region, tasks_mask });
+                Ok(())
+            }
+            None => Err(KernelError::LimitExceeded),
+        }
+    }
+
+    fn shared_count(&self, tid: TaskId) -> usize {
+        self.shared
+            .iter()
+            .flatten()
+            .filter(|shared| shared.tasks_mask & (1 << tid) != 0)
+            .count()
+    }
+
+    /// Returns the stack region of task `tid` followed by the regions shared with it.
+    pub fn get_regions(
+        &self,
+        tid: TaskId,
+    ) -> (Option<MemoryRegion>, [Option<MemoryRegion>; MPU_TASK_REGIONS]) {
+        let mut regions = [None; MPU_TASK_REGIONS];
+        let shared = self
+            .shared
+            .iter()
+            .flatten()
+            .filter(|shared| shared.tasks_mask & (1 << tid) != 0);
+        for (slot, shared) in regions.iter_mut().zip(shared) {
+            *slot = Some(shared.region);
+        }
+        (self.stacks[tid as usize], regions)
+    }
+}
@@ -0,0 +1,433 @@
+//! # Message Queue
+//!
+//! A bounded FIFO queue of `N` messages held in static storage. Unlike `Message`, which holds a
+//! single value, a queue keeps every message sent until it is received, up to its capacity. When
+//! the queue is full, a send is rejected or overwrites the oldest message, as given by its
+//! `OverflowPolicy`. Tasks blocked on a queue are held in the `waiting_tasks` vector of the
+//! Scheduler, each send or receive wakes the highest priority task waiting on the other end.
+
+use core::cell::RefCell;
+use core::mem::{align_of, size_of, ManuallyDrop, MaybeUninit};
+use core::ptr;
+
+use crate::kernel::syscall::{decode_flag, register, syscall, BlockingArgs, Syscall};
+use crate::kernel::tasks::{preempt, schedule, TaskManager};
+use crate::system::registry::{KernelObject, QueueOps};
+use crate::system::scheduler::BooleanVector;
+use crate::utils::arch::{critical_section, is_privileged, CriticalSection};
+use crate::KernelError;
+
+#[cfg(feature = "timer")]
+use crate::{kernel::timer::get_time, system::time::Duration};
+
+/// Option of a send or receive passed to the `QueueSend` and `QueueReceive` system calls, which
+/// blocks the task rather than fail if the queue is full or empty.
+pub(crate) const BLOCK: usize = 1 << 0;
+
+/// What a send does when the queue is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// The message is rejected, or the sender blocks until there is room for it.
+    Reject,
+    /// The oldest message is dropped to make room for the new one, sends never block.
+    OverwriteOldest,
+}
+
+struct Buffer<T, const N: usize> {
+    messages: [MaybeUninit<T>; N],
+    /// The index of the oldest message.
+    head: usize,
+    /// The number of messages held.
+    len: usize,
+    /// The tasks waiting for a message.
+    receivers: BooleanVector,
+    /// The tasks waiting for room to send a message.
+    senders: BooleanVector,
+}
+
+impl<T, const N: usize> Buffer<T, N> {
+    fn push(&mut self, msg: T) {
+        let tail = (self.head + self.len) % N;
+        self.messages[tail] = MaybeUninit::new(msg);
+        self.len += 1;
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        // The slot is initialized as it is within the `len` messages from `head`, and it is
+        // vacated right after being read.
+        let msg = unsafe { self.messages[self.head].as_ptr().read() };
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(msg)
+    }
+}
+
+impl<T, const N: usize> Drop for Buffer<T, N> {
+    /// Drops the messages which were never received.
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+/// Holds the messages of the queue along with the tasks waiting on it.
+pub struct Queue<T, const N: usize> {
+    buffer: RefCell<Buffer<T, N>>,
+    policy: OverflowPolicy,
+}
+
+impl<T, const N: usize> Queue<T, N> {
+    /// Fails to compile for a queue without room for a message.
+    const HAS_ROOM: () = assert!(N > 0, "a queue must hold at least one message");
+
+    /// Initializes a new empty queue.
+    pub const fn new(policy: OverflowPolicy) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::HAS_ROOM;
+        Self {
+            buffer: RefCell::new(Buffer {
+                // An array of `MaybeUninit` needs no initialization.
+                messages: unsafe { MaybeUninit::uninit().assume_init() },
+                head: 0,
+                len: 0,
+                receivers: 0,
+                senders: 0,
+            }),
+            policy,
+        }
+    }
+
+    /// Registers the queue with the Kernel, so that unprivileged tasks can send and receive
+    /// through it. The routines of its type are recorded along with it, as system calls can not
+    /// be generic over the message type. Only privileged code can register queues.
+    pub fn register(&'static self) -> Result<(), KernelError>
+    where
+        T: 'static,
+    {
+        let ops = QueueOps {
+            send: Self::send_raw,
+            receive: Self::receive_raw,
+            end_wait: Self::end_wait_raw,
+            len: Self::len_raw,
+            size: size_of::<T>(),
+            align: align_of::<T>(),
+        };
+        register(self.addr(), KernelObject::Queue(ops))
+    }
+
+    /// Returns the number of messages held, none are held by a queue an unprivileged task can
+    /// not use.
+    pub fn len(&'static self) -> usize {
+        if !is_privileged() {
+            return syscall(Syscall::QueueLen, [self.addr(), 0, 0]);
+        }
+        critical_section(|_| self.buffer.borrow().len)
+    }
+
+    /// Returns whether the queue holds no messages.
+    pub fn is_empty(&'static self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends `msg` to the queue if there is room for it. With `OverwriteOldest` the oldest
+    /// message makes room for it, else it fails with `LimitExceeded` and `msg` is dropped. Can be
+    /// called from interrupt handlers.
+    pub fn try_send(&'static self, msg: T) -> Result<(), KernelError> {
+        self.send_until(msg, false, None)
+    }
+
+    /// Appends `msg` to the queue, blocks the running task while the queue is full under the
+    /// `Reject` policy. Fails with `AccessDenied` if the idle task would have to block, as it
+    /// can not. Must not be called from interrupt handlers.
+    pub fn send(&'static self, msg: T) -> Result<(), KernelError> {
+        self.send_until(msg, true, None)
+    }
+
+    /// Like `send`, but gives up once `timeout` has passed without room for `msg`.
+    #[cfg(feature = "timer")]
+    pub fn send_timeout(&'static self, msg: T, timeout: Duration) -> Result<(), KernelError> {
+        let timeout_time = (get_time() + timeout).ticks();
+        self.send_until(msg, true, Some(timeout_time))
+    }
+
+    /// Removes and returns the oldest message, fails with `Empty` if there is none. Can be called
+    /// from interrupt handlers.
+    pub fn try_receive(&'static self) -> Result<T, KernelError> {
+        self.receive_until(false, None)
+    }
+
+    /// Removes and returns the oldest message, blocks the running task until one is sent if
+    /// the queue is empty. Fails with `AccessDenied` if the idle task would have to block, as it
+    /// can not. Must not be called from interrupt handlers.
+    pub fn receive(&'static self) -> Result<T, KernelError> {
+        self.receive_until(true, None)
+    }
+
+    /// Like `receive`, but gives up once `timeout` has passed without a message being sent.
+    #[cfg(feature = "timer")]
+    pub fn receive_timeout(&'static self, timeout: Duration) -> Result<T, KernelError> {
+        let timeout_time = (get_time() + timeout).ticks();
+        self.receive_until(true, Some(timeout_time))
+    }
+
+    /// Sends a clone of the value at `msg` to the queue at `queue`, the routine behind a post
+    /// `EventAction`, which can not be generic over the queue type. Nothing is sent if the
+    /// queue is full under the `Reject` policy.
+    pub(crate) unsafe fn post_clone(cs: &CriticalSection, queue: usize, msg: usize)
+    where
+        T: Clone + 'static,
+    {
+        let queue: &'static Self = &*(queue as *const Self);
+        queue.push_with_cs(cs, || (*(msg as *const T)).clone());
+    }
+
+    /// Appends the message made by `msg` and wakes the highest priority receiver, returns false
+    /// without making it if the queue is full under the `Reject` policy.
+    fn push_with_cs(&'static self, cs_token: &CriticalSection, msg: impl FnOnce() -> T) -> bool {
+        let buffer = &mut *self.buffer.borrow_mut();
+        if buffer.is_full() {
+            if self.policy == OverflowPolicy::Reject {
+                return false;
+            }
+            drop(buffer.pop());
+        }
+        buffer.push(msg());
+        wake_highest(cs_token, self.addr(), &mut buffer.receivers);
+        true
+    }
+
+    /// Sends `msg`, blocking the running task while the queue is full if `block` is set, until
+    /// it is woken up by a receive or its timeout at `timeout_time`. A woken sender tries again,
+    /// as the room may have been taken by another task in the meantime. An unprivileged task can
+    /// only send through a registered queue, else `msg` is dropped.
+    fn send_until(
+        &'static self,
+        msg: T,
+        block: bool,
+        timeout_time: Option<u64>,
+    ) -> Result<(), KernelError> {
+        if !is_privileged() {
+            let mut msg = ManuallyDrop::new(msg);
+            loop {
+                let value = &*msg as *const T as usize;
+                let mut send = BlockingArgs::new(value, block as usize * BLOCK, timeout_time);
+                let args = [self.addr(), &mut send as *mut BlockingArgs as usize, 0];
+                match decode_flag(syscall(Syscall::QueueSend, args)) {
+                    // The message has been moved into the queue.
+                    Ok(true) => return Ok(()),
+                    Ok(false) => {
+                        syscall(Syscall::QueueWaitEnd, args);
+                    }
+                    Err(err) => {
+                        unsafe { ManuallyDrop::drop(&mut msg) };
+                        return Err(err);
+                    }
+                }
+            }
+        }
+        let mut msg = Some(msg);
+        loop {
+            // The message is only taken once there is room for it.
+            if self.begin_send(|| msg.take().unwrap(), block, timeout_time)? {
+                return Ok(());
+            }
+            preempt();
+            self.end_wait();
+        }
+    }
+
+    /// Receives a message, blocking the running task while the queue is empty if `block` is set,
+    /// until it is woken up by a send or its timeout at `timeout_time`. A woken receiver tries
+    /// again, as the message may have been taken by another task in the meantime. An
+    /// unprivileged task can only receive through a registered queue.
+    fn receive_until(
+        &'static self,
+        block: bool,
+        timeout_time: Option<u64>,
+    ) -> Result<T, KernelError> {
+        if !is_privileged() {
+            let mut msg = MaybeUninit::<T>::uninit();
+            loop {
+                let value = msg.as_mut_ptr() as usize;
+                let mut receive = BlockingArgs::new(value, block as usize * BLOCK, timeout_time);
+                let args = [self.addr(), &mut receive as *mut BlockingArgs as usize, 0];
+                if decode_flag(syscall(Syscall::QueueReceive, args))? {
+                    // The message has been written by the Kernel.
+                    return Ok(unsafe { msg.assume_init() });
+                }
+                syscall(Syscall::QueueWaitEnd, args);
+            }
+        }
+        loop {
+            if let Some(msg) = self.begin_receive(block, timeout_time)? {
+                return Ok(msg);
+            }
+            preempt();
+            self.end_wait();
+        }
+    }
+
+    /// Sends the message made by `msg` and returns true if there is room for it, else makes the
+    /// running task wait for room and returns false if `block` is set. The task is switched out
+    /// once it leaves the Kernel.
+    pub(crate) fn begin_send(
+        &'static self,
+        msg: impl FnOnce() -> T,
+        block: bool,
+        timeout_time: Option<u64>,
+    ) -> Result<bool, KernelError> {
+        critical_section(|cs_token| {
+            if self.push_with_cs(cs_token, msg) {
+                return Ok(true);
+            }
+            if !block {
+                return Err(KernelError::LimitExceeded);
+            }
+            self.wait(cs_token, timeout_time, |buffer| &mut buffer.senders)?;
+            Ok(false)
+        })
+    }
+
+    /// Removes and returns the oldest message and wakes the highest priority sender, else makes
+    /// the running task wait for a message and returns `None` if `block` is set. The task is
+    /// switched out once it leaves the Kernel.
+    pub(crate) fn begin_receive(
+        &'static self,
+        block: bool,
+        timeout_time: Option<u64>,
+    ) -> Result<Option<T>, KernelError> {
+        critical_section(|cs_token| {
+            {
+                let buffer = &mut *self.buffer.borrow_mut();
+                if let Some(msg) = buffer.pop() {
+                    wake_highest(cs_token, self.addr(), &mut buffer.senders);
+                    return Ok(Some(msg));
+                }
+            }
+            if !block {
+                return Err(KernelError::Empty);
+            }
+            self.wait(cs_token, timeout_time, |buffer| &mut buffer.receivers)?;
+            Ok(None)
+        })
+    }
+
+    /// Adds the running task to the waiters given by `waiters` and blocks it. Fails with
+    /// `TimedOut` if `timeout_time` has passed, and with `AccessDenied` for the idle task.
+    fn wait(
+        &'static self,
+        cs_token: &CriticalSection,
+        timeout_time: Option<u64>,
+        waiters: fn(&mut Buffer<T, N>) -> &mut BooleanVector,
+    ) -> Result<(), KernelError> {
+        if is_expired(timeout_time) {
+            return Err(KernelError::TimedOut);
+        }
+        let handler = &mut TaskManager.borrow(cs_token).borrow_mut();
+        let tid = handler.curr_tid;
+        if tid == 0 {
+            return Err(KernelError::AccessDenied);
+        }
+        *waiters(&mut *self.buffer.borrow_mut()) |= 1 << tid;
+        match timeout_time {
+            #[cfg(feature = "timer")]
+            Some(timeout_time) => handler.wait_until(tid, self.addr(), timeout_time),
+            _ => handler.wait(tid, self.addr()),
+        }
+        Ok(())
+    }
+
+    /// Removes the running task from the waiters once it runs again, in case it timed out.
+    pub(crate) fn end_wait(&'static self) {
+        critical_section(|cs_token| {
+            let tid = TaskManager.borrow(cs_token).borrow().curr_tid;
+            let buffer = &mut *self.buffer.borrow_mut();
+            buffer.senders &= !(1 << tid);
+            buffer.receivers &= !(1 << tid);
+        })
+    }
+
+    /// Moves the value at `msg` into the queue at `queue` if there is room for it, the routine
+    /// behind the `QueueSend` system call. The value is left to the task otherwise.
+    unsafe fn send_raw(
+        queue: usize,
+        msg: usize,
+        block: bool,
+        timeout_time: Option<u64>,
+    ) -> Result<bool, KernelError>
+    where
+        T: 'static,
+    {
+        let queue: &'static Self = &*(queue as *const Self);
+        queue.begin_send(|| ptr::read(msg as *const T), block, timeout_time)
+    }
+
+    /// Moves the oldest message of the queue at `queue` to `msg`, the routine behind the
+    /// `QueueReceive` system call.
+    unsafe fn receive_raw(
+        queue: usize,
+        msg: usize,
+        block: bool,
+        timeout_time: Option<u64>,
+    ) -> Result<bool, KernelError>
+    where
+        T: 'static,
+    {
+        let queue: &'static Self = &*(queue as *const Self);
+        match queue.begin_receive(block, timeout_time)? {
+            Some(value) => {
+                ptr::write(msg as *mut T, value);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    unsafe fn end_wait_raw(queue: usize)
+    where
+        T: 'static,
+    {
+        (*(queue as *const Self)).end_wait()
+    }
+
+    unsafe fn len_raw(queue: usize) -> usize
+    where
+        T: 'static,
+    {
+        (*(queue as *const Self)).len()
+    }
+
+    /// Identifies the queue to the Scheduler, which keeps what each task waits on.
+    fn addr(&'static self) -> usize {
+        self as *const Self as usize
+    }
+}
+
+/// Wakes the highest priority task in `waiters` still waiting on the queue at `object` and
+/// removes it from them.
+fn wake_highest(cs_token: &CriticalSection, object: usize, waiters: &mut BooleanVector) {
+    let handler = &mut TaskManager.borrow(cs_token).borrow_mut();
+    *waiters = handler.get_waiters(object, *waiters);
+    if let Some(tid) = handler.get_highest(*waiters) {
+        *waiters &= !(1 << tid);
+        handler.notify(1 << tid);
+        schedule(handler.is_preemptive);
+    }
+}
+
+/// Returns whether `timeout_time` has passed, never without a timeout.
+fn is_expired(timeout_time: Option<u64>) -> bool {
+    match timeout_time {
+        #[cfg(feature = "timer")]
+        Some(timeout_time) => get_time().ticks() >= timeout_time,
+        _ => false,
+    }
+}
+
+unsafe impl<T: Send, const N: usize> Sync for Queue<T, N> {}
@@ -0,0 +1,98 @@
+//! # Object Registry
+//! The Definition of the table of Kernel objects unprivileged tasks may pass to system calls.
+//! A system call only acts on an object found in the table, and takes what it needs to know
+//! about the object from its entry, which was filled in by privileged code. Hence a task can
+//! neither forge an object nor make the Kernel call code at an address of its choosing.
+
+use crate::config::MAX_KERNEL_OBJECTS;
+use crate::system::scheduler::BooleanVector;
+use crate::KernelError;
+
+/// The type erased routines of a `Message`, taken from the generic message when it is registered.
+#[derive(Clone, Copy)]
+pub struct MessageOps {
+    /// Moves the value at the second address into the message at the first one and broadcasts it.
+    pub broadcast: unsafe fn(usize, usize),
+    /// Writes the value of the message at the first address to the second one, if the running
+    /// task received it.
+    pub receive: unsafe fn(usize, usize) -> bool,
+    /// The size of the value of the message.
+    pub size: usize,
+    /// The alignment of the value of the message.
+    pub align: usize,
+}
+
+/// The type erased routines of a `Queue`, taken from the generic queue when it is registered.
+#[derive(Clone, Copy)]
+pub struct QueueOps {
+    /// Moves the value at the second address into the queue at the first one if there is room
+    /// for it, else makes the running task wait for room if it is to block.
+    pub send: unsafe fn(usize, usize, bool, Option<u64>) -> Result<bool, KernelError>,
+    /// Moves the oldest message of the queue at the first address to the second one, else makes
+    /// the running task wait for a message if it is to block.
+    pub receive: unsafe fn(usize, usize, bool, Option<u64>) -> Result<bool, KernelError>,
+    /// Ends the wait of the running task on the queue.
+    pub end_wait: unsafe fn(usize),
+    /// Returns the number of messages held by the queue.
+    pub len: unsafe fn(usize) -> usize,
+    /// The size of a message.
+    pub size: usize,
+    /// The alignment of a message.
+    pub align: usize,
+}
+
+/// A registered Kernel object along with what the Kernel needs to know about it.
+#[derive(Clone, Copy)]
+pub enum KernelObject {
+    /// A `Resource` accessible to the tasks in the mask.
+    Resource(BooleanVector),
+    Semaphore,
+    Message(MessageOps),
+    EventGroup,
+    CountingSemaphore,
+    Queue(QueueOps),
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    address: usize,
+    object: KernelObject,
+}
+
+pub struct ObjectTable {
+    entries: [Option<Entry>; MAX_KERNEL_OBJECTS],
+}
+
+impl ObjectTable {
+    pub const fn new() -> Self {
+        Self {
+            entries: [None; MAX_KERNEL_OBJECTS],
+        }
+    }
+
+    /// Records `object` at `address`. Registering an address again replaces its entry.
+    pub fn register(&mut self, address: usize, object: KernelObject) -> Result<(), KernelError> {
+        let slot = match self.entries.iter().position(|entry| match entry {
+            Some(entry) => entry.address == address,
+            None => false,
+        }) {
+            Some(slot) => slot,
+            None => self
+                .entries
+                .iter()
+                .position(|entry| entry.is_none())
+                .ok_or(KernelError::LimitExceeded)?,
+        };
+        self.entries[slot] = Some(Entry { address, object });
+        Ok(())
+    }
+
+    /// Returns the object registered at `address`.
+    pub fn get(&self, address: usize) -> Option<KernelObject> {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|entry| entry.address == address)
+            .map(|entry| entry.object)
+    }
+}
@@ -3,13 +3,12 @@
 //! Defines the Kernel routines and primitives for resource management.
 use core::cell::RefCell;
 
-use cortex_m::asm::bkpt;
-
-use crate::kernel::tasks::{get_curr_tid, schedule, TaskManager};
+use crate::kernel::syscall::{decode, register, syscall, Syscall};
+use crate::kernel::tasks::{schedule, TaskManager};
 use crate::system::pi_stack::PiStack;
+use crate::system::registry::KernelObject;
 use crate::system::scheduler::{BooleanVector, Context, TaskId};
-use crate::utils::arch::{critical_section, Mutex};
-use crate::utils::helpers::get_msb_const;
+use crate::utils::arch::{critical_section, is_privileged, Mutex};
 use crate::KernelError;
 
 #[cfg(feature = "system_logger")]
@@ -20,11 +19,13 @@ static PiStackGlobal: Mutex<RefCell<PiStack>> = Mutex::new(RefCell::new(PiStack:
 
 /// A Safe Container to store a resource, it can hold resource of any Generic Type
 /// and allow safe access to it without ending up in Data races or Deadlocks.
+/// Under earliest deadline first scheduling, the priorities of the tasks are their preemption
+/// levels, so the ceiling of the resource is its Stack Resource Policy preemption ceiling.
+/// A resource shared by unprivileged tasks has to be registered first, see `register`.
 #[derive(Debug)]
 pub struct Resource<T: Sized> {
-    /// An boolean vector holding which tasks have access to the resource.
-    ceiling: TaskId,
-    /// It holds the priority of the highest priority task that can access that resource.
+    /// An boolean vector holding which tasks have access to the resource. Its ceiling, the
+    /// priority of the highest priority task that can access it, is looked up on locking.
     tasks_mask: BooleanVector,
     /// This field holds the actual resource that has to be locked.
     inner: T,
@@ -37,70 +38,37 @@ impl<T: Sized> Resource<T> {
         Self {
             inner: val,
             tasks_mask: tasks_mask,
-            ceiling: get_msb_const(tasks_mask) as TaskId,
         }
     }
 
-    /// Returns the `Pi_mask`, which is just a boolean vector with all bits up to ceiling (including) set to 1.
-    fn get_pi_mask(ceiling: TaskId) -> u32 {
-        let mask;
-        if ceiling < 31 {
-            mask = (1 << (ceiling + 1)) - 1;
-        } else {
-            mask = 0xffffffff
-        }
-        mask
+    /// Registers the resource with the Kernel, so that unprivileged tasks can lock it. The tasks
+    /// which have access to it are recorded, the ceiling of a registered resource never follows
+    /// its memory. Only privileged code can register resources.
+    pub fn register(&'static self) -> Result<(), KernelError> {
+        register(
+            self as *const Self as usize,
+            KernelObject::Resource(self.tasks_mask),
+        )
     }
 
     /// Lock the Resource for the currently running task and blocks the competing tasks
     fn lock(&self, cxt: &Context) -> Result<&T, KernelError> {
-        critical_section(|cs_token| {
-            let pi_stack = &mut PiStackGlobal.borrow(cs_token).borrow_mut();
-            let curr_tid = cxt.get_tid() as u32;
-
-            let ceiling = self.ceiling;
-            let pid_mask = 1 << curr_tid;
-            if self.tasks_mask & pid_mask != pid_mask {
-                return Err(KernelError::AccessDenied);
-            }
-            if ceiling as i32 > pi_stack.system_ceiling {
-                pi_stack.push_stack(ceiling)?;
-                let mask = Self::get_pi_mask(ceiling) & !(1 << curr_tid);
-                TaskManager.borrow(cs_token).borrow_mut().block_tasks(mask);
-                #[cfg(feature = "system_logger")]
-                {
-                    if logging::get_resource_lock() {
-                        logging::report(LogEventType::ResourceLock(curr_tid));
-                    }
-                }
-                return Ok(&self.inner);
-            }
-            return Err(KernelError::AccessDenied);
-        })
+        if !is_privileged() {
+            let resource = self as *const Self as usize;
+            decode(syscall(Syscall::ResourceLock, [resource, 0, 0]))?;
+        } else {
+            lock_resource(self.tasks_mask, cxt.get_tid())?;
+        }
+        Ok(&self.inner)
     }
 
     /// Unlocks the Resource and unblocks the tasks which were blocked during the call to lock
-    fn unlock(&self) -> Result<(), KernelError> {
-        let is_preemptive = critical_section(|cs_token| {
-            let pi_stack = &mut PiStackGlobal.borrow(cs_token).borrow_mut();
-            if self.ceiling as i32 == pi_stack.system_ceiling {
-                pi_stack.pop_stack()?;
-                let mask = Self::get_pi_mask(self.ceiling);
-                TaskManager
-                    .borrow(cs_token)
-                    .borrow_mut()
-                    .unblock_tasks(mask);
-            }
-            #[cfg(feature = "system_logger")]
-            {
-                if logging::get_resource_unlock() {
-                    logging::report(LogEventType::ResourceUnlock(get_curr_tid() as u32));
-                }
-            }
-            Ok(TaskManager.borrow(cs_token).borrow_mut().is_preemptive)
-        })?;
-        schedule(is_preemptive);
-        Ok(())
+    fn unlock(&self, cxt: &Context) -> Result<(), KernelError> {
+        if !is_privileged() {
+            let resource = self as *const Self as usize;
+            return decode(syscall(Syscall::ResourceUnlock, [resource, 0, 0]));
+        }
+        unlock_resource(self.tasks_mask, cxt.get_tid())
     }
     /// A helper function that ensures that if a resource is locked, it is unlocked.
     pub fn acquire<F, R>(&self, cxt: &Context, mut handler: F) -> Result<R, KernelError>
@@ -113,9 +81,82 @@ impl<T: Sized> Resource<T> {
         }
         let value = self.lock(cxt)?;
         let res = handler(value);
-        self.unlock()?;
+        self.unlock(cxt)?;
         return Ok(res);
     }
 }
 
 unsafe impl<T> Sync for Resource<T> {}
+
+/// Returns true if any resource is locked, i.e. tasks are blocked based on the current priorities.
+pub(crate) fn is_any_locked() -> bool {
+    critical_section(|cs_token| PiStackGlobal.borrow(cs_token).borrow().system_ceiling >= 0)
+}
+
+/// Locks the resource accessible to `tasks_mask` for task `curr_tid` and blocks the competing tasks,
+/// i.e. all tasks with a priority up to the ceiling of the resource (including).
+/// The type independent part of `Resource::lock`, which is also the `ResourceLock` system call.
+pub(crate) fn lock_resource(tasks_mask: BooleanVector, curr_tid: TaskId) -> Result<(), KernelError> {
+    critical_section(|cs_token| {
+        let pi_stack = &mut PiStackGlobal.borrow(cs_token).borrow_mut();
+        let scheduler = &mut TaskManager.borrow(cs_token).borrow_mut();
+
+        let pid_mask = 1 << curr_tid;
+        if tasks_mask & pid_mask != pid_mask {
+            return Err(KernelError::AccessDenied);
+        }
+        let ceiling = scheduler.get_ceiling(tasks_mask);
+        if ceiling as i32 > pi_stack.system_ceiling {
+            pi_stack.push_stack(ceiling)?;
+            // The idle task is never blocked, so that there is always a task to run.
+            let mask = scheduler.get_tasks_upto(ceiling) & !(1 << curr_tid) & !1;
+            scheduler.block_tasks(mask);
+            #[cfg(feature = "system_logger")]
+            {
+                if logging::get_block_tasks() {
+                    logging::report(LogEventType::BlockTasks(mask));
+                }
+                if logging::get_resource_lock() {
+                    logging::report(LogEventType::ResourceLock(curr_tid));
+                }
+            }
+            return Ok(());
+        }
+        return Err(KernelError::AccessDenied);
+    })
+}
+
+/// Unlocks the resource accessible to `tasks_mask` for task `curr_tid` and unblocks the tasks which
+/// were blocked during the call to lock. Only the last locked resource can be unlocked, by a task
+/// which has access to it. The type independent part of `Resource::unlock`, which is also the
+/// `ResourceUnlock` system call.
+pub(crate) fn unlock_resource(tasks_mask: BooleanVector, curr_tid: TaskId) -> Result<(), KernelError> {
+    let is_preemptive = critical_section(|cs_token| {
+        let pi_stack = &mut PiStackGlobal.borrow(cs_token).borrow_mut();
+        let scheduler = &mut TaskManager.borrow(cs_token).borrow_mut();
+
+        let pid_mask = 1 << curr_tid;
+        if tasks_mask & pid_mask != pid_mask {
+            return Err(KernelError::AccessDenied);
+        }
+        let ceiling = scheduler.get_ceiling(tasks_mask);
+        if ceiling as i32 != pi_stack.system_ceiling {
+            return Err(KernelError::AccessDenied);
+        }
+        pi_stack.pop_stack()?;
+        let mask = scheduler.get_tasks_upto(ceiling) & !1;
+        scheduler.unblock_tasks(mask);
+        #[cfg(feature = "system_logger")]
+        {
+            if logging::get_unblock_tasks() {
+                logging::report(LogEventType::UnblockTasks(mask));
+            }
+            if logging::get_resource_unlock() {
+                logging::report(LogEventType::ResourceUnlock(curr_tid));
+            }
+        }
+        Ok(scheduler.is_preemptive)
+    })?;
+    schedule(is_preemptive);
+    Ok(())
+}
@@ -2,33 +2,64 @@
 //! The Definition of Data-structures required for task management.
 //!
 
-#[macro_use]
 use arr_macro::arr;
-use cortex_m::asm::bkpt;
 
-use crate::utils::arch::{get_msb, load_context, save_context, wait_for_interrupt};
+use crate::utils::arch::{get_msb, init_stack, load_context, save_context};
 use crate::KernelError;
-use crate::{config::MAX_TASKS, utils::arch::set_pendsv};
+use crate::config::{MAX_TASKS, STACK_PAINT, TIME_SLICE};
+use core::ptr::read_volatile;
+use core::slice;
 
 #[cfg(feature = "task_monitor")]
-use crate::kernel::task_monitor::{clear_deadline, set_deadline};
-use cortex_m_semihosting::hprintln;
+use crate::{
+    kernel::task_monitor::set_deadline,
+    system::time::Duration,
+};
+
+#[cfg(feature = "mpu")]
+use crate::kernel::mpu;
+
+#[cfg(feature = "timer")]
+use crate::{kernel::timer::idle, system::sleep_queue::SleepQueue};
+
+#[cfg(not(feature = "timer"))]
+use crate::utils::arch::wait_for_interrupt;
 
 pub type TaskId = u32;
+/// The priority of a task, tasks with a higher value take precedence. Several tasks can share a priority.
+pub type Priority = u32;
+/// A bitmap with a bit per task, the bit at the position of a TaskId corresponds to that task.
+#[cfg(not(feature = "tasks_64"))]
 pub type BooleanVector = u32;
+#[cfg(feature = "tasks_64")]
+pub type BooleanVector = u64;
+
+/// The policy by which the Scheduler picks the next task amongst the tasks in ready state.
+#[cfg(feature = "task_monitor")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SchedulingPolicy {
+    /// The highest priority task is scheduled, tasks of the same priority take turns.
+    FixedPriority,
+    /// The task with the earliest absolute deadline is scheduled, tasks without a deadline are
+    /// scheduled by priority once no task with a deadline is ready. The priority of a task serves
+    /// as its Stack Resource Policy preemption level, hence tasks with a shorter relative deadline
+    /// have to be given a higher priority. A task only preempts the running task if its preemption
+    /// level is above the ceilings of all locked resources, so locking stays deadlock-free.
+    EarliestDeadlineFirst,
+}
 
 #[repr(C)]
 #[derive(Debug)]
 pub struct Context {
-    priority: TaskId,
+    tid: TaskId,
 }
 
 impl Context {
-    pub(crate) const fn new(priority: TaskId) -> Self {
-        Context { priority }
+    pub(crate) const fn new(tid: TaskId) -> Self {
+        Context { tid }
     }
     pub fn get_tid(&self) -> TaskId {
-        self.priority
+        self.tid
     }
 }
 
@@ -54,34 +85,132 @@ pub struct Scheduler {
     /// A variable which decided if the scheduler should preemptively schedule tasks or not.
     pub is_preemptive: bool,
     pub preempt_disable_count: u32,
+    /// A boolean vector in which, if a bit at a position is true, it implies that the task is suspended and cannot be scheduled until it is resumed.
+    pub suspended_tasks: BooleanVector,
+    /// A boolean vector in which, if a bit at a position is true, it implies that the task sleeps and cannot be scheduled until its wake-up time.
+    #[cfg(feature = "timer")]
+    pub sleeping_tasks: BooleanVector,
+    /// A boolean vector in which, if a bit at a position is true, it implies that the task waits on a primitive and cannot be scheduled until it is notified or its timeout expires.
+    pub waiting_tasks: BooleanVector,
+    /// The address of the primitive each task last waited on, zero if none. A primitive only
+    /// wakes the tasks still waiting on it, see `get_waiters`.
+    wait_objects: [usize; MAX_TASKS],
+    /// The sleeping tasks ordered by their wake-up time.
+    #[cfg(feature = "timer")]
+    sleep_queue: SleepQueue,
+    /// The period of each task in ticks, zero if the task is not periodic.
+    #[cfg(feature = "timer")]
+    periods: [u64; MAX_TASKS],
+    /// The number of releases of each periodic task which found it still active.
+    #[cfg(feature = "timer")]
+    overruns: [u32; MAX_TASKS],
+    /// The periodic tasks ordered by their next release time.
+    #[cfg(feature = "timer")]
+    release_queue: SleepQueue,
+    /// A boolean vector per priority, holding the tasks of that priority.
+    priority_masks: [BooleanVector; MAX_TASKS],
+    /// A boolean vector in which, if a bit at a position is true, it implies that there are tasks of that priority.
+    priority_levels: BooleanVector,
+    /// The TaskId of the task whose turn it is, per priority. Ready tasks of the same priority
+    /// take turns in decreasing order of their TaskId.
+    turns: [usize; MAX_TASKS],
+    /// Number of ticks a task runs before the next ready task of the same priority takes its turn, zero disables round-robin scheduling.
+    pub time_slice: u32,
+    /// Ticks left in the time slice of the currently running task.
+    slice_left: u32,
+    /// A boolean vector in which, if a bit at a position is true, it implies that the task violated its memory access permissions and is never scheduled again.
+    #[cfg(feature = "mpu")]
+    pub faulted_tasks: BooleanVector,
+    /// The policy by which the next task is picked.
+    #[cfg(feature = "task_monitor")]
+    pub policy: SchedulingPolicy,
+    /// The absolute deadline in ticks of each task in `deadline_tasks`. A missed deadline is kept
+    /// until the task exits, the task monitor only reports the miss.
+    #[cfg(feature = "task_monitor")]
+    deadlines: [u64; MAX_TASKS],
+    /// A boolean vector of the tasks with a pending absolute deadline.
+    #[cfg(feature = "task_monitor")]
+    deadline_tasks: BooleanVector,
+    /// The task of `deadline_tasks` with the earliest absolute deadline, kept up to date as
+    /// deadlines are set and cleared so that `get_next_tid` seldom searches for it.
+    #[cfg(feature = "task_monitor")]
+    earliest_deadline: Option<usize>,
+    /// Called with the TaskId of a task whose stack overflowed.
+    pub overflow_handler: Option<fn(TaskId)>,
+    /// The run time statistics of each task.
+    stats: [TaskStats; MAX_TASKS],
+    /// The value of `get_cycles` when the run time was last accounted for.
+    last_cycles: u32,
     contexts: [Option<Context>; MAX_TASKS],
 }
 
-/// A single tasks's state
-#[cfg(not(feature = "task_monitor"))]
-#[derive(Clone, Copy)]
-// #[repr(C)]
-pub struct TaskControlBlock {
-    /// Holds a reference to the stack pointer for the task.
-    stack_pointer: usize, // current stack pointer of this thread
+/// Run time statistics of a task. Run time is counted in CPU cycles on ports with a cycle
+/// counter (the DWT on Cortex-M3/M4) and in ticks on the others.
+#[derive(Clone, Copy, Debug)]
+pub struct TaskStats {
+    /// Total time the task has been running.
+    pub run_time: u64,
+    /// Number of times the task has been switched in.
+    pub context_switches: u32,
+    /// Number of times the task has been switched out while still ready, i.e. preempted.
+    pub preemptions: u32,
 }
 
-#[cfg(feature = "task_monitor")]
+impl TaskStats {
+    pub const fn new() -> Self {
+        Self {
+            run_time: 0,
+            context_switches: 0,
+            preemptions: 0,
+        }
+    }
+}
+
+/// A single tasks's state
 #[derive(Clone, Copy)]
 #[repr(C)]
 pub struct TaskControlBlock {
     /// Holds a reference to the stack pointer for the task.
-    stack_pointer: usize, // current stack pointer of this thread
-    deadline: u32,
+    pub(crate) stack_pointer: usize, // current stack pointer of this thread
+    /// False if the task runs in unprivileged Thread mode.
+    pub(crate) privileged: bool,
+    pub(crate) priority: Priority,
+    /// The deadline of the task relative to its release, zero if it has none.
+    #[cfg(feature = "task_monitor")]
+    pub(crate) deadline: Duration,
+    /// Address of the lowest word of the stack, which holds the canary.
+    pub(crate) stack_base: usize,
+    /// Size of the stack in words.
+    pub(crate) stack_size: usize,
 }
 
 impl TaskControlBlock {
+    /// Returns the stack pointer of the task, as returned by `Port::init_stack` or saved on the
+    /// last context switch.
+    pub fn stack_pointer(&self) -> usize {
+        self.stack_pointer
+    }
     pub fn save_context(&self) {
         save_context(self)
     }
     pub fn load_context(&self) {
         load_context(self)
     }
+    /// Returns true if the `size` bytes at `address` lie on the stack of the task.
+    pub fn is_on_stack(&self, address: usize, size: usize) -> bool {
+        let end = self.stack_base + self.stack_size * 4;
+        address >= self.stack_base && address.checked_add(size).is_some_and(|last| last <= end)
+    }
+    /// Returns false if the canary at the bottom of the stack has been overwritten, i.e. the stack overflowed.
+    pub fn check_canary(&self) -> bool {
+        unsafe { read_volatile(self.stack_base as *const u32) == STACK_PAINT }
+    }
+    /// Returns the largest number of words of the stack used so far, as told by the paint left on it.
+    pub fn get_high_water_mark(&self) -> usize {
+        let stack = unsafe { slice::from_raw_parts(self.stack_base as *const u32, self.stack_size) };
+        let unused = stack.iter().take_while(|word| **word == STACK_PAINT).count();
+        self.stack_size - unused
+    }
 }
 
 impl Scheduler {
@@ -93,10 +222,13 @@ impl Scheduler {
             task_control_blocks: [None; MAX_TASKS],
             #[cfg(all(
                 any(feature = "tasks_32", feature = "default"),
-                not(any(feature = "tasks_16", feature = "tasks_8"))
+                not(any(feature = "tasks_16", feature = "tasks_8", feature = "tasks_64"))
             ))]
             contexts: arr![None; 32],
 
+            #[cfg(all(feature = "tasks_64", not(feature = "tasks_32")))]
+            contexts: arr![None; 64],
+
             #[cfg(all(feature = "tasks_16", not(feature = "tasks_32")))]
             contexts: arr![None; 16],
 
@@ -107,20 +239,40 @@ impl Scheduler {
             blocked_tasks: 0,
             is_preemptive: false,
             preempt_disable_count: 0,
+            suspended_tasks: 0,
+            #[cfg(feature = "timer")]
+            sleeping_tasks: 0,
+            waiting_tasks: 0,
+            wait_objects: [0; MAX_TASKS],
+            #[cfg(feature = "timer")]
+            sleep_queue: SleepQueue::new(),
+            #[cfg(feature = "timer")]
+            periods: [0; MAX_TASKS],
+            #[cfg(feature = "timer")]
+            overruns: [0; MAX_TASKS],
+            #[cfg(feature = "timer")]
+            release_queue: SleepQueue::new(),
+            priority_masks: [0; MAX_TASKS],
+            priority_levels: 0,
+            turns: [0; MAX_TASKS],
+            time_slice: TIME_SLICE,
+            slice_left: TIME_SLICE,
+            #[cfg(feature = "mpu")]
+            faulted_tasks: 0,
+            #[cfg(feature = "task_monitor")]
+            policy: SchedulingPolicy::FixedPriority,
+            #[cfg(feature = "task_monitor")]
+            deadlines: [0; MAX_TASKS],
+            #[cfg(feature = "task_monitor")]
+            deadline_tasks: 0,
+            #[cfg(feature = "task_monitor")]
+            earliest_deadline: None,
+            overflow_handler: None,
+            stats: [TaskStats::new(); MAX_TASKS],
+            last_cycles: 0,
         }
     }
 
-    #[cfg(feature = "task_monitor")]
-    pub fn init(&mut self) -> Result<(), KernelError> {
-        self.is_preemptive = true;
-
-        static mut stack0: [u32; 64] = [0; 64];
-        self.create_task(0, 100, unsafe { &mut stack0 }, || loop {
-            wait_for_interrupt();
-        })
-    }
-
-    #[cfg(not(feature = "task_monitor"))]
     pub fn init(
         &mut self,
         init_handler: fn(cxt: &Context) -> Result<(), KernelError>,
@@ -129,115 +281,117 @@ impl Scheduler {
 
         static mut stack0: [u32; 64] = [0; 64];
         const TASK_PRIORITY: usize = 0;
-        self.create_task(TASK_PRIORITY, unsafe { &mut stack0 }, |cxt| loop {
-            wait_for_interrupt();
-        })?;
-        init_handler(&self.contexts[TASK_PRIORITY].as_ref().unwrap())
+        let tid = self.create_task(
+            TASK_PRIORITY,
+            #[cfg(feature = "task_monitor")]
+            Duration::ZERO,
+            unsafe { &mut stack0 },
+            false,
+            true,
+            |_| loop {
+                #[cfg(feature = "timer")]
+                idle();
+                #[cfg(not(feature = "timer"))]
+                wait_for_interrupt();
+            },
+        )?;
+        init_handler(&self.contexts[tid as usize].as_ref().unwrap())
     }
 
     /// The program counter for the task is pointer value of the function pointer (`handler_fn`). param is a variable whose reference will be made accessible to the task, and this helps in sharing global state with other tasks. Both these values are stored in a specific index of the stack so that when the context\_switch function loads the stack for this task, the appropriate program counter and argument for that function is loaded.
-    /// An important thing to note is that the task’s index in the `task_control_blocks` is the priority of the task. Hence there can be only one task of the mentioned priority. Also, another important thing is that the argument param is of a generic type(`T`).
+    /// The task’s index in the `task_control_blocks` is its TaskId, which is returned. The TaskId equals the priority if that slot is still free, else the lowest free slot is taken, so any number of tasks can share a priority. Also, another important thing is that the argument param is of a generic type(`T`).
     ///
     /// The `<T: Sync>` informs the compiler that the type `T` must implement the Sync trait. By implementing the Sync trait, a type becomes safe to be shared across tasks. Hence if a type that doesn’t implement Sync trait (like a mutable integer) is passed as param, then the code won’t compile. Kernel primitives like Message and Resource (which are data race safe) implement the Sync trait; hence, it can be passed as param. In this way, the Kernel makes safety a requirement rather than a choice.
     ///
     /// `handler_fn` is of type `fn(&T) -> !`, which implies it is a function pointer which takes a parameter of Type `&T` and infinitely loops. For more details, look into `spawn!` Macro.
-    #[cfg(not(feature = "task_monitor"))]
     pub fn create_task(
         &mut self,
         priority: usize,
+        #[cfg(feature = "task_monitor")] deadline: Duration,
         stack: &mut [u32],
+        uses_fpu: bool,
+        privileged: bool,
         handler_fn: fn(ContextType) -> !,
-    ) -> Result<(), KernelError> {
-        let tcb = self.create_tcb(stack, handler_fn, priority)?;
-        self.insert_tcb(priority, tcb)
+    ) -> Result<TaskId, KernelError> {
+        let tid = self.get_free_tid(priority)?;
+        let tcb = self.create_tcb(stack, uses_fpu, privileged, handler_fn, tid, priority)?;
+        #[cfg(feature = "task_monitor")]
+        let tcb = TaskControlBlock { deadline, ..tcb };
+        self.insert_tcb(tid, tcb)?;
+        Ok(tid as TaskId)
     }
 
-    #[cfg(feature = "task_monitor")]
-    pub fn create_task(
-        &mut self,
-        priority: usize,
-        deadline: u32,
-        stack: &mut [u32],
-        handler_fn: fn() -> !,
-    ) -> Result<(), KernelError> {
-        let tcb = self.create_tcb(deadline, stack, handler_fn)?;
-        self.insert_tcb(priority, tcb)
+    /// Returns the slot for a new task of `priority`, the one at `priority` if it is free, else the lowest free one.
+    /// Priority zero is reserved for the idle task.
+    fn get_free_tid(&self, priority: usize) -> Result<usize, KernelError> {
+        if priority >= MAX_TASKS {
+            return Err(KernelError::NotFound);
+        }
+        if self.task_control_blocks[priority].is_none() {
+            return Ok(priority);
+        }
+        if priority == 0 {
+            return Err(KernelError::Exists);
+        }
+        self.task_control_blocks
+            .iter()
+            .position(|tcb| tcb.is_none())
+            .ok_or(KernelError::LimitExceeded)
     }
 
     /// Creates a TCB corresponding to the tasks details passed onto this method.
-    #[cfg(not(feature = "task_monitor"))]
     fn create_tcb(
         &mut self,
         stack: &mut [u32],
+        uses_fpu: bool,
+        privileged: bool,
         handler: fn(ContextType) -> !,
-        pr: usize,
+        tid: usize,
+        priority: usize,
     ) -> Result<TaskControlBlock, KernelError> {
-        if stack.len() < 32 {
+        // Tasks using the FPU additionally hold the floating point registers on their stack.
+        let min_stack_size = if uses_fpu { 64 } else { 32 };
+        if stack.len() < min_stack_size {
             return Err(KernelError::StackTooSmall);
         }
 
-        let pos = stack.len() - 1;
-        let pc: usize = handler as usize;
         // Dropped at end of scope.
-        self.contexts[pr] = Some(Context::new(pr as TaskId));
-        let x = self.contexts[pr].as_ref().unwrap();
+        self.contexts[tid] = Some(Context::new(tid as TaskId));
+        let x = self.contexts[tid].as_ref().unwrap();
         let cxt = x as *const Context;
 
-        stack[pos] = 1 << 24; // xPSR
-        stack[pos - 1] = pc as u32; // PC
-        stack[pos - 2] = 0xFFFFFFFD; // LR
-        stack[pos - 3] = 0xCCCCCCCC; // R12
-        stack[pos - 4] = 0x33333333; // R3
-        stack[pos - 5] = 0x22222222; // R2
-        stack[pos - 6] = 0x11111111; // R1
-        stack[pos - 7] = cxt as u32; // R0
-                                     // aditional regs
-        stack[pos - 08] = 0x77777777; // R7
-        stack[pos - 09] = 0x66666666; // R6
-        stack[pos - 10] = 0x55555555; // R5
-        stack[pos - 11] = 0x44444444; // R4
-        stack[pos - 12] = 0xBBBBBBBB; // R11
-        stack[pos - 13] = 0xAAAAAAAA; // R10
-        stack[pos - 14] = 0x99999999; // R9
-        stack[pos - 15] = 0x88888888; // R8
-
-        // stack[pos - 7] = 3 as u32;
-
-        let stack_pointer: usize = unsafe { core::intrinsics::transmute(&stack[stack.len() - 16]) };
-        let tcb = TaskControlBlock {
-            stack_pointer: stack_pointer as usize,
+        // Unprivileged tasks can only access their own stack, hence their Context is placed on top of it.
+        #[cfg(feature = "mpu")]
+        let (stack, cxt) = if privileged {
+            (stack, cxt)
+        } else {
+            mpu::set_stack(tid as TaskId, stack)?;
+            let (stack, top) = stack.split_at_mut(stack.len() - 2);
+            let cxt = top.as_mut_ptr() as *mut Context;
+            unsafe { cxt.write(Context::new(tid as TaskId)) };
+            (stack, cxt as *const Context)
         };
 
-        Ok(tcb)
-    }
-
-    #[cfg(feature = "task_monitor")]
-    fn create_tcb(
-        &self,
-        deadline: u32,
-        stack: &mut [u32],
-        handler: fn() -> !,
-    ) -> Result<TaskControlBlock, KernelError> {
-        if stack.len() < 32 {
-            return Err(KernelError::StackTooSmall);
+        // The stack is painted, so that its lowest word serves as canary and the depth it has
+        // been used to can be told by how much of the paint is left.
+        for word in stack.iter_mut() {
+            *word = STACK_PAINT;
         }
-
-        let pos = stack.len() - 1;
-        let pc: usize = handler as usize;
-
-        stack[pos] = 1 << 24; // xPSR
-        stack[pos - 1] = pc as u32; // PC
-
-        let stack_pointer: usize = unsafe { core::intrinsics::transmute(&stack[stack.len() - 16]) };
+        let stack_pointer = init_stack(stack, handler, cxt, uses_fpu);
         let tcb = TaskControlBlock {
-            deadline,
             stack_pointer: stack_pointer as usize,
+            privileged,
+            priority: priority as Priority,
+            #[cfg(feature = "task_monitor")]
+            deadline: Duration::ZERO,
+            stack_base: stack.as_ptr() as usize,
+            stack_size: stack.len(),
         };
 
         Ok(tcb)
     }
 
-    /// Inserts the `TCB` into `task_control_blocks` at position `id`.
+    /// Inserts the `TCB` into `task_control_blocks` at position `id` and adds the task to its priority.
     fn insert_tcb(&mut self, id: usize, tcb: TaskControlBlock) -> Result<(), KernelError> {
         if id >= MAX_TASKS {
             return Err(KernelError::NotFound);
@@ -245,10 +399,123 @@ impl Scheduler {
         if self.task_control_blocks[id].is_some() {
             return Err(KernelError::Exists);
         }
+        let priority = tcb.priority as usize;
+        self.priority_masks[priority] |= 1 << id;
+        self.priority_levels |= 1 << priority;
         self.task_control_blocks[id] = Some(tcb);
+        self.stats[id] = TaskStats::new();
         return Ok(());
     }
 
+    /// Checks that task `tid` exists and is not the idle task.
+    fn check_tid(&self, tid: usize) -> Result<(), KernelError> {
+        if tid >= MAX_TASKS || self.task_control_blocks[tid].is_none() {
+            return Err(KernelError::NotFound);
+        }
+        if tid == 0 {
+            return Err(KernelError::AccessDenied);
+        }
+        Ok(())
+    }
+
+    /// Suspends task `tid`, it is not scheduled until it is resumed whatever its other states are.
+    pub fn suspend(&mut self, tid: usize) -> Result<(), KernelError> {
+        self.check_tid(tid)?;
+        self.suspended_tasks |= 1 << tid;
+        Ok(())
+    }
+
+    /// Resumes the suspended task `tid`.
+    pub fn resume(&mut self, tid: usize) -> Result<(), KernelError> {
+        self.check_tid(tid)?;
+        self.suspended_tasks &= !(1 << tid);
+        Ok(())
+    }
+
+    /// Removes task `tid` from the Scheduler and frees its slots in `task_control_blocks` and
+    /// `contexts`, so that a new task can be created in its place.
+    pub fn delete_task(&mut self, tid: usize) -> Result<(), KernelError> {
+        self.check_tid(tid)?;
+        let mask: BooleanVector = !(1 << tid);
+        let priority = self.get_priority(tid) as usize;
+        self.priority_masks[priority] &= mask;
+        if self.priority_masks[priority] == 0 {
+            self.priority_levels &= !(1 << priority);
+        }
+        self.active_tasks &= mask;
+        self.blocked_tasks &= mask;
+        self.suspended_tasks &= mask;
+        self.waiting_tasks &= mask;
+        self.wait_objects[tid] = 0;
+        #[cfg(feature = "timer")]
+        {
+            self.sleeping_tasks &= mask;
+            self.sleep_queue.remove(tid);
+            self.release_queue.remove(tid);
+            self.periods[tid] = 0;
+            self.overruns[tid] = 0;
+        }
+        #[cfg(feature = "mpu")]
+        {
+            self.faulted_tasks &= mask;
+        }
+        #[cfg(feature = "task_monitor")]
+        self.clear_deadline(tid);
+        self.task_control_blocks[tid] = None;
+        self.contexts[tid] = None;
+        Ok(())
+    }
+
+    /// Moves task `tid` to `priority`. Its TaskId is kept, hence the boolean vectors referring to
+    /// it stay valid, and the ceilings of the resources it accesses follow its new priority.
+    /// Priority zero is reserved for the idle task.
+    pub fn set_priority(&mut self, tid: usize, priority: usize) -> Result<(), KernelError> {
+        self.check_tid(tid)?;
+        if priority == 0 || priority >= MAX_TASKS {
+            return Err(KernelError::InvalidArgument);
+        }
+        let old_priority = self.get_priority(tid) as usize;
+        self.priority_masks[old_priority] &= !(1 << tid);
+        if self.priority_masks[old_priority] == 0 {
+            self.priority_levels &= !(1 << old_priority);
+        }
+        self.priority_masks[priority] |= 1 << tid;
+        self.priority_levels |= 1 << priority;
+        self.task_control_blocks[tid].as_mut().unwrap().priority = priority as Priority;
+        Ok(())
+    }
+
+    /// Returns the priority of task `tid`.
+    pub fn get_priority(&self, tid: usize) -> Priority {
+        self.task_control_blocks[tid].as_ref().unwrap().priority
+    }
+
+    /// Returns the highest priority amongst the tasks in `tasks_mask`, zero if there are none.
+    pub fn get_ceiling(&self, tasks_mask: BooleanVector) -> Priority {
+        let mut levels = self.priority_levels;
+        while let Some(priority) = get_msb(levels) {
+            if self.priority_masks[priority] & tasks_mask != 0 {
+                return priority as Priority;
+            }
+            levels &= !(1 << priority);
+        }
+        0
+    }
+
+    /// Returns the TaskId of the highest priority task in `tasks_mask`, the one with the highest
+    /// TaskId amongst tasks of the same priority. `None` if there are no tasks in `tasks_mask`.
+    pub fn get_highest(&self, tasks_mask: BooleanVector) -> Option<usize> {
+        let priority = self.get_ceiling(tasks_mask) as usize;
+        get_msb(self.priority_masks[priority] & tasks_mask)
+    }
+
+    /// Returns a boolean vector of all tasks with a priority up to `priority` (including).
+    pub fn get_tasks_upto(&self, priority: Priority) -> BooleanVector {
+        self.priority_masks[..=priority as usize]
+            .iter()
+            .fold(0, |mask, tasks| mask | tasks)
+    }
+
     #[inline(always)]
     /// Appends `tasks_mask` onto `blocked_tasks`.
     pub fn block_tasks(&mut self, tasks_mask: BooleanVector) {
@@ -261,31 +528,301 @@ impl Scheduler {
         self.blocked_tasks &= !tasks_mask;
     }
 
-    /// Returns the TaskId currently high priority task, which is in ready state.
-    /// The highest priority is determined by calculating the most significant bit of boolean vector
-    /// corresponding to the tasks in the ready state. The tasks in the ready state can be identified
-    /// by the boolean and of `active_tasks` and boolean not(`blocked_tasks`).
-    pub fn get_next_tid(&self) -> usize {
-        let mask = self.active_tasks & !self.blocked_tasks;
-        return get_msb(mask).unwrap();
+    /// Makes task `tid` wait on the primitive at address `object` until it is notified.
+    pub fn wait(&mut self, tid: usize, object: usize) {
+        self.waiting_tasks |= 1 << tid;
+        self.wait_objects[tid] = object;
     }
 
-    /// Updates `active_tasks` with `task_mask`.
-    pub fn release(&mut self, tasks_mask: BooleanVector) {
+    /// Makes task `tid` wait on the primitive at address `object` until it is notified or
+    /// `timeout_time` is reached, whichever comes first. The timeout is kept in the sleep queue.
+    #[cfg(feature = "timer")]
+    pub fn wait_until(&mut self, tid: usize, object: usize, timeout_time: u64) {
+        self.wait(tid, object);
+        self.sleep(tid, timeout_time);
+    }
+
+    /// Returns the tasks in `tasks_mask` which last waited on the primitive at address `object`.
+    /// A primitive records its waiters by TaskId, this drops the ones which were deleted since,
+    /// even if their TaskId was taken by a new task.
+    pub fn get_waiters(&self, object: usize, tasks_mask: BooleanVector) -> BooleanVector {
+        let mut waiters = tasks_mask;
+        let mut tasks = tasks_mask;
+        while let Some(tid) = get_msb(tasks) {
+            tasks &= !(1 << tid);
+            if self.wait_objects[tid] != object {
+                waiters &= !(1 << tid);
+            }
+        }
+        waiters
+    }
+
+    /// Ends the wait of the tasks in `tasks_mask` and cancels their timeouts.
+    pub fn notify(&mut self, tasks_mask: BooleanVector) {
+        let notified = self.waiting_tasks & tasks_mask;
+        self.waiting_tasks &= !notified;
+        #[cfg(feature = "timer")]
+        {
+            let mut timed = notified & self.sleeping_tasks;
+            self.sleeping_tasks &= !timed;
+            while let Some(tid) = get_msb(timed) {
+                timed &= !(1 << tid);
+                self.sleep_queue.remove(tid);
+            }
+        }
+    }
+
+    /// Returns a boolean vector of the tasks in ready state, i.e. the tasks which are active but
+    /// neither blocked, suspended, waiting nor sleeping.
+    pub fn get_ready_tasks(&self) -> BooleanVector {
+        let ready = self.active_tasks & !self.blocked_tasks & !self.suspended_tasks;
+        let ready = ready & !self.waiting_tasks;
+        #[cfg(feature = "timer")]
+        let ready = ready & !self.sleeping_tasks;
+        ready
+    }
+
+    /// Returns the TaskId of the ready task to run next, see `get_ready_tasks`. The priority
+    /// levels are walked down from the highest until one has ready tasks, amongst which the task
+    /// whose turn it is gets scheduled. Under `EarliestDeadlineFirst`, the ready task with the
+    /// earliest absolute deadline is run instead. The tasks up to the system ceiling are blocked
+    /// while resources are locked, which makes this the Stack Resource Policy. The idle task is
+    /// run if no task is ready.
+    pub fn get_next_tid(&self) -> usize {
+        let ready = self.get_ready_tasks();
         #[cfg(feature = "task_monitor")]
         {
-            for i in 0..32 {
-                if (tasks_mask & 1 << i) > 0 {
-                    set_deadline(i as TaskId, self.task_control_blocks[i].unwrap().deadline)
+            if self.policy == SchedulingPolicy::EarliestDeadlineFirst {
+                let earliest = match self.earliest_deadline {
+                    Some(tid) if ready & (1 << tid) != 0 => Some(tid),
+                    _ => self.find_earliest_deadline(ready & self.deadline_tasks),
+                };
+                if let Some(tid) = earliest {
+                    return tid;
                 }
             }
         }
-        self.active_tasks |= tasks_mask;
+        let mut levels = self.priority_levels;
+        loop {
+            let Some(priority) = get_msb(levels) else {
+                return 0;
+            };
+            let mask = ready & self.priority_masks[priority];
+            if mask != 0 {
+                return self.get_turn(priority, mask);
+            }
+            levels &= !(1 << priority);
+        }
+    }
+
+    /// Returns the task of the ready tasks `mask` of `priority` whose turn it is: the task holding
+    /// the turn, else the next one below it, wrapping around to the highest TaskId.
+    fn get_turn(&self, priority: usize, mask: BooleanVector) -> usize {
+        let turn = self.turns[priority];
+        let upto_turn = if turn < MAX_TASKS - 1 {
+            (1 << (turn + 1)) - 1
+        } else {
+            !0
+        };
+        match get_msb(mask & upto_turn) {
+            Some(tid) => tid,
+            None => get_msb(mask).unwrap(),
+        }
+    }
+
+    /// Adds the time since it was last called to the run time of the running task. Called on
+    /// every context switch, `cycles` being the current value of `get_cycles`.
+    pub fn account_run_time(&mut self, cycles: u32) {
+        if self.started {
+            let elapsed = cycles.wrapping_sub(self.last_cycles);
+            self.stats[self.curr_tid].run_time += elapsed as u64;
+        }
+        self.last_cycles = cycles;
+    }
+
+    /// Records the switch from task `curr_tid` to task `next_tid`.
+    pub fn account_switch(&mut self, curr_tid: usize, next_tid: usize) {
+        if self.started && self.get_ready_tasks() & (1 << curr_tid) != 0 {
+            self.stats[curr_tid].preemptions += 1;
+        }
+        self.stats[next_tid].context_switches += 1;
+    }
+
+    /// Returns the run time statistics of task `tid`.
+    pub fn get_stats(&self, tid: usize) -> Result<TaskStats, KernelError> {
+        match self.task_control_blocks.get(tid) {
+            Some(Some(_)) => Ok(self.stats[tid]),
+            _ => Err(KernelError::NotFound),
+        }
     }
 
-    pub fn schedule(&self) {
-        if self.is_preemptive {
-            set_pendsv();
+    /// Returns the share of the run time not spent in the idle task, in percent.
+    pub fn get_utilisation(&self) -> u32 {
+        let total: u64 = self.stats.iter().map(|stats| stats.run_time).sum();
+        if total == 0 {
+            return 0;
+        }
+        let busy = total - self.stats[0].run_time;
+        (busy * 100 / total) as u32
+    }
+
+    /// Called when the context of task `tid` is loaded. The task takes the turn of its priority
+    /// and starts a new time slice.
+    pub fn start_slice(&mut self, tid: usize) {
+        let priority = self.get_priority(tid) as usize;
+        self.turns[priority] = tid;
+        self.slice_left = self.time_slice;
+    }
+
+    /// Called on every tick. Once the running task has used up its time slice, the turn passes
+    /// on to the next ready task of the same priority, if there is one.
+    pub fn tick_slice(&mut self) {
+        if !self.started || self.time_slice == 0 {
+            return;
+        }
+        self.slice_left = self.slice_left.saturating_sub(1);
+        if self.slice_left > 0 {
+            return;
+        }
+        self.slice_left = self.time_slice;
+        let curr_tid = self.curr_tid;
+        // The running task may have been deleted, it is switched out by the pending PendSV.
+        if self.task_control_blocks[curr_tid].is_none() {
+            return;
         }
+        let priority = self.get_priority(curr_tid) as usize;
+        let ready = self.get_ready_tasks();
+        if ready & self.priority_masks[priority] & !(1 << curr_tid) != 0 {
+            self.turns[priority] = (curr_tid + MAX_TASKS - 1) % MAX_TASKS;
+        }
+    }
+
+    /// Puts task `tid` to sleep until `wake_time`.
+    #[cfg(feature = "timer")]
+    pub fn sleep(&mut self, tid: usize, wake_time: u64) {
+        self.sleeping_tasks |= 1 << tid;
+        self.sleep_queue.insert(tid, wake_time);
+    }
+
+    /// Wakes up the tasks whose wake-up time has been reached at `curr_time`, tasks waiting with
+    /// a timeout stop waiting.
+    #[cfg(feature = "timer")]
+    pub fn wake_tasks(&mut self, curr_time: u64) {
+        if self.sleeping_tasks != 0 {
+            let expired = self.sleep_queue.pop_expired(curr_time);
+            self.sleeping_tasks &= !expired;
+            self.waiting_tasks &= !expired;
+        }
+    }
+
+    /// Returns the number of ticks until the next sleeping task is woken up or periodic task is
+    /// released, `None` if there is neither.
+    #[cfg(feature = "timer")]
+    pub fn get_next_wake_up(&self, curr_time: u64) -> Option<u64> {
+        let wake_up = self.sleep_queue.ticks_until_next(curr_time);
+        let release = self.release_queue.ticks_until_next(curr_time);
+        match (wake_up, release) {
+            (Some(wake_up), Some(release)) => Some(wake_up.min(release)),
+            (wake_up, release) => wake_up.or(release),
+        }
+    }
+
+    /// Makes task `tid` periodic, it is released every `period` ticks starting at `first_release`.
+    #[cfg(feature = "timer")]
+    pub fn set_period(&mut self, tid: usize, period: u64, first_release: u64) {
+        self.periods[tid] = period;
+        self.release_queue.insert(tid, first_release);
+    }
+
+    /// Returns the number of releases of task `tid` which found it still active.
+    #[cfg(feature = "timer")]
+    pub fn get_overruns(&self, tid: usize) -> u32 {
+        self.overruns[tid]
+    }
+
+    /// Releases the periodic tasks whose release time has been reached at `curr_time` and returns
+    /// them as a boolean vector. The next release of each of them is scheduled exactly one period
+    /// after the previous one, so releases do not drift. A task which is still active when it is
+    /// released has overrun.
+    #[cfg(feature = "timer")]
+    pub fn release_periodic(&mut self, curr_time: u64) -> BooleanVector {
+        let released = self.release_queue.pop_expired(curr_time);
+        let mut due = released;
+        while let Some(tid) = get_msb(due) {
+            due &= !(1 << tid);
+            if self.active_tasks & (1 << tid) != 0 {
+                self.overruns[tid] += 1;
+            }
+            let next_release = self.release_queue.get_wake_time(tid) + self.periods[tid];
+            self.release_queue.insert(tid, next_release);
+        }
+        if released != 0 {
+            self.release(released);
+        }
+        released
+    }
+
+    /// Sets the absolute deadline of task `tid` to `deadline` ticks.
+    #[cfg(feature = "task_monitor")]
+    fn set_deadline(&mut self, tid: usize, deadline: u64) {
+        self.deadlines[tid] = deadline;
+        self.deadline_tasks |= 1 << tid;
+        match self.earliest_deadline {
+            Some(earliest) if !self.is_earlier(tid, earliest) => {}
+            _ => self.earliest_deadline = Some(tid),
+        }
+    }
+
+    /// Clears the absolute deadline of task `tid`, as it was met or the task is gone.
+    #[cfg(feature = "task_monitor")]
+    pub fn clear_deadline(&mut self, tid: usize) {
+        self.deadline_tasks &= !(1 << tid);
+        if self.earliest_deadline == Some(tid) {
+            self.earliest_deadline = self.find_earliest_deadline(self.deadline_tasks);
+        }
+    }
+
+    /// Returns whether the deadline of task `tid` precedes the one of task `other`, ties are
+    /// broken in favour of the higher TaskId.
+    #[cfg(feature = "task_monitor")]
+    fn is_earlier(&self, tid: usize, other: usize) -> bool {
+        (self.deadlines[tid], other) < (self.deadlines[other], tid)
+    }
+
+    /// Returns the task amongst `tasks_mask` with the earliest absolute deadline, all of them
+    /// having one.
+    #[cfg(feature = "task_monitor")]
+    fn find_earliest_deadline(&self, mut tasks_mask: BooleanVector) -> Option<usize> {
+        let mut earliest = get_msb(tasks_mask)?;
+        tasks_mask &= !(1 << earliest);
+        while let Some(tid) = get_msb(tasks_mask) {
+            tasks_mask &= !(1 << tid);
+            if self.is_earlier(tid, earliest) {
+                earliest = tid;
+            }
+        }
+        Some(earliest)
+    }
+
+    /// Updates `active_tasks` with `task_mask`. With the `task_monitor` feature, the absolute
+    /// deadline of each task which was not active yet is set relative to the current time.
+    pub fn release(&mut self, tasks_mask: BooleanVector) {
+        #[cfg(feature = "mpu")]
+        let tasks_mask = tasks_mask & !self.faulted_tasks;
+        #[cfg(feature = "task_monitor")]
+        {
+            let released = tasks_mask & !self.active_tasks;
+            for i in 0..MAX_TASKS {
+                if (released & 1 << i) > 0 {
+                    if let Some(tcb) = self.task_control_blocks[i] {
+                        if !tcb.deadline.is_zero() {
+                            let deadline = set_deadline(i as TaskId, tcb.deadline);
+                            self.set_deadline(i, deadline);
+                        }
+                    }
+                }
+            }
+        }
+        self.active_tasks |= tasks_mask;
     }
 }
@@ -1,17 +1,12 @@
 //! # Software synchronization bus definition
 //!
-use cortex_m::{asm::bkpt, interrupt::CriticalSection};
-
-use crate::utils::arch::critical_section;
+use crate::kernel::syscall::{decode_flag, register, split_mask, syscall, Syscall};
+use crate::system::registry::KernelObject;
+use crate::system::scheduler::TaskId;
+use crate::utils::arch::{critical_section, is_privileged, CriticalSection};
 use crate::KernelError;
-use crate::{
-    kernel::tasks::{get_curr_tid, schedule},
-    tasks::Context,
-};
-use crate::{
-    kernel::tasks::{preempt, TaskManager},
-    system::scheduler::BooleanVector,
-};
+use crate::{kernel::tasks::schedule, tasks::Context};
+use crate::{kernel::tasks::TaskManager, system::scheduler::BooleanVector};
 use core::cell::RefCell;
 
 #[cfg(feature = "system_logger")]
@@ -34,8 +29,21 @@ impl Semaphore {
         }
     }
 
+    /// Registers the semaphore with the Kernel, so that unprivileged tasks can signal and test it.
+    /// Only privileged code can register semaphores.
+    pub fn register(&'static self) -> Result<(), KernelError> {
+        register(self as *const Self as usize, KernelObject::Semaphore)
+    }
+
     /// Signals the semaphore, all tasks specified in semaphore::flags can test for it and all tasks in semaphore::tasks are released
+    /// An unprivileged task can only signal a registered semaphore.
     pub fn signal_and_release(&'static self, tasks_mask: BooleanVector) {
+        if !is_privileged() {
+            let [mask_lo, mask_hi] = split_mask(tasks_mask);
+            let semaphore = self as *const Self as usize;
+            syscall(Syscall::SemaphoreSignal, [semaphore, mask_lo, mask_hi]);
+            return;
+        }
         critical_section(|cs| {
             self.signal_and_release_with_cs(cs, tasks_mask);
         });
@@ -43,7 +51,7 @@ impl Semaphore {
 
     #[inline(always)]
     pub(crate) fn signal_and_release_with_cs(
-        &'static self,
+        &self,
         cs: &CriticalSection,
         tasks_mask: BooleanVector,
     ) {
@@ -61,26 +69,30 @@ impl Semaphore {
     }
 
     /// Checks if the flag was enabled for the currently running task.
+    /// An unprivileged task can only test a registered semaphore.
     pub fn test_and_reset(&'static self, cxt: &Context) -> Result<bool, KernelError> {
-        critical_section(|_| self.unsafe_test_and_reset(cxt))
+        if !is_privileged() {
+            let semaphore = self as *const Self as usize;
+            return decode_flag(syscall(Syscall::SemaphoreTest, [semaphore, 0, 0]));
+        }
+        Ok(critical_section(|_| self.test_and_reset_for(cxt.get_tid())))
     }
 
-    /// Checks if the flag was enabled for the currently running task.
-    pub(crate) fn unsafe_test_and_reset(&'static self, cxt: &Context) -> Result<bool, KernelError> {
+    /// Checks if the flag was enabled for task `tid`, the caller holds a critical section.
+    pub(crate) fn test_and_reset_for(&self, tid: TaskId) -> bool {
         let flags: &mut BooleanVector = &mut self.flags.borrow_mut();
-        let curr_tid = cxt.get_tid() as u32;
-        let curr_tid_mask = 1 << curr_tid;
+        let curr_tid_mask = 1 << tid;
         if *flags & curr_tid_mask == curr_tid_mask {
             *flags &= !curr_tid_mask;
             #[cfg(feature = "system_logger")]
             {
                 if logging::get_semaphore_reset() {
-                    logging::report(LogEventType::SemaphoreReset(curr_tid));
+                    logging::report(LogEventType::SemaphoreReset(tid));
                 }
             }
-            return Ok(true);
+            return true;
         } else {
-            return Ok(false);
+            return false;
         }
     }
 }
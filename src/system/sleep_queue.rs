@@ -0,0 +1,94 @@
+//! # Sleep Queue
+//! The Definition of Data-structures required for putting tasks to sleep and releasing periodic tasks.
+//! The tasks are kept in a list ordered by their wake-up (or release) time, hence on each tick
+//! only the head of the list has to be checked instead of all the tasks.
+
+use crate::config::MAX_TASKS;
+use crate::system::scheduler::BooleanVector;
+
+pub struct SleepQueue {
+    /// The wake-up time of each sleeping task.
+    wake_times: [u64; MAX_TASKS],
+    /// The TaskId of the task following each task in the list.
+    next: [Option<usize>; MAX_TASKS],
+    /// The TaskId of the task to be woken up first.
+    head: Option<usize>,
+}
+
+impl SleepQueue {
+    pub const fn new() -> Self {
+        Self {
+            wake_times: [0; MAX_TASKS],
+            next: [None; MAX_TASKS],
+            head: None,
+        }
+    }
+
+    /// Inserts task `tid` into the list, after all tasks which wake up at or before `wake_time`.
+    pub fn insert(&mut self, tid: usize, wake_time: u64) {
+        self.wake_times[tid] = wake_time;
+        let mut prev: Option<usize> = None;
+        let mut curr = self.head;
+        while let Some(other) = curr {
+            if wake_time < self.wake_times[other] {
+                break;
+            }
+            prev = curr;
+            curr = self.next[other];
+        }
+        self.next[tid] = curr;
+        match prev {
+            Some(prev) => self.next[prev] = Some(tid),
+            None => self.head = Some(tid),
+        }
+    }
+
+    /// Removes task `tid` from the list, if it is in there.
+    pub fn remove(&mut self, tid: usize) {
+        let mut prev: Option<usize> = None;
+        let mut curr = self.head;
+        while let Some(other) = curr {
+            if other == tid {
+                match prev {
+                    Some(prev) => self.next[prev] = self.next[tid],
+                    None => self.head = self.next[tid],
+                }
+                self.next[tid] = None;
+                return;
+            }
+            prev = curr;
+            curr = self.next[other];
+        }
+    }
+
+    /// Returns the wake-up time task `tid` was last inserted with.
+    pub fn get_wake_time(&self, tid: usize) -> u64 {
+        self.wake_times[tid]
+    }
+
+    /// Returns the earliest wake-up time in the list.
+    pub fn peek(&self) -> Option<u64> {
+        self.head.map(|tid| self.wake_times[tid])
+    }
+
+    /// Returns the number of ticks from `curr_time` until the earliest wake-up time in the list,
+    /// at least one as the wake-up times are checked on the next tick the earliest.
+    pub fn ticks_until_next(&self, curr_time: u64) -> Option<u64> {
+        self.peek()
+            .map(|wake_time| wake_time.saturating_sub(curr_time).max(1))
+    }
+
+    /// Removes the tasks whose wake-up time has been reached at `curr_time` and returns them as a boolean vector.
+    pub fn pop_expired(&mut self, curr_time: u64) -> BooleanVector {
+        let mut expired: BooleanVector = 0;
+        while let Some(tid) = self.head {
+            if curr_time < self.wake_times[tid] {
+                break;
+            }
+            self.head = self.next[tid];
+            self.next[tid] = None;
+            expired |= 1 << tid;
+        }
+        expired
+    }
+}
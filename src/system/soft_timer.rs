@@ -0,0 +1,217 @@
+//! # Software Timers
+//! The Definition of Data-structures required for software timers.
+//! The running timers are kept in a delta list, ordered by expiry, in which each timer holds the
+//! number of ticks from the expiry of the timer before it. Hence a tick only decrements the head
+//! of the list, whatever the number of running timers.
+
+use crate::config::MAX_SOFT_TIMERS;
+use crate::system::time::Duration;
+use crate::KernelError;
+
+pub type TimerId = usize;
+
+/// Whether a timer stops or starts over once it expires.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimerMode {
+    /// The timer expires once and then stops.
+    OneShot,
+    /// The timer starts over with its period each time it expires.
+    AutoReload,
+}
+
+/// Timer Descriptor
+#[derive(Clone, Copy)]
+struct SoftTimer {
+    mode: TimerMode,
+    period: Duration,
+    handler: fn(),
+    /// Whether the timer is in the list.
+    is_active: bool,
+    /// Ticks from the expiry of the previous timer in the list to the expiry of this one.
+    delta: u64,
+    /// The timer following this one in the list.
+    next: Option<TimerId>,
+}
+
+pub struct TimerList {
+    /// The timer descriptors, deleted timers leave their slot free for reuse.
+    timers: [Option<SoftTimer>; MAX_SOFT_TIMERS],
+    /// The timer which expires first.
+    head: Option<TimerId>,
+}
+
+impl TimerList {
+    pub const fn new() -> Self {
+        Self {
+            timers: [None; MAX_SOFT_TIMERS],
+            head: None,
+        }
+    }
+
+    /// Creates a new timer in the first free slot, which is stopped until it is started.
+    pub fn create(
+        &mut self,
+        mode: TimerMode,
+        period: Duration,
+        handler: fn(),
+    ) -> Result<TimerId, KernelError> {
+        if period.is_zero() {
+            return Err(KernelError::InvalidArgument);
+        }
+        let id = self
+            .timers
+            .iter()
+            .position(|slot| slot.is_none())
+            .ok_or(KernelError::LimitExceeded)?;
+        self.timers[id] = Some(SoftTimer {
+            mode,
+            period,
+            handler,
+            is_active: false,
+            delta: 0,
+            next: None,
+        });
+        Ok(id)
+    }
+
+    /// Deletes timer `id`, stopping it first if it is running. Its TimerId is handed out again by
+    /// `create`.
+    pub fn delete(&mut self, id: TimerId) -> Result<(), KernelError> {
+        self.stop(id)?;
+        self.timers[id] = None;
+        Ok(())
+    }
+
+    fn get(&mut self, id: TimerId) -> Result<&mut SoftTimer, KernelError> {
+        match self.timers.get_mut(id) {
+            Some(Some(timer)) => Ok(timer),
+            _ => Err(KernelError::NotFound),
+        }
+    }
+
+    /// Starts timer `id`, it expires one period from now. A running timer is left as it is.
+    pub fn start(&mut self, id: TimerId) -> Result<(), KernelError> {
+        if !self.get(id)?.is_active {
+            self.arm(id);
+        }
+        Ok(())
+    }
+
+    /// Stops timer `id`, it does not expire until it is started again.
+    pub fn stop(&mut self, id: TimerId) -> Result<(), KernelError> {
+        if self.get(id)?.is_active {
+            self.unlink(id);
+        }
+        Ok(())
+    }
+
+    /// Starts timer `id` over, it expires one period from now whether it was running or not.
+    pub fn restart(&mut self, id: TimerId) -> Result<(), KernelError> {
+        self.stop(id)?;
+        self.arm(id);
+        Ok(())
+    }
+
+    /// Sets the period of timer `id`. A running timer is started over with the new period.
+    pub fn set_period(&mut self, id: TimerId, period: Duration) -> Result<(), KernelError> {
+        if period.is_zero() {
+            return Err(KernelError::InvalidArgument);
+        }
+        let timer = self.get(id)?;
+        timer.period = period;
+        if timer.is_active {
+            self.restart(id)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_active(&mut self, id: TimerId) -> Result<bool, KernelError> {
+        Ok(self.get(id)?.is_active)
+    }
+
+    /// Inserts timer `id` into the list, one period from now and after the timers expiring at the same tick.
+    fn arm(&mut self, id: TimerId) {
+        let mut delta = self.timers[id].unwrap().period.ticks();
+        let mut prev: Option<TimerId> = None;
+        let mut curr = self.head;
+        while let Some(other) = curr {
+            let other = self.timers[other].as_ref().unwrap();
+            if delta < other.delta {
+                break;
+            }
+            delta -= other.delta;
+            prev = curr;
+            curr = other.next;
+        }
+        if let Some(next) = curr {
+            self.timers[next].as_mut().unwrap().delta -= delta;
+        }
+        let timer = self.timers[id].as_mut().unwrap();
+        timer.is_active = true;
+        timer.delta = delta;
+        timer.next = curr;
+        match prev {
+            Some(prev) => self.timers[prev].as_mut().unwrap().next = Some(id),
+            None => self.head = Some(id),
+        }
+    }
+
+    /// Removes the running timer `id` from the list, its remaining ticks are handed on to the timer after it.
+    fn unlink(&mut self, id: TimerId) {
+        let timer = self.timers[id].unwrap();
+        if let Some(next) = timer.next {
+            self.timers[next].as_mut().unwrap().delta += timer.delta;
+        }
+        if self.head == Some(id) {
+            self.head = timer.next;
+        } else {
+            let mut curr = self.head;
+            while let Some(other) = curr {
+                let other = self.timers[other].as_mut().unwrap();
+                if other.next == Some(id) {
+                    other.next = timer.next;
+                    break;
+                }
+                curr = other.next;
+            }
+        }
+        let timer = self.timers[id].as_mut().unwrap();
+        timer.is_active = false;
+        timer.next = None;
+    }
+
+    /// Called on every tick, counts down the timer which expires first.
+    pub fn tick(&mut self) {
+        self.skip(1);
+    }
+
+    /// Counts down `ticks` ticks which passed without `tick` being called. There must not be
+    /// more of them than the timer which expires first has left.
+    pub fn skip(&mut self, ticks: u64) {
+        if let Some(head) = self.head {
+            let timer = self.timers[head].as_mut().unwrap();
+            timer.delta = timer.delta.saturating_sub(ticks);
+        }
+    }
+
+    /// Removes the first timer from the list if it has expired and returns its handler. An
+    /// auto-reload timer is inserted again, one period after its expiry.
+    pub fn pop_expired(&mut self) -> Option<fn()> {
+        let head = self.head?;
+        let timer = self.timers[head].unwrap();
+        if timer.delta > 0 {
+            return None;
+        }
+        self.unlink(head);
+        if timer.mode == TimerMode::AutoReload {
+            self.arm(head);
+        }
+        Some(timer.handler)
+    }
+
+    /// Returns the number of ticks until the first timer expires, `None` if no timer is running.
+    pub fn ticks_until_next(&self) -> Option<u64> {
+        self.head
+            .map(|head| self.timers[head].as_ref().unwrap().delta.max(1))
+    }
+}
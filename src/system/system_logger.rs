@@ -1,10 +1,14 @@
-use crate::system::scheduler::{BooleanVector,TaskId};
+use crate::system::scheduler::{BooleanVector,Priority,TaskId};
 use crate::config::MAX_LOGS;
+use crate::system::time::Instant;
 use core::fmt;
 
 #[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
 use crate::system::event::EventId;
 
+#[cfg(feature = "interrupts")]
+use crate::system::interrupt::IrqNumber;
+
 pub type Logs = [Option<LogEvent>; MAX_LOGS];
 
 #[derive(Clone, Copy)]
@@ -12,26 +16,29 @@ pub enum LogEventType {
     ReleaseTasks(BooleanVector),
     BlockTasks(BooleanVector),
     UnblockTasks(BooleanVector),
-    TaskExit(BooleanVector),
+    TaskExit(TaskId),
     ResourceLock(TaskId),
     ResourceUnlock(TaskId),
     MessageBroadcast(BooleanVector),
     MessageRecieve(TaskId),
     SemaphoreSignal(BooleanVector,BooleanVector),
     SemaphoreReset(TaskId),
-    DeadlineExpired(TaskId,u32),
+    DeadlineExpired(TaskId,Instant),
+    PriorityChange(TaskId,Priority),
     #[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
     TimerEvent(EventId),
+    #[cfg(feature = "interrupts")]
+    Interrupt(IrqNumber),
 }
 
 #[derive(Clone, Copy, Debug)]
 pub struct LogEvent {
     pub event_type: LogEventType,
-    pub timestamp: u32
+    pub timestamp: Instant
 }
 
 impl LogEvent {
-    pub fn new(event_type: LogEventType, timestamp: u32) -> Self {
+    pub fn new(event_type: LogEventType, timestamp: Instant) -> Self {
         Self {
             event_type,
             timestamp
@@ -53,9 +60,13 @@ pub struct SystemLogger {
     pub message_recieve_log: bool,
     pub semaphore_signal_log: bool,
     pub semaphore_reset_log: bool,
+    pub deadline_expired_log: bool,
+    pub priority_change_log: bool,
     
     #[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
     pub timer_event_log: bool,
+    #[cfg(feature = "interrupts")]
+    pub interrupt_log: bool,
 }
 // use a circular queue instead of this crap.
 // ensure the handler is not None in start_kernel.
@@ -75,9 +86,13 @@ impl SystemLogger {
             message_recieve_log : false,
             semaphore_signal_log : false,
             semaphore_reset_log : false,
+            deadline_expired_log : false,
+            priority_change_log : false,
             
             #[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
             timer_event_log : false,
+            #[cfg(feature = "interrupts")]
+            interrupt_log : false,
         }
     }
     pub fn push(&mut self, event: LogEvent) {
@@ -107,18 +122,21 @@ impl fmt::Debug for LogEventType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             LogEventType::ReleaseTasks(tasks_mask) => write!(f, "Tasks Released: {}", tasks_mask),
-            LogEventType::BlockTasks(tasks_mask) => write!(f, "BlockTasks"),
-            LogEventType::UnblockTasks(tasks_mask) => write!(f, "UnblockTasks"),
-            LogEventType::TaskExit(tasks_mask) => write!(f, "TaskExit"),
-            LogEventType::ResourceLock(ceiling) => write!(f, "ResourceLock"),
-            LogEventType::ResourceUnlock(ceiling) => write!(f, "ResourceUnlock"),
-            LogEventType::MessageBroadcast(recievers) => write!(f, "MessageBroadcast"),
-            LogEventType::MessageRecieve(task_id) => write!(f, "MessageRecieve"),
-            LogEventType::SemaphoreSignal(tasks_released,tasks_notified) => write!(f, "SemaphoreSignal"),
-            LogEventType::SemaphoreReset(task_id) => write!(f, "SemaphoreReset"),
-            LogEventType::DeadlineExpired(TaskId, u32) => write!(f, "DeadlineExpired"),
+            LogEventType::BlockTasks(_) => write!(f, "BlockTasks"),
+            LogEventType::UnblockTasks(_) => write!(f, "UnblockTasks"),
+            LogEventType::TaskExit(_) => write!(f, "TaskExit"),
+            LogEventType::ResourceLock(_) => write!(f, "ResourceLock"),
+            LogEventType::ResourceUnlock(_) => write!(f, "ResourceUnlock"),
+            LogEventType::MessageBroadcast(_) => write!(f, "MessageBroadcast"),
+            LogEventType::MessageRecieve(_) => write!(f, "MessageRecieve"),
+            LogEventType::SemaphoreSignal(_, _) => write!(f, "SemaphoreSignal"),
+            LogEventType::SemaphoreReset(_) => write!(f, "SemaphoreReset"),
+            LogEventType::DeadlineExpired(_, _) => write!(f, "DeadlineExpired"),
+            LogEventType::PriorityChange(_, _) => write!(f, "PriorityChange"),
             #[cfg(any(feature = "events_32", feature = "events_16", feature = "events_64"))]
-            LogEventType::TimerEvent(EventId) => write!(f, "TimerEvent"),
+            LogEventType::TimerEvent(_) => write!(f, "TimerEvent"),
+            #[cfg(feature = "interrupts")]
+            LogEventType::Interrupt(irqn) => write!(f, "Interrupt: {}", irqn),
         }
     }
 }
@@ -1,10 +1,9 @@
 use crate::config::MAX_TASKS;
-use crate::system::scheduler::TaskId;
-use crate::kernel::logging;
-use crate::system::system_logger::LogEventType;
+use crate::system::scheduler::{BooleanVector, TaskId};
 
 pub struct TaskMonitor {
-    active_deadlines: [Option<u32>; MAX_TASKS],
+    /// The absolute deadline of each task in ticks.
+    active_deadlines: [Option<u64>; MAX_TASKS],
     handler: Option<fn()>,
 }
 
@@ -15,25 +14,38 @@ impl TaskMonitor {
             handler: None,
         }
     }
-    pub fn set_deadline(&mut self, tid: TaskId, abs_deadline: u32) {
+    pub fn set_deadline(&mut self, tid: TaskId, abs_deadline: u64) {
         self.active_deadlines[tid as usize] = Some(abs_deadline);
     }
     pub fn set_handler(&mut self, handler: fn()) {
         self.handler = Some(handler);
     }
+    pub fn get_handler(&self) -> Option<fn()> {
+        self.handler
+    }
     pub fn clear_deadline(&mut self, tid: TaskId) {
         self.active_deadlines[tid as usize] = None;
     }
-    pub fn sweep_deadlines(&mut self, curr_time: u32) {
+    /// Returns the number of ticks until the earliest deadline is checked, `None` if there is none.
+    pub fn get_next_deadline(&self, curr_time: u64) -> Option<u64> {
+        self.active_deadlines
+            .iter()
+            .flatten()
+            .map(|deadline| deadline.saturating_sub(curr_time).max(1))
+            .min()
+    }
+    /// Clears the deadlines which have expired by `curr_time` and returns a boolean vector of the
+    /// tasks which missed them. Only the monitoring ends, the task keeps its deadline for scheduling.
+    pub fn sweep_deadlines(&mut self, curr_time: u64) -> BooleanVector {
+        let mut expired: BooleanVector = 0;
         for tid in 0..MAX_TASKS {
             if let Some(deadline) = self.active_deadlines[tid] {
-                if deadline == curr_time {
+                if deadline <= curr_time {
                     self.active_deadlines[tid] = None;
-                    if self.handler.is_some() {
-                        (self.handler.unwrap())();
-                    }
+                    expired |= 1 << tid;
                 }
             }
         }
+        expired
     }
-}
\ No newline at end of file
+}
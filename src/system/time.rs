@@ -0,0 +1,143 @@
+//! # Time
+//! The Definition of the types Kernel time is expressed in. Time is counted in ticks of the
+//! Kernel timer by a 64 bit counter, which does not wrap during the lifetime of a device.
+//! Conversions from and to real time units are based on `TICK_RATE_HZ`.
+
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+
+use crate::config::TICK_RATE_HZ;
+
+/// A point in time, counted in ticks since the Kernel timer was started.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant(u64);
+
+/// A span of time in ticks. Conversions from real time units round up, so that a `Duration` is
+/// never shorter than the time it was created from, conversions to them round down.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Duration(u64);
+
+impl Instant {
+    pub const fn from_ticks(ticks: u64) -> Self {
+        Self(ticks)
+    }
+
+    pub const fn ticks(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the time elapsed from `earlier` to this instant, zero if `earlier` is later.
+    pub const fn duration_since(&self, earlier: Instant) -> Duration {
+        Duration(self.0.saturating_sub(earlier.0))
+    }
+
+    /// Returns the time since the Kernel timer was started in milliseconds.
+    pub const fn as_millis(&self) -> u64 {
+        Duration(self.0).as_millis()
+    }
+
+    /// Returns the time since the Kernel timer was started in microseconds.
+    pub const fn as_micros(&self) -> u64 {
+        Duration(self.0).as_micros()
+    }
+}
+
+impl Duration {
+    pub const ZERO: Duration = Duration(0);
+
+    pub const fn from_ticks(ticks: u64) -> Self {
+        Self(ticks)
+    }
+
+    pub const fn from_secs(secs: u64) -> Self {
+        Self(secs.saturating_mul(TICK_RATE_HZ))
+    }
+
+    pub const fn from_millis(millis: u64) -> Self {
+        Self(ceil_div(millis.saturating_mul(TICK_RATE_HZ), 1_000))
+    }
+
+    pub const fn from_micros(micros: u64) -> Self {
+        Self(ceil_div(micros.saturating_mul(TICK_RATE_HZ), 1_000_000))
+    }
+
+    pub const fn ticks(&self) -> u64 {
+        self.0
+    }
+
+    pub const fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn as_secs(&self) -> u64 {
+        self.0 / TICK_RATE_HZ
+    }
+
+    pub const fn as_millis(&self) -> u64 {
+        self.0.saturating_mul(1_000) / TICK_RATE_HZ
+    }
+
+    pub const fn as_micros(&self) -> u64 {
+        self.0.saturating_mul(1_000_000) / TICK_RATE_HZ
+    }
+}
+
+const fn ceil_div(val: u64, div: u64) -> u64 {
+    val / div + (val % div != 0) as u64
+}
+
+// The arithmetic saturates rather than overflows, so that a time computed from a task supplied
+// duration can not wrap around into the past.
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+    fn add(self, rhs: Duration) -> Instant {
+        Instant(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, rhs: Duration) {
+        *self = *self + rhs;
+    }
+}
+
+/// Saturates at the start of the Kernel timer, like the difference of two instants does at zero.
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+    fn sub(self, rhs: Duration) -> Instant {
+        Instant(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+    fn sub(self, rhs: Instant) -> Duration {
+        self.duration_since(rhs)
+    }
+}
+
+impl Add for Duration {
+    type Output = Duration;
+    fn add(self, rhs: Duration) -> Duration {
+        Duration(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl AddAssign for Duration {
+    fn add_assign(&mut self, rhs: Duration) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Duration {
+    type Output = Duration;
+    fn sub(self, rhs: Duration) -> Duration {
+        Duration(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl SubAssign for Duration {
+    fn sub_assign(&mut self, rhs: Duration) {
+        *self = *self - rhs;
+    }
+}
@@ -0,0 +1,267 @@
+//! # Cortex-M0/M0+ port
+//!
+//! ARMv6-M lacks the `clz` instruction and only offers the Thumb-1 instruction set, hence the MSB
+//! is computed with a lookup table and the context switch only uses instructions available on
+//! ARMv6-M, i.e. high registers are moved through r4-r7 before being stored.
+
+// Platform specific Exports
+pub use cortex_m::interrupt::free as critical_section;
+pub use cortex_m::interrupt::{CriticalSection, Mutex};
+pub use cortex_m::peripheral::syst::SystClkSource;
+pub use cortex_m::peripheral::Peripherals;
+
+use cortex_m::register::control;
+use cortex_m_rt::exception;
+
+use crate::kernel::syscall::dispatch;
+use crate::kernel::tasks::context_switch;
+use crate::system::scheduler::{Context, ContextType, TaskControlBlock};
+use crate::utils::arch::Port;
+use crate::utils::helpers::get_msb_table;
+
+#[cfg(feature = "interrupts")]
+use {crate::kernel::interrupts::dispatch_irq, cortex_m::peripheral::NVIC};
+
+#[cfg(feature = "timer")]
+use {
+    crate::kernel::timer::{get_time, sys_tick},
+    core::sync::atomic::{AtomicU32, Ordering},
+    cortex_m::peripheral::SYST,
+};
+
+/// Largest value the SysTick counter can be reloaded with.
+#[cfg(feature = "timer")]
+const SYST_MAX: u32 = 0x00FF_FFFF;
+/// Enables the SysTick counter in SYST_CSR.
+#[cfg(feature = "timer")]
+const SYST_CSR_ENABLE: u32 = 1;
+/// SysTick reload value of a single tick, saved while the tick is suppressed.
+#[cfg(feature = "timer")]
+static TICK_RELOAD: AtomicU32 = AtomicU32::new(0);
+/// Cycles of the current tick which had passed when the tick was suppressed.
+#[cfg(feature = "timer")]
+static TICK_ELAPSED: AtomicU32 = AtomicU32::new(0);
+/// Number of ticks the last `suppress_ticks` programmed SysTick for.
+#[cfg(feature = "timer")]
+static SUPPRESSED_TICKS: AtomicU32 = AtomicU32::new(0);
+
+/// Port for ARMv6-M (Cortex-M0/M0+) microcontrollers.
+pub struct CortexM0;
+
+impl Port for CortexM0 {
+    /// Returns the MSB of `val` through a lookup table.
+    fn get_msb(val: u32) -> Option<usize> {
+        get_msb_table(val)
+    }
+
+    /// Writes the initial exception frame of a task onto `stack` so that the first `load_context`
+    /// followed by an exception return starts executing `handler` with `cxt` in R0.
+    /// Returns the stack pointer to be stored in the task's TCB.
+    fn init_stack(
+        stack: &mut [u32],
+        handler: fn(ContextType) -> !,
+        cxt: *const Context,
+        _uses_fpu: bool,
+    ) -> usize {
+        let pos = stack.len() - 1;
+        let pc: usize = handler as usize;
+
+        stack[pos] = 1 << 24; // xPSR
+        stack[pos - 1] = pc as u32; // PC
+        stack[pos - 2] = 0xFFFFFFFD; // LR
+        stack[pos - 3] = 0xCCCCCCCC; // R12
+        stack[pos - 4] = 0x33333333; // R3
+        stack[pos - 5] = 0x22222222; // R2
+        stack[pos - 6] = 0x11111111; // R1
+        stack[pos - 7] = cxt as u32; // R0
+                                     // aditional regs
+        stack[pos - 08] = 0x77777777; // R7
+        stack[pos - 09] = 0x66666666; // R6
+        stack[pos - 10] = 0x55555555; // R5
+        stack[pos - 11] = 0x44444444; // R4
+        stack[pos - 12] = 0xBBBBBBBB; // R11
+        stack[pos - 13] = 0xAAAAAAAA; // R10
+        stack[pos - 14] = 0x99999999; // R9
+        stack[pos - 15] = 0x88888888; // R8
+
+        unsafe { core::intrinsics::transmute(&stack[stack.len() - 16]) }
+    }
+
+    #[inline(always)]
+    fn save_context(task_stack: &TaskControlBlock) {
+        unsafe {
+            asm!(
+                "mrs r0, psp",
+                "subs r0, #16",
+                "stmia r0!,{{r4-r7}}",
+                "mov	r4, r8",
+                "mov	r5, r9",
+                "mov	r6, r10",
+                "mov	r7, r11",
+                "subs	r0, #32",
+                "stmia	r0!,{{r4-r7}}",
+                "subs	r0, #16",
+                "mov	r1, {0}",
+                "@ldr	r1, [r2]",
+                "str	r0, [r1]",
+                in(reg) task_stack,
+                out("r0") _,
+                out("r1") _,
+            )
+        };
+    }
+
+    #[inline(always)]
+    fn load_context(task_stack: &TaskControlBlock) {
+        unsafe {
+            asm!(
+                "cpsid	i",
+                "mov	r1, {0}",
+                "@ldr	r1, [r2]",
+                "@ldr	r1, [r1]",
+                "ldr	r0, [r1]",
+                "ldmia	r0!,{{r4-r7}}",
+                "mov	r8, r4",
+                "mov	r9, r5",
+                "mov	r10, r6",
+                "mov	r11, r7",
+                "ldmia	r0!,{{r4-r7}}",
+                "msr	psp, r0",
+                in(reg) task_stack,
+                out("r0") _,
+                out("r1") _,
+            )
+        };
+    }
+
+    fn set_pendsv() {
+        cortex_m::peripheral::SCB::set_pendsv();
+    }
+
+    fn wait_for_interrupt() {
+        cortex_m::asm::wfi();
+    }
+
+    /// Returns true if Currently the Kernel is operating in Privileged mode.
+    fn is_privileged() -> bool {
+        return control::read().npriv() == control::Npriv::Privileged;
+    }
+
+    /// All tasks run privileged, hence the system call is dispatched right away.
+    fn syscall(number: usize, args: [usize; 3]) -> usize {
+        dispatch(number, args)
+    }
+
+    /// ARMv6-M has no cycle counter, hence run time is measured in ticks.
+    fn start_cycle_counter() {}
+
+    fn get_cycles() -> u32 {
+        #[cfg(feature = "timer")]
+        return get_time().ticks() as u32;
+        #[cfg(not(feature = "timer"))]
+        return 0;
+    }
+
+    /// Stretches the SysTick period over the ticks to be suppressed, counting from where the
+    /// current tick is. Not done if the SysTick interrupt is already pending.
+    #[cfg(feature = "timer")]
+    fn suppress_ticks(ticks: u32) -> u32 {
+        let syst = unsafe { &*SYST::ptr() };
+        let reload = syst.rvr.read();
+        let interval = reload + 1;
+        let ticks = ticks.min(SYST_MAX / interval);
+        if ticks < 2 {
+            return 0;
+        }
+        unsafe { syst.csr.modify(|csr| csr & !SYST_CSR_ENABLE) };
+        if cortex_m::peripheral::SCB::is_pendst_pending() {
+            unsafe { syst.csr.modify(|csr| csr | SYST_CSR_ENABLE) };
+            return 0;
+        }
+        let remaining = syst.cvr.read();
+        unsafe {
+            syst.rvr.write(remaining + (ticks - 1) * interval);
+            syst.cvr.write(0);
+            syst.csr.modify(|csr| csr | SYST_CSR_ENABLE);
+        }
+        TICK_RELOAD.store(reload, Ordering::SeqCst);
+        TICK_ELAPSED.store(interval - remaining, Ordering::SeqCst);
+        SUPPRESSED_TICKS.store(ticks, Ordering::SeqCst);
+        ticks
+    }
+
+    /// Counts the whole ticks since the last tick before the sleep and restarts SysTick so that
+    /// the next tick stays in phase. If SysTick expired, its pending interrupt accounts for the last tick.
+    #[cfg(feature = "timer")]
+    fn resume_ticks() -> u32 {
+        let syst = unsafe { &*SYST::ptr() };
+        let reload = TICK_RELOAD.load(Ordering::SeqCst);
+        let interval = reload + 1;
+        unsafe { syst.csr.modify(|csr| csr & !SYST_CSR_ENABLE) };
+        let (passed, first_reload) = if cortex_m::peripheral::SCB::is_pendst_pending() {
+            (SUPPRESSED_TICKS.load(Ordering::SeqCst) - 1, reload)
+        } else {
+            let elapsed = TICK_ELAPSED.load(Ordering::SeqCst) + syst.rvr.read() - syst.cvr.read();
+            (elapsed / interval, (interval - elapsed % interval - 1).max(1))
+        };
+        // The reload value only takes effect on the next wrap, hence the shortened first period.
+        unsafe {
+            syst.rvr.write(first_reload);
+            syst.cvr.write(0);
+            syst.csr.modify(|csr| csr | SYST_CSR_ENABLE);
+            syst.rvr.write(reload);
+        }
+        passed
+    }
+
+    #[cfg(feature = "interrupts")]
+    fn enable_irq(irqn: u16) {
+        let nvic = unsafe { &*NVIC::ptr() };
+        unsafe { nvic.iser[irqn as usize / 32].write(1 << (irqn % 32)) };
+    }
+
+    #[cfg(feature = "interrupts")]
+    fn disable_irq(irqn: u16) {
+        let nvic = unsafe { &*NVIC::ptr() };
+        unsafe { nvic.icer[irqn as usize / 32].write(1 << (irqn % 32)) };
+    }
+}
+
+#[inline(always)]
+unsafe fn return_to_psp() {
+    asm!(
+        "
+        ldr r0, =0xFFFFFFFD
+        bx	r0
+        "
+    );
+}
+
+/// ### SysTick Interrupt handler
+/// Its the Crux of the Kernel’s time management module and Task scheduling.
+/// This interrupt handler updates the time and also dispatches the appropriate event handlers.
+/// The interrupt handler also calls `schedule()` in here so as to dispatch any higher priority
+/// task if there are any.
+#[cfg(feature = "timer")]
+#[exception]
+fn SysTick() {
+    sys_tick();
+}
+
+/// ### Default Interrupt handler
+/// Takes every interrupt without a handler of its own, i.e. the ones left to the Kernel, and
+/// executes the action the interrupt is bound to. The others are passed on to the handler set
+/// through `interrupts::set_default_handler`. `irqn` is negative for system exceptions.
+#[cfg(feature = "interrupts")]
+#[exception]
+fn DefaultHandler(irqn: i16) {
+    dispatch_irq(irqn);
+}
+
+/// ### PendSV Interrupt handler,
+/// PendSV interrupt handler does the actual context switch in the Kernel.
+#[exception]
+fn PendSV() {
+    context_switch();
+    unsafe { return_to_psp() }
+}
@@ -0,0 +1,543 @@
+//! # Cortex-M3/M4 port
+//!
+//! Defines functions which are defined majorly in assembly. Thus, might change for one board to another.
+//!
+//! With the `fpu` feature (Cortex-M4F/M7F) every saved context additionally starts with the
+//! EXC_RETURN value the task has to be resumed with. If its bit 4 is clear, the hardware has
+//! stacked an extended frame for the task, hence S16-S31 are saved and restored along with R4-R11.
+//! Lazy stacking stays in effect, the `vstmdb` of S16-S31 triggers the deferred stacking of S0-S15.
+
+// Platform specific Exports
+pub use cortex_m::interrupt::free as critical_section;
+pub use cortex_m::interrupt::{CriticalSection, Mutex};
+pub use cortex_m::peripheral::syst::SystClkSource;
+pub use cortex_m::peripheral::Peripherals;
+
+use cortex_m::peripheral::{DCB, DWT};
+use cortex_m::register::{control, psp};
+use cortex_m_rt::exception;
+
+use crate::kernel::syscall::dispatch;
+use crate::kernel::tasks::context_switch;
+use crate::system::scheduler::{Context, ContextType, TaskControlBlock};
+use crate::utils::arch::Port;
+
+#[cfg(feature = "interrupts")]
+use {crate::kernel::interrupts::dispatch_irq, cortex_m::peripheral::NVIC};
+
+#[cfg(feature = "timer")]
+use {
+    crate::kernel::timer::sys_tick,
+    core::sync::atomic::{AtomicU32, Ordering},
+    cortex_m::peripheral::SYST,
+};
+
+#[cfg(feature = "mpu")]
+use {
+    crate::kernel::mpu::task_fault,
+    crate::system::mpu::MemoryRegion,
+    cortex_m::peripheral::{MPU, SCB},
+};
+
+/// EXC_RETURN of the task being switched out, replaced by that of the task being switched in.
+#[cfg(feature = "fpu")]
+static mut EXC_RETURN: u32 = 0xFFFFFFFD;
+
+/// Enables the DWT and ITM blocks in DEMCR.
+const DEMCR_TRCENA: u32 = 1 << 24;
+/// Enables the cycle counter in DWT_CTRL.
+const DWT_CYCCNTENA: u32 = 1;
+
+/// Largest value the SysTick counter can be reloaded with.
+#[cfg(feature = "timer")]
+const SYST_MAX: u32 = 0x00FF_FFFF;
+/// Enables the SysTick counter in SYST_CSR.
+#[cfg(feature = "timer")]
+const SYST_CSR_ENABLE: u32 = 1;
+/// SysTick reload value of a single tick, saved while the tick is suppressed.
+#[cfg(feature = "timer")]
+static TICK_RELOAD: AtomicU32 = AtomicU32::new(0);
+/// Cycles of the current tick which had passed when the tick was suppressed.
+#[cfg(feature = "timer")]
+static TICK_ELAPSED: AtomicU32 = AtomicU32::new(0);
+/// Number of ticks the last `suppress_ticks` programmed SysTick for.
+#[cfg(feature = "timer")]
+static SUPPRESSED_TICKS: AtomicU32 = AtomicU32::new(0);
+
+/// Port for ARMv7-M (Cortex-M3/M4) microcontrollers.
+pub struct CortexM;
+
+impl Port for CortexM {
+    /// Returns the MSB of `val`. It is written using CLZ instruction.
+    fn get_msb(val: u32) -> Option<usize> {
+        let mut res: usize;
+        unsafe {
+            asm!(
+                "clz {1}, {0}",
+                in(reg) val,
+                out(reg) res,
+            );
+        }
+        res = 32 - res;
+        if res == 0 {
+            return None;
+        } else {
+            res -= 1;
+        }
+        return Some(res);
+    }
+
+    /// Writes the initial exception frame of a task onto `stack` so that the first `load_context`
+    /// followed by an exception return starts executing `handler` with `cxt` in R0.
+    /// Returns the stack pointer to be stored in the task's TCB.
+    #[cfg(not(feature = "fpu"))]
+    fn init_stack(
+        stack: &mut [u32],
+        handler: fn(ContextType) -> !,
+        cxt: *const Context,
+        _uses_fpu: bool,
+    ) -> usize {
+        let pos = stack.len() - 1;
+        let pc: usize = handler as usize;
+
+        stack[pos] = 1 << 24; // xPSR
+        stack[pos - 1] = pc as u32; // PC
+        stack[pos - 2] = 0xFFFFFFFD; // LR
+        stack[pos - 3] = 0xCCCCCCCC; // R12
+        stack[pos - 4] = 0x33333333; // R3
+        stack[pos - 5] = 0x22222222; // R2
+        stack[pos - 6] = 0x11111111; // R1
+        stack[pos - 7] = cxt as u32; // R0
+                                     // aditional regs
+        stack[pos - 08] = 0x77777777; // R7
+        stack[pos - 09] = 0x66666666; // R6
+        stack[pos - 10] = 0x55555555; // R5
+        stack[pos - 11] = 0x44444444; // R4
+        stack[pos - 12] = 0xBBBBBBBB; // R11
+        stack[pos - 13] = 0xAAAAAAAA; // R10
+        stack[pos - 14] = 0x99999999; // R9
+        stack[pos - 15] = 0x88888888; // R8
+
+        unsafe { core::intrinsics::transmute(&stack[stack.len() - 16]) }
+    }
+
+    /// Writes the initial exception frame of a task onto `stack`, preceded by its EXC_RETURN.
+    /// Tasks using the FPU start off an extended frame with cleared S0-S31 and FPSCR.
+    /// Returns the stack pointer to be stored in the task's TCB.
+    #[cfg(feature = "fpu")]
+    fn init_stack(
+        stack: &mut [u32],
+        handler: fn(ContextType) -> !,
+        cxt: *const Context,
+        uses_fpu: bool,
+    ) -> usize {
+        let mut pos = stack.len();
+        if uses_fpu {
+            // S0-S15, FPSCR and the reserved word of the extended frame.
+            pos -= 18;
+            for reg in stack[pos..pos + 18].iter_mut() {
+                *reg = 0;
+            }
+        }
+        pos -= 8;
+        stack[pos + 7] = 1 << 24; // xPSR
+        stack[pos + 6] = handler as usize as u32; // PC
+        stack[pos + 5] = 0xFFFFFFFD; // LR
+        stack[pos + 4] = 0xCCCCCCCC; // R12
+        stack[pos + 3] = 0x33333333; // R3
+        stack[pos + 2] = 0x22222222; // R2
+        stack[pos + 1] = 0x11111111; // R1
+        stack[pos] = cxt as u32; // R0
+        if uses_fpu {
+            // S16-S31
+            pos -= 16;
+            for reg in stack[pos..pos + 16].iter_mut() {
+                *reg = 0;
+            }
+        }
+        pos -= 8;
+        stack[pos + 7] = 0xBBBBBBBB; // R11
+        stack[pos + 6] = 0xAAAAAAAA; // R10
+        stack[pos + 5] = 0x99999999; // R9
+        stack[pos + 4] = 0x88888888; // R8
+        stack[pos + 3] = 0x77777777; // R7
+        stack[pos + 2] = 0x66666666; // R6
+        stack[pos + 1] = 0x55555555; // R5
+        stack[pos] = 0x44444444; // R4
+        pos -= 1;
+        stack[pos] = if uses_fpu { 0xFFFFFFED } else { 0xFFFFFFFD }; // EXC_RETURN
+
+        unsafe { core::intrinsics::transmute(&stack[pos]) }
+    }
+
+    #[cfg(not(feature = "fpu"))]
+    #[inline(always)]
+    fn save_context(task_stack: &TaskControlBlock) {
+        unsafe {
+            asm!(
+                "mrs r0, psp",
+                "subs r0, #16",
+                "stmia r0!,{{r4-r7}}",
+                "mov	r4, r8",
+                "mov	r5, r9",
+                "mov	r6, r10",
+                "mov	r7, r11",
+                "subs	r0, #32",
+                "stmia	r0!,{{r4-r7}}",
+                "subs	r0, #16",
+                "mov	r1, {0}",
+                "@ldr	r1, [r2]",
+                "str	r0, [r1]",
+                in(reg) task_stack,
+                out("r0") _,
+                out("r1") _,
+            )
+        };
+    }
+
+    #[cfg(feature = "fpu")]
+    #[inline(always)]
+    fn save_context(task_stack: &TaskControlBlock) {
+        unsafe {
+            asm!(
+                "mrs	r0, psp",
+                "ldr	r1, [r3]",
+                "tst	r1, #0x10",
+                "it	eq",
+                "vstmdbeq	r0!, {{s16-s31}}",
+                "stmdb	r0!, {{r1, r4-r11}}",
+                "str	r0, [r2]",
+                in("r2") task_stack,
+                in("r3") core::ptr::addr_of!(EXC_RETURN),
+                out("r0") _,
+                out("r1") _,
+            )
+        };
+    }
+
+    #[cfg(not(feature = "fpu"))]
+    #[inline(always)]
+    fn load_context(task_stack: &TaskControlBlock) {
+        unsafe {
+            asm!(
+                "cpsid	i",
+                "mov	r1, {0}",
+                "@ldr	r1, [r2]",
+                "@ldr	r1, [r1]",
+                "ldr	r0, [r1]",
+                "ldmia	r0!,{{r4-r7}}",
+                "mov	r8, r4",
+                "mov	r9, r5",
+                "mov	r10, r6",
+                "mov	r11, r7",
+                "ldmia	r0!,{{r4-r7}}",
+                "msr	psp, r0",
+                "msr	control, r12",
+                in(reg) task_stack,
+                in("r12") thread_control(task_stack.privileged),
+                out("r0") _,
+                out("r1") _,
+            )
+        };
+    }
+
+    #[cfg(feature = "fpu")]
+    #[inline(always)]
+    fn load_context(task_stack: &TaskControlBlock) {
+        unsafe {
+            asm!(
+                "cpsid	i",
+                "ldr	r0, [r2]",
+                "ldmia	r0!, {{r1, r4-r11}}",
+                "tst	r1, #0x10",
+                "it	eq",
+                "vldmiaeq	r0!, {{s16-s31}}",
+                "msr	psp, r0",
+                "msr	control, r12",
+                "str	r1, [r3]",
+                in("r2") task_stack,
+                in("r3") core::ptr::addr_of_mut!(EXC_RETURN),
+                in("r12") thread_control(task_stack.privileged),
+                out("r0") _,
+                out("r1") _,
+            )
+        };
+    }
+
+    fn set_pendsv() {
+        cortex_m::peripheral::SCB::set_pendsv();
+    }
+
+    fn wait_for_interrupt() {
+        cortex_m::asm::wfi();
+    }
+
+    /// Returns true if Currently the Kernel is operating in Privileged mode.
+    /// Handler mode is always privileged, whatever the privilege of Thread mode is.
+    fn is_privileged() -> bool {
+        let ipsr: u32;
+        unsafe { asm!("mrs {0}, ipsr", out(reg) ipsr) };
+        return ipsr != 0 || control::read().npriv() == control::Npriv::Privileged;
+    }
+
+    /// Raises the SVCall exception with the system call number in R0 and its arguments in R1-R3.
+    /// The SVCall handler replaces the stacked R0 with the result.
+    fn syscall(number: usize, args: [usize; 3]) -> usize {
+        let res: usize;
+        unsafe {
+            asm!(
+                "svc 0",
+                inout("r0") number => res,
+                in("r1") args[0],
+                in("r2") args[1],
+                in("r3") args[2],
+            )
+        };
+        res
+    }
+
+    /// Enables the DWT cycle counter.
+    fn start_cycle_counter() {
+        unsafe {
+            (*DCB::ptr()).demcr.modify(|demcr| demcr | DEMCR_TRCENA);
+            (*DWT::ptr()).ctrl.modify(|ctrl| ctrl | DWT_CYCCNTENA);
+        }
+    }
+
+    fn get_cycles() -> u32 {
+        unsafe { (*DWT::ptr()).cyccnt.read() }
+    }
+
+    /// Stretches the SysTick period over the ticks to be suppressed, counting from where the
+    /// current tick is. Not done if the SysTick interrupt is already pending.
+    #[cfg(feature = "timer")]
+    fn suppress_ticks(ticks: u32) -> u32 {
+        let syst = unsafe { &*SYST::ptr() };
+        let reload = syst.rvr.read();
+        let interval = reload + 1;
+        let ticks = ticks.min(SYST_MAX / interval);
+        if ticks < 2 {
+            return 0;
+        }
+        unsafe { syst.csr.modify(|csr| csr & !SYST_CSR_ENABLE) };
+        if cortex_m::peripheral::SCB::is_pendst_pending() {
+            unsafe { syst.csr.modify(|csr| csr | SYST_CSR_ENABLE) };
+            return 0;
+        }
+        let remaining = syst.cvr.read();
+        unsafe {
+            syst.rvr.write(remaining + (ticks - 1) * interval);
+            syst.cvr.write(0);
+            syst.csr.modify(|csr| csr | SYST_CSR_ENABLE);
+        }
+        TICK_RELOAD.store(reload, Ordering::SeqCst);
+        TICK_ELAPSED.store(interval - remaining, Ordering::SeqCst);
+        SUPPRESSED_TICKS.store(ticks, Ordering::SeqCst);
+        ticks
+    }
+
+    /// Counts the whole ticks since the last tick before the sleep and restarts SysTick so that
+    /// the next tick stays in phase. If SysTick expired, its pending interrupt accounts for the last tick.
+    #[cfg(feature = "timer")]
+    fn resume_ticks() -> u32 {
+        let syst = unsafe { &*SYST::ptr() };
+        let reload = TICK_RELOAD.load(Ordering::SeqCst);
+        let interval = reload + 1;
+        unsafe { syst.csr.modify(|csr| csr & !SYST_CSR_ENABLE) };
+        let (passed, first_reload) = if cortex_m::peripheral::SCB::is_pendst_pending() {
+            (SUPPRESSED_TICKS.load(Ordering::SeqCst) - 1, reload)
+        } else {
+            let elapsed = TICK_ELAPSED.load(Ordering::SeqCst) + syst.rvr.read() - syst.cvr.read();
+            (elapsed / interval, (interval - elapsed % interval - 1).max(1))
+        };
+        // The reload value only takes effect on the next wrap, hence the shortened first period.
+        unsafe {
+            syst.rvr.write(first_reload);
+            syst.cvr.write(0);
+            syst.csr.modify(|csr| csr | SYST_CSR_ENABLE);
+            syst.rvr.write(reload);
+        }
+        passed
+    }
+
+    #[cfg(feature = "interrupts")]
+    fn enable_irq(irqn: u16) {
+        let nvic = unsafe { &*NVIC::ptr() };
+        unsafe { nvic.iser[irqn as usize / 32].write(1 << (irqn % 32)) };
+    }
+
+    #[cfg(feature = "interrupts")]
+    fn disable_irq(irqn: u16) {
+        let nvic = unsafe { &*NVIC::ptr() };
+        unsafe { nvic.icer[irqn as usize / 32].write(1 << (irqn % 32)) };
+    }
+}
+
+/// Returns the value of CONTROL which makes Thread mode run with the task's privilege.
+/// `load_context` writes it along with PSP, it takes effect on the exception return.
+#[inline(always)]
+fn thread_control(privileged: bool) -> u32 {
+    let mut ctrl = control::read();
+    ctrl.set_npriv(if privileged {
+        control::Npriv::Privileged
+    } else {
+        control::Npriv::Unprivileged
+    });
+    ctrl.bits()
+}
+
+#[cfg(not(feature = "fpu"))]
+#[inline(always)]
+unsafe fn return_to_psp() {
+    asm!(
+        "
+        ldr r0, =0xFFFFFFFD
+        bx	r0
+        "
+    );
+}
+
+#[cfg(feature = "fpu")]
+#[inline(always)]
+unsafe fn return_to_psp() {
+    asm!(
+        "ldr	r0, [{0}]",
+        "bx	r0",
+        in(reg) core::ptr::addr_of!(EXC_RETURN),
+    );
+}
+
+/// ### SysTick Interrupt handler
+/// Its the Crux of the Kernel’s time management module and Task scheduling.
+/// This interrupt handler updates the time and also dispatches the appropriate event handlers.
+/// The interrupt handler also calls `schedule()` in here so as to dispatch any higher priority
+/// task if there are any.
+#[cfg(feature = "timer")]
+#[exception]
+fn SysTick() {
+    sys_tick();
+}
+
+/// ### Default Interrupt handler
+/// Takes every interrupt without a handler of its own, i.e. the ones left to the Kernel, and
+/// executes the action the interrupt is bound to. The others are passed on to the handler set
+/// through `interrupts::set_default_handler`. `irqn` is negative for system exceptions.
+#[cfg(feature = "interrupts")]
+#[exception]
+fn DefaultHandler(irqn: i16) {
+    dispatch_irq(irqn);
+}
+
+/// ### PendSV Interrupt handler,
+/// PendSV interrupt handler does the actual context switch in the Kernel.
+#[exception]
+fn PendSV() {
+    #[cfg(feature = "fpu")]
+    unsafe {
+        asm!("str	lr, [{0}]", in(reg) core::ptr::addr_of_mut!(EXC_RETURN));
+    }
+    context_switch();
+    unsafe { return_to_psp() }
+}
+
+/// ### SVCall Interrupt handler
+/// Executes the system call raised by an unprivileged task. The number and arguments of the
+/// system call are read from the exception frame stacked onto the task's stack, the result is
+/// written back in place of the stacked R0.
+#[exception]
+fn SVCall() {
+    let frame = psp::read() as *mut usize;
+    unsafe {
+        let args = [*frame.add(1), *frame.add(2), *frame.add(3)];
+        *frame = dispatch(*frame, args);
+    }
+}
+
+/// MPU_RBAR.VALID, makes the write to MPU_RBAR select the region in its low bits.
+#[cfg(feature = "mpu")]
+const RBAR_VALID: u32 = 1 << 4;
+/// MPU_RASR.XN, instruction fetches from the region fault.
+#[cfg(feature = "mpu")]
+const RASR_XN: u32 = 1 << 28;
+/// MPU_RASR.AP, read-only for privileged and unprivileged accesses.
+#[cfg(feature = "mpu")]
+const RASR_AP_RO: u32 = 0b110 << 24;
+/// MPU_RASR.AP, full access for privileged and unprivileged accesses.
+#[cfg(feature = "mpu")]
+const RASR_AP_FULL: u32 = 0b011 << 24;
+/// MPU_RASR attributes for Flash (normal, write-through, not shareable).
+#[cfg(feature = "mpu")]
+const RASR_FLASH: u32 = 1 << 17;
+/// MPU_RASR attributes for SRAM (normal, write-through, shareable).
+#[cfg(feature = "mpu")]
+const RASR_SRAM: u32 = (1 << 18) | (1 << 17);
+#[cfg(feature = "mpu")]
+const RASR_ENABLE: u32 = 1;
+/// MPU_CTRL.PRIVDEFENA and MPU_CTRL.ENABLE, privileged code keeps the default memory map.
+#[cfg(feature = "mpu")]
+const CTRL_PRIVDEFENA_ENABLE: u32 = (1 << 2) | 1;
+/// SCB_SHCSR.MEMFAULTENA
+#[cfg(feature = "mpu")]
+const SHCSR_MEMFAULTENA: u32 = 1 << 16;
+/// SCB_ICSR.RETTOBASE, set if the active exception was taken from Thread mode.
+#[cfg(feature = "mpu")]
+const ICSR_RETTOBASE: u32 = 1 << 11;
+
+/// Returns the MPU_RASR.SIZE field for a region of `size` bytes.
+#[cfg(feature = "mpu")]
+fn rasr_size(size: usize) -> u32 {
+    (size.trailing_zeros() - 1) << 1
+}
+
+/// Enables the MPU with region 0 covering the code region (0x00000000 - 0x1FFFFFFF) read-only,
+/// and enables the MemManage fault.
+#[cfg(feature = "mpu")]
+pub fn enable_mpu() {
+    unsafe {
+        let mpu = &*MPU::ptr();
+        mpu.rbar.write(0x0000_0000 | RBAR_VALID | 0);
+        mpu.rasr
+            .write(RASR_AP_RO | RASR_FLASH | rasr_size(0x2000_0000) | RASR_ENABLE);
+        mpu.ctrl.write(CTRL_PRIVDEFENA_ENABLE);
+        (*SCB::ptr()).shcsr.modify(|shcsr| shcsr | SHCSR_MEMFAULTENA);
+    }
+    cortex_m::asm::dsb();
+    cortex_m::asm::isb();
+}
+
+/// Sets MPU region `number` to cover `region` with full access and no execution,
+/// the region is disabled if `region` is `None`.
+#[cfg(feature = "mpu")]
+pub fn set_mpu_region(number: usize, region: Option<MemoryRegion>) {
+    unsafe {
+        let mpu = &*MPU::ptr();
+        match region {
+            Some(region) => {
+                mpu.rbar
+                    .write(region.base as u32 | RBAR_VALID | number as u32);
+                mpu.rasr.write(
+                    RASR_XN | RASR_AP_FULL | RASR_SRAM | rasr_size(region.size) | RASR_ENABLE,
+                );
+            }
+            None => {
+                mpu.rnr.write(number as u32);
+                mpu.rasr.write(0);
+            }
+        }
+    }
+}
+
+/// ### MemManage Interrupt handler
+/// A memory access violation by a task faults the task instead of halting the system.
+/// A violation by an exception handler can not be recovered from.
+#[cfg(feature = "mpu")]
+#[exception]
+fn MemoryManagement() {
+    let scb = unsafe { &*SCB::ptr() };
+    if scb.icsr.read() & ICSR_RETTOBASE == 0 {
+        panic!("MemManage fault in an exception handler");
+    }
+    // Clears the MemManage fault status.
+    unsafe { scb.cfsr.write(0xFF) };
+    task_fault();
+}
@@ -0,0 +1,575 @@
+//! # Host simulation port
+//!
+//! Runs the Kernel on a hosted (`std`) target so that the Kernel and applications built on it
+//! can be exercised by `cargo test`. Every task runs on its own OS thread, but only the thread
+//! holding the simulated CPU executes at any time. Interrupts (including `SysTick`) are raised
+//! from outside the Kernel via `interrupt` and `tick`, and are serviced by the running task when
+//! it leaves its next critical section, or right away if it is idling in `wait_for_interrupt`.
+//! Hence tasks are only preempted at Kernel calls, a task spinning without calling into the
+//! Kernel is never preempted.
+//!
+//! Like on ARMv7-M, tasks created with `create_unprivileged_task` run unprivileged and reach the
+//! Kernel through system calls. Tests can inject the cycle counter the port reports through
+//! `set_cycle_counter`, or replace the simulated CPU altogether by a mock `Port` registered
+//! through `register_port`. The Kernel then calls the mock instead, and the test drives the
+//! Kernel itself on its own thread through `run_pendsv` and `run_systick`.
+//!
+//! In tickless idle mode the simulated SysTick is stretched like on ARMv7-M: the ticks of a
+//! suppressed period are only counted by `tick`, and the last of them raises the interrupt.
+
+use core::cell::{Cell, UnsafeCell};
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::boxed::Box;
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex as StdMutex, MutexGuard, OnceLock};
+use std::thread;
+use std::vec::Vec;
+
+use crate::kernel::syscall::dispatch;
+use crate::kernel::tasks::context_switch;
+use crate::system::scheduler::{Context, ContextType, TaskControlBlock};
+use crate::utils::arch::Port;
+
+#[cfg(feature = "timer")]
+use {
+    crate::kernel::timer::{get_time, sys_tick},
+    core::sync::atomic::AtomicU32,
+};
+
+#[cfg(feature = "interrupts")]
+use crate::kernel::interrupts::dispatch_irq;
+
+/// Token proving that the holder is inside a critical section.
+pub struct CriticalSection {
+    _0: (),
+}
+
+/// A data container which can only be accessed inside a critical section, the host counterpart
+/// of `cortex_m::interrupt::Mutex`.
+pub struct Mutex<T> {
+    inner: UnsafeCell<T>,
+}
+
+impl<T> Mutex<T> {
+    pub const fn new(value: T) -> Self {
+        Mutex {
+            inner: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn borrow<'cs>(&'cs self, _cs: &'cs CriticalSection) -> &'cs T {
+        unsafe { &*self.inner.get() }
+    }
+}
+
+unsafe impl<T> Sync for Mutex<T> where T: Send {}
+
+/// Clock source of the simulated SysTick, kept for API compatibility with the Cortex-M port.
+pub enum SystClkSource {
+    Core,
+    External,
+}
+
+/// The simulated SysTick timer. It only fires when `tick` is called.
+pub struct SYST {
+    _0: (),
+}
+
+impl SYST {
+    pub fn set_clock_source(&mut self, _clk_source: SystClkSource) {}
+
+    pub fn set_reload(&mut self, _reload_value: u32) {}
+
+    pub fn enable_counter(&mut self) {}
+
+    pub fn enable_interrupt(&mut self) {
+        SYSTICK_ENABLED.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Simulated core peripherals.
+#[allow(non_snake_case)]
+pub struct Peripherals {
+    pub SYST: SYST,
+}
+
+impl Peripherals {
+    /// Returns the peripherals once, mirroring `cortex_m::Peripherals::take`.
+    pub fn take() -> Option<Self> {
+        if PERIPHERALS_TAKEN.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        Some(Peripherals { SYST: SYST { _0: () } })
+    }
+}
+
+type Isr = Box<dyn FnOnce() + Send>;
+
+/// State of the simulated CPU.
+struct Cpu {
+    /// Stack pointer of the task whose thread currently owns the CPU.
+    running: Option<usize>,
+    /// Interrupts raised but not yet serviced.
+    pending: VecDeque<Isr>,
+    /// Number of interrupts raised so far.
+    raised: u64,
+    /// Number of interrupts serviced so far.
+    serviced: u64,
+    /// True while the idle task waits for an interrupt.
+    idle: bool,
+}
+
+/// A task registered through `init_stack`.
+struct HostTask {
+    stack_pointer: usize,
+    handler: fn(ContextType) -> !,
+    cxt: usize,
+    spawned: bool,
+}
+
+static CPU: StdMutex<Cpu> = StdMutex::new(Cpu {
+    running: None,
+    pending: VecDeque::new(),
+    raised: 0,
+    serviced: 0,
+    idle: false,
+});
+static CPU_EVENT: Condvar = Condvar::new();
+static TASKS: StdMutex<Vec<HostTask>> = StdMutex::new(Vec::new());
+static PENDSV: AtomicBool = AtomicBool::new(false);
+static SYSTICK_ENABLED: AtomicBool = AtomicBool::new(false);
+/// The number of ticks the SysTick is stretched over by `suppress_ticks`, zero if it is not.
+#[cfg(feature = "timer")]
+static SUPPRESSED_TICKS: AtomicU32 = AtomicU32::new(0);
+/// The number of ticks which passed since the SysTick was stretched.
+#[cfg(feature = "timer")]
+static PASSED_TICKS: AtomicU32 = AtomicU32::new(0);
+static PERIPHERALS_TAKEN: AtomicBool = AtomicBool::new(false);
+/// The counterpart of CONTROL.nPRIV, false while an unprivileged task holds the CPU.
+static THREAD_PRIVILEGED: AtomicBool = AtomicBool::new(true);
+/// The cycle counter injected through `set_cycle_counter`, `None` for the tick count.
+static CYCLE_COUNTER: StdMutex<Option<fn() -> u32>> = StdMutex::new(None);
+/// The operations of the port registered through `register_port`.
+static HOOKS: OnceLock<PortHooks> = OnceLock::new();
+
+/// `Port::init_stack`, which sets up the stack of a task and returns its stack pointer.
+type InitStack = fn(&mut [u32], fn(ContextType) -> !, *const Context, bool) -> usize;
+
+/// The operations of a `Port`, taken as function pointers as the Kernel is compiled for `Host`.
+struct PortHooks {
+    get_msb: fn(u32) -> Option<usize>,
+    init_stack: InitStack,
+    save_context: fn(&TaskControlBlock),
+    load_context: fn(&TaskControlBlock),
+    set_pendsv: fn(),
+    wait_for_interrupt: fn(),
+    is_privileged: fn() -> bool,
+    syscall: fn(usize, [usize; 3]) -> usize,
+    start_cycle_counter: fn(),
+    get_cycles: fn() -> u32,
+    #[cfg(feature = "timer")]
+    suppress_ticks: fn(u32) -> u32,
+    #[cfg(feature = "timer")]
+    resume_ticks: fn() -> u32,
+    #[cfg(feature = "interrupts")]
+    enable_irq: fn(u16),
+    #[cfg(feature = "interrupts")]
+    disable_irq: fn(u16),
+}
+
+fn hooks() -> Option<&'static PortHooks> {
+    HOOKS.get()
+}
+
+std::thread_local! {
+    /// Stack pointer of the task this thread runs, `None` for threads which are not tasks.
+    static CURRENT: Cell<Option<usize>> = const { Cell::new(None) };
+    /// Nesting depth of critical sections on this thread.
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+    /// True while this thread executes an interrupt or PendSV handler.
+    static IN_HANDLER: Cell<bool> = const { Cell::new(false) };
+    /// Task picked by the last context switch on this thread.
+    static NEXT: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+fn cpu() -> MutexGuard<'static, Cpu> {
+    CPU.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+fn wait(guard: MutexGuard<'static, Cpu>) -> MutexGuard<'static, Cpu> {
+    CPU_EVENT.wait(guard).unwrap_or_else(|err| err.into_inner())
+}
+
+/// Executes `f` in a critical section. Pending interrupts and PendSV are serviced once the
+/// outermost critical section is left, which makes every Kernel call a preemption point.
+/// An unprivileged task can not mask interrupts on the hardware, hence it must reach the Kernel
+/// through a system call, which the simulation enforces.
+pub fn critical_section<F, R>(f: F) -> R
+where
+    F: FnOnce(&CriticalSection) -> R,
+{
+    assert!(
+        Host::is_privileged(),
+        "critical section entered by an unprivileged task"
+    );
+    let depth = DEPTH.with(|depth| depth.replace(depth.get() + 1));
+    let res = f(&CriticalSection { _0: () });
+    DEPTH.with(|d| d.set(depth));
+    if depth == 0 {
+        service();
+    }
+    res
+}
+
+/// Port simulating a single core CPU on the host.
+pub struct Host;
+
+impl Port for Host {
+    fn get_msb(val: u32) -> Option<usize> {
+        if let Some(hooks) = hooks() {
+            return (hooks.get_msb)(val);
+        }
+        if val == 0 {
+            return None;
+        }
+        Some(31 - val.leading_zeros() as usize)
+    }
+
+    /// Registers the task with the simulated CPU, its thread is spawned when it is first scheduled.
+    /// The stack itself is left untouched as the thread brings its own. Returns a number unique to
+    /// this task, which identifies it in the port in place of a stack pointer. A task created on
+    /// the stack of a deleted task hence gets a thread of its own.
+    fn init_stack(
+        stack: &mut [u32],
+        handler: fn(ContextType) -> !,
+        cxt: *const Context,
+        uses_fpu: bool,
+    ) -> usize {
+        if let Some(hooks) = hooks() {
+            return (hooks.init_stack)(stack, handler, cxt, uses_fpu);
+        }
+        let mut tasks = TASKS.lock().unwrap();
+        let stack_pointer = tasks.len() + 1;
+        tasks.push(HostTask {
+            stack_pointer,
+            handler,
+            cxt: cxt as usize,
+            spawned: false,
+        });
+        stack_pointer
+    }
+
+    /// The task's registers live on its own thread, hence there is nothing to save.
+    fn save_context(task_stack: &TaskControlBlock) {
+        if let Some(hooks) = hooks() {
+            (hooks.save_context)(task_stack);
+        }
+    }
+
+    /// Records the task to hand the CPU over to once the PendSV handler returns, along with
+    /// the privilege it runs with.
+    fn load_context(task_stack: &TaskControlBlock) {
+        if let Some(hooks) = hooks() {
+            return (hooks.load_context)(task_stack);
+        }
+        THREAD_PRIVILEGED.store(task_stack.privileged, Ordering::SeqCst);
+        NEXT.with(|next| next.set(Some(task_stack.stack_pointer)));
+    }
+
+    /// Pends the simulated PendSV. It runs as soon as the current thread leaves all critical sections.
+    fn set_pendsv() {
+        if let Some(hooks) = hooks() {
+            return (hooks.set_pendsv)();
+        }
+        PENDSV.store(true, Ordering::SeqCst);
+        if DEPTH.with(|depth| depth.get()) == 0 {
+            service();
+        }
+    }
+
+    /// Blocks the calling task until an interrupt is raised and services it. Inside a critical
+    /// section the interrupt only wakes the CPU, it is serviced once the critical section is left.
+    fn wait_for_interrupt() {
+        if let Some(hooks) = hooks() {
+            return (hooks.wait_for_interrupt)();
+        }
+        let mut cpu = cpu();
+        cpu.idle = true;
+        CPU_EVENT.notify_all();
+        while cpu.pending.is_empty() {
+            cpu = wait(cpu);
+        }
+        cpu.idle = false;
+        drop(cpu);
+        if DEPTH.with(|depth| depth.get()) == 0 {
+            service();
+        }
+    }
+
+    /// Handlers and threads which are not tasks always run privileged, tasks run with the
+    /// privilege they were created with.
+    fn is_privileged() -> bool {
+        if let Some(hooks) = hooks() {
+            return (hooks.is_privileged)();
+        }
+        IN_HANDLER.with(|in_handler| in_handler.get())
+            || CURRENT.with(|curr| curr.get()).is_none()
+            || THREAD_PRIVILEGED.load(Ordering::SeqCst)
+    }
+
+    /// Dispatches the system call in the simulated `SVCall` handler. A context switch it requests
+    /// is carried out once the handler returns, as PendSV tail-chains it on Cortex-M.
+    fn syscall(number: usize, args: [usize; 3]) -> usize {
+        if let Some(hooks) = hooks() {
+            return (hooks.syscall)(number, args);
+        }
+        let was_in_handler = IN_HANDLER.with(|in_handler| in_handler.replace(true));
+        let res = dispatch(number, args);
+        IN_HANDLER.with(|in_handler| in_handler.set(was_in_handler));
+        if DEPTH.with(|depth| depth.get()) == 0 {
+            service();
+        }
+        res
+    }
+
+    /// The simulated CPU has no cycle counter, hence run time is measured in ticks unless a
+    /// counter was injected through `set_cycle_counter`.
+    fn start_cycle_counter() {
+        if let Some(hooks) = hooks() {
+            (hooks.start_cycle_counter)();
+        }
+    }
+
+    fn get_cycles() -> u32 {
+        if let Some(hooks) = hooks() {
+            return (hooks.get_cycles)();
+        }
+        if let Some(counter) = *CYCLE_COUNTER.lock().unwrap() {
+            return counter();
+        }
+        #[cfg(feature = "timer")]
+        return get_time().ticks() as u32;
+        #[cfg(not(feature = "timer"))]
+        return 0;
+    }
+
+    /// Stretches the simulated SysTick over `ticks` ticks, from then on `tick` only counts them
+    /// until the last one.
+    #[cfg(feature = "timer")]
+    fn suppress_ticks(ticks: u32) -> u32 {
+        if let Some(hooks) = hooks() {
+            return (hooks.suppress_ticks)(ticks);
+        }
+        if ticks < 2 {
+            return 0;
+        }
+        PASSED_TICKS.store(0, Ordering::SeqCst);
+        SUPPRESSED_TICKS.store(ticks, Ordering::SeqCst);
+        ticks
+    }
+
+    /// Returns the ticks which passed while the SysTick was stretched. If it expired, its
+    /// interrupt is pending and accounts for the last tick.
+    #[cfg(feature = "timer")]
+    fn resume_ticks() -> u32 {
+        if let Some(hooks) = hooks() {
+            return (hooks.resume_ticks)();
+        }
+        let suppressed = SUPPRESSED_TICKS.swap(0, Ordering::SeqCst);
+        PASSED_TICKS.load(Ordering::SeqCst).min(suppressed - 1)
+    }
+
+    /// Simulated interrupts are raised through `irq`, which ignores the interrupt controller.
+    #[cfg(feature = "interrupts")]
+    fn enable_irq(irqn: u16) {
+        if let Some(hooks) = hooks() {
+            (hooks.enable_irq)(irqn);
+        }
+    }
+
+    #[cfg(feature = "interrupts")]
+    fn disable_irq(irqn: u16) {
+        if let Some(hooks) = hooks() {
+            (hooks.disable_irq)(irqn);
+        }
+    }
+}
+
+/// Raises an interrupt which executes `isr` on the simulated CPU. Waits for the Kernel to be
+/// started before raising it, then blocks until the interrupt has been serviced and the CPU
+/// is back in `wait_for_interrupt`, i.e. all the tasks it made ready have run.
+pub fn interrupt<F>(isr: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    let mut cpu = cpu();
+    while cpu.running.is_none() {
+        cpu = wait(cpu);
+    }
+    cpu.pending.push_back(Box::new(isr));
+    cpu.raised += 1;
+    let ticket = cpu.raised;
+    CPU_EVENT.notify_all();
+    while cpu.serviced < ticket || !cpu.idle {
+        cpu = wait(cpu);
+    }
+}
+
+/// Registers port `P` in place of the simulated CPU, the Kernel calls its operations from then on.
+/// Has to be called before the Kernel is initialized, later registrations are ignored. The
+/// registered port is in charge of context switches: its `set_pendsv` only records the request,
+/// which the test carries out through `run_pendsv` once it left the Kernel.
+pub fn register_port<P: Port>() {
+    let _ = HOOKS.set(PortHooks {
+        get_msb: P::get_msb,
+        init_stack: P::init_stack,
+        save_context: P::save_context,
+        load_context: P::load_context,
+        set_pendsv: P::set_pendsv,
+        wait_for_interrupt: P::wait_for_interrupt,
+        is_privileged: P::is_privileged,
+        syscall: P::syscall,
+        start_cycle_counter: P::start_cycle_counter,
+        get_cycles: P::get_cycles,
+        #[cfg(feature = "timer")]
+        suppress_ticks: P::suppress_ticks,
+        #[cfg(feature = "timer")]
+        resume_ticks: P::resume_ticks,
+        #[cfg(feature = "interrupts")]
+        enable_irq: P::enable_irq,
+        #[cfg(feature = "interrupts")]
+        disable_irq: P::disable_irq,
+    });
+}
+
+/// Executes the Kernel's part of the PendSV handler on the calling thread, i.e. picks the next
+/// task and loads its context through the registered port.
+pub fn run_pendsv() {
+    context_switch();
+}
+
+/// Executes the Kernel's part of the SysTick handler on the calling thread.
+#[cfg(feature = "timer")]
+pub fn run_systick() {
+    sys_tick();
+}
+
+/// Executes system call `number` with `args` like the `SVCall` handler, for registered ports
+/// which raise system calls.
+pub fn dispatch_syscall(number: usize, args: [usize; 3]) -> usize {
+    dispatch(number, args)
+}
+
+/// Injects `counter` as the cycle counter of the simulated CPU, which run time and interrupt
+/// hand-off latencies are then measured in.
+pub fn set_cycle_counter(counter: fn() -> u32) {
+    *CYCLE_COUNTER.lock().unwrap() = Some(counter);
+}
+
+/// Advances the simulated SysTick by one tick. The tick is dropped if `start_timer` has not
+/// been called by the time it is serviced. While the SysTick is stretched, only the last of the
+/// suppressed ticks raises the interrupt, the others are counted and return right away.
+#[cfg(feature = "timer")]
+pub fn tick() {
+    let suppressed = SUPPRESSED_TICKS.load(Ordering::SeqCst);
+    if suppressed != 0 && PASSED_TICKS.fetch_add(1, Ordering::SeqCst) + 1 < suppressed {
+        return;
+    }
+    interrupt(|| {
+        if SYSTICK_ENABLED.load(Ordering::SeqCst) {
+            sys_tick();
+        }
+    });
+}
+
+/// Raises interrupt `irqn`, which executes the action it is bound to like the default interrupt
+/// handler of the Cortex-M ports. An interrupt which is not bound is passed on to the handler set
+/// through `interrupts::set_default_handler`, without which it panics.
+#[cfg(feature = "interrupts")]
+pub fn irq(irqn: u16) {
+    interrupt(move || dispatch_irq(irqn as i16));
+}
+
+/// Services the pending interrupts followed by PendSV, in the same order as the NVIC would.
+/// Interrupts are only serviced by the thread of the running task.
+fn service() {
+    if IN_HANDLER.with(|in_handler| in_handler.get()) {
+        return;
+    }
+    let is_task = CURRENT.with(|curr| curr.get()).is_some();
+    if !is_task && cpu().running.is_some() {
+        // Foreign threads leave PendSV to the running task.
+        return;
+    }
+    IN_HANDLER.with(|in_handler| in_handler.set(true));
+    if is_task {
+        // The lock is released before the handler runs, as it may raise interrupts itself.
+        loop {
+            let Some(isr) = cpu().pending.pop_front() else {
+                break;
+            };
+            isr();
+            cpu().serviced += 1;
+            CPU_EVENT.notify_all();
+        }
+    }
+    if PENDSV.swap(false, Ordering::SeqCst) {
+        PendSV();
+    }
+    IN_HANDLER.with(|in_handler| in_handler.set(false));
+}
+
+/// ### PendSV handler
+/// Does the context switch through the Kernel, then hands the CPU over to the next task's thread.
+#[allow(non_snake_case)]
+fn PendSV() {
+    context_switch();
+    if let Some(stack_pointer) = NEXT.with(|next| next.take()) {
+        switch_to(stack_pointer);
+    }
+}
+
+/// Gives the CPU to the task identified by `stack_pointer` and blocks the calling thread until
+/// it is scheduled again. The thread which started the Kernel is never scheduled again.
+fn switch_to(stack_pointer: usize) {
+    let mut cpu = cpu();
+    cpu.running = Some(stack_pointer);
+    let mut tasks = TASKS.lock().unwrap();
+    let task = tasks
+        .iter_mut()
+        .find(|task| task.stack_pointer == stack_pointer)
+        .unwrap();
+    if !task.spawned {
+        task.spawned = true;
+        spawn(task.handler, task.cxt, stack_pointer);
+    }
+    drop(tasks);
+    CPU_EVENT.notify_all();
+    match CURRENT.with(|curr| curr.get()) {
+        Some(me) => {
+            while cpu.running != Some(me) {
+                cpu = wait(cpu);
+            }
+        }
+        None => {
+            drop(cpu);
+            loop {
+                thread::park();
+            }
+        }
+    }
+}
+
+fn spawn(handler: fn(ContextType) -> !, cxt: usize, stack_pointer: usize) {
+    thread::Builder::new()
+        .spawn(move || {
+            CURRENT.with(|curr| curr.set(Some(stack_pointer)));
+            let mut cpu = cpu();
+            while cpu.running != Some(stack_pointer) {
+                cpu = wait(cpu);
+            }
+            drop(cpu);
+            handler(unsafe { &*(cxt as *const Context) })
+        })
+        .unwrap();
+}
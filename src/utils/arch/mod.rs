@@ -0,0 +1,210 @@
+//! # Machine specific
+//!
+//! Declares the `Port` trait, the set of machine specific operations the Kernel is built on, and
+//! selects the port the Kernel is compiled for. The rest of the Kernel only goes through the
+//! functions re-exported here, hence supporting a new target amounts to implementing `Port`
+//! (along with the critical section primitives and the interrupt handlers) in a new module and
+//! selecting it below. The host port doubles as a mock port for testing the Kernel.
+
+use crate::system::scheduler::{BooleanVector, Context, ContextType, TaskControlBlock};
+
+#[cfg(all(not(feature = "std"), target_arch = "arm", not(armv6m)))]
+mod armv7m;
+#[cfg(all(not(feature = "std"), target_arch = "arm", not(armv6m)))]
+pub use self::armv7m::{critical_section, CriticalSection, Mutex};
+#[cfg(all(not(feature = "std"), target_arch = "arm", not(armv6m), feature = "timer"))]
+pub use self::armv7m::{Peripherals, SystClkSource};
+#[cfg(all(not(feature = "std"), target_arch = "arm", not(armv6m)))]
+/// The port the Kernel is compiled for.
+pub type Target = self::armv7m::CortexM;
+#[cfg(all(not(feature = "std"), target_arch = "arm", not(armv6m), feature = "mpu"))]
+pub use self::armv7m::{enable_mpu, set_mpu_region};
+
+#[cfg(all(not(feature = "std"), armv6m))]
+mod armv6m;
+#[cfg(all(not(feature = "std"), armv6m))]
+pub use self::armv6m::{critical_section, CriticalSection, Mutex};
+#[cfg(all(not(feature = "std"), armv6m, feature = "timer"))]
+pub use self::armv6m::{Peripherals, SystClkSource};
+#[cfg(all(not(feature = "std"), armv6m))]
+/// The port the Kernel is compiled for.
+pub type Target = self::armv6m::CortexM0;
+
+#[cfg(all(not(feature = "std"), target_arch = "riscv32"))]
+mod riscv32;
+#[cfg(all(not(feature = "std"), target_arch = "riscv32"))]
+pub use self::riscv32::{critical_section, CriticalSection, Mutex};
+#[cfg(all(not(feature = "std"), target_arch = "riscv32", feature = "timer"))]
+pub use self::riscv32::{Peripherals, SystClkSource};
+#[cfg(all(not(feature = "std"), target_arch = "riscv32"))]
+/// The port the Kernel is compiled for.
+pub type Target = self::riscv32::Riscv32;
+
+#[cfg(feature = "std")]
+mod host;
+#[cfg(feature = "std")]
+pub use self::host::{critical_section, interrupt, set_cycle_counter, CriticalSection, Mutex, Peripherals};
+#[cfg(feature = "std")]
+pub use self::host::{dispatch_syscall, register_port, run_pendsv};
+#[cfg(all(feature = "std", feature = "timer"))]
+pub use self::host::{run_systick, tick, SystClkSource};
+#[cfg(all(feature = "std", feature = "interrupts"))]
+pub use self::host::irq;
+#[cfg(feature = "std")]
+/// The port the Kernel is compiled for.
+pub type Target = self::host::Host;
+
+/// Machine specific operations required by the Kernel.
+pub trait Port {
+    /// Returns the position of the most significant set bit of `val`, `None` if `val` is zero.
+    fn get_msb(val: u32) -> Option<usize>;
+
+    /// Prepares `stack` so that the task starts executing `handler` with `cxt` as its argument
+    /// when it is first loaded. `uses_fpu` is set for tasks which use the floating point unit,
+    /// ports without one ignore it. Returns the stack pointer to be stored in the task's TCB.
+    fn init_stack(
+        stack: &mut [u32],
+        handler: fn(ContextType) -> !,
+        cxt: *const Context,
+        uses_fpu: bool,
+    ) -> usize;
+
+    /// Saves the context of the running task onto its stack and updates its TCB.
+    fn save_context(task_stack: &TaskControlBlock);
+
+    /// Restores the context of the task described by the TCB.
+    fn load_context(task_stack: &TaskControlBlock);
+
+    /// Requests a context switch, it is carried out once no other interrupt is active.
+    fn set_pendsv();
+
+    /// Puts the CPU to sleep until the next interrupt.
+    fn wait_for_interrupt();
+
+    /// Returns true if Currently the Kernel is operating in Privileged mode.
+    fn is_privileged() -> bool;
+
+    /// Raises system call `number` with `args` from an unprivileged task and returns its result.
+    /// Ports which run all tasks privileged dispatch it right away.
+    fn syscall(number: usize, args: [usize; 3]) -> usize;
+
+    /// Starts the counter `get_cycles` reads, ports without a cycle counter do nothing.
+    fn start_cycle_counter();
+
+    /// Returns the free running count the run time of tasks is measured in. Ports with a cycle
+    /// counter return the CPU cycles, the others return the tick count of the Kernel timer.
+    fn get_cycles() -> u32;
+
+    /// Programs the timer to interrupt once after up to `ticks` ticks instead of every tick, so
+    /// that the CPU can sleep through the ticks in between. Returns the number of ticks
+    /// programmed, which is bounded by the range of the timer, or zero if the tick can not be
+    /// suppressed right now. Called with interrupts disabled.
+    #[cfg(feature = "timer")]
+    fn suppress_ticks(ticks: u32) -> u32;
+
+    /// Restores the periodic tick after `suppress_ticks`. Returns the number of ticks which have
+    /// passed in the meantime, leaving out the tick a pending timer interrupt accounts for.
+    /// Called with interrupts disabled.
+    #[cfg(feature = "timer")]
+    fn resume_ticks() -> u32;
+
+    /// Enables interrupt `irqn` in the interrupt controller. Ports whose external interrupts are
+    /// not dispatched by the Kernel do nothing.
+    #[cfg(feature = "interrupts")]
+    fn enable_irq(irqn: u16);
+
+    /// Disables interrupt `irqn` in the interrupt controller.
+    #[cfg(feature = "interrupts")]
+    fn disable_irq(irqn: u16);
+}
+
+/// Returns the position of the most significant set bit of the BooleanVector `val`.
+/// A 64 bit vector is looked up in two steps, its upper word first.
+#[cfg(not(feature = "tasks_64"))]
+#[inline(always)]
+pub fn get_msb(val: BooleanVector) -> Option<usize> {
+    Target::get_msb(val)
+}
+
+#[cfg(feature = "tasks_64")]
+#[inline(always)]
+pub fn get_msb(val: BooleanVector) -> Option<usize> {
+    match Target::get_msb((val >> 32) as u32) {
+        Some(msb) => Some(msb + 32),
+        None => Target::get_msb(val as u32),
+    }
+}
+
+#[inline(always)]
+pub fn init_stack(
+    stack: &mut [u32],
+    handler: fn(ContextType) -> !,
+    cxt: *const Context,
+    uses_fpu: bool,
+) -> usize {
+    Target::init_stack(stack, handler, cxt, uses_fpu)
+}
+
+#[inline(always)]
+pub fn save_context(task_stack: &TaskControlBlock) {
+    Target::save_context(task_stack)
+}
+
+#[inline(always)]
+pub fn load_context(task_stack: &TaskControlBlock) {
+    Target::load_context(task_stack)
+}
+
+#[inline(always)]
+pub fn set_pendsv() {
+    Target::set_pendsv()
+}
+
+#[inline(always)]
+pub fn wait_for_interrupt() {
+    Target::wait_for_interrupt()
+}
+
+#[inline(always)]
+pub fn is_privileged() -> bool {
+    Target::is_privileged()
+}
+
+#[inline(always)]
+pub fn syscall(number: usize, args: [usize; 3]) -> usize {
+    Target::syscall(number, args)
+}
+
+#[inline(always)]
+pub fn start_cycle_counter() {
+    Target::start_cycle_counter()
+}
+
+#[inline(always)]
+pub fn get_cycles() -> u32 {
+    Target::get_cycles()
+}
+
+#[cfg(feature = "timer")]
+#[inline(always)]
+pub fn suppress_ticks(ticks: u32) -> u32 {
+    Target::suppress_ticks(ticks)
+}
+
+#[cfg(feature = "timer")]
+#[inline(always)]
+pub fn resume_ticks() -> u32 {
+    Target::resume_ticks()
+}
+
+#[cfg(feature = "interrupts")]
+#[inline(always)]
+pub fn enable_irq(irqn: u16) {
+    Target::enable_irq(irqn)
+}
+
+#[cfg(feature = "interrupts")]
+#[inline(always)]
+pub fn disable_irq(irqn: u16) {
+    Target::disable_irq(irqn)
+}
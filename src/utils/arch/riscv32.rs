@@ -0,0 +1,380 @@
+//! # RISC-V (rv32imac) port
+//!
+//! The Kernel runs entirely in Machine mode. The role of SysTick is taken over by the machine
+//! timer and the role of PendSV by the machine software interrupt (MSIP), both found in the
+//! CLINT. As RISC-V has no hardware stacking, every trap saves the full register file onto the
+//! stack of the interrupted task and restores it from the stack the Kernel points it to, hence
+//! a context switch only amounts to swapping the saved stack pointer. The trap handler itself
+//! runs on a dedicated trap stack, so the task stacks only need to hold one register frame.
+//!
+//! The CLINT addresses are the ones of the QEMU `virt` machine (and the SiFive cores).
+//! Without the `clz` instruction, the MSB is computed with a lookup table.
+
+// Platform specific Exports
+pub use riscv::interrupt::free as critical_section;
+pub use riscv::interrupt::{CriticalSection, Mutex};
+
+use core::ptr::{read_volatile, write_volatile};
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use riscv::register::mcause::{self, Interrupt, Trap};
+use riscv::register::mie;
+
+use crate::kernel::syscall::dispatch;
+use crate::kernel::tasks::context_switch;
+use crate::system::scheduler::{Context, ContextType, TaskControlBlock};
+use crate::utils::arch::Port;
+use crate::utils::helpers::get_msb_table;
+
+#[cfg(feature = "timer")]
+use crate::kernel::timer::{get_time, sys_tick};
+
+/// Machine software interrupt pending register of hart 0.
+const CLINT_MSIP: usize = 0x0200_0000;
+/// Machine timer compare register of hart 0.
+const CLINT_MTIMECMP: usize = 0x0200_4000;
+/// Machine timer register.
+const CLINT_MTIME: usize = 0x0200_BFF8;
+
+/// Number of words in the register frame saved on each trap.
+const FRAME_SIZE: usize = 32;
+/// Position of `mepc` in the register frame, register `xN` is stored at position `N`.
+const FRAME_MEPC: usize = 0;
+const FRAME_A0: usize = 10;
+
+/// Stack pointer of the register frame of the interrupted task, replaced by `load_context`
+/// with the one of the task to switch to.
+static mut TRAP_SP: usize = 0;
+
+/// Number of machine timer ticks between two Kernel ticks.
+static TICK_INTERVAL: AtomicU32 = AtomicU32::new(0);
+/// Number of Kernel ticks the last `suppress_ticks` programmed the timer for.
+#[cfg(feature = "timer")]
+static SUPPRESSED_TICKS: AtomicU32 = AtomicU32::new(0);
+static PERIPHERALS_TAKEN: AtomicBool = AtomicBool::new(false);
+
+// Trap entry, overrides the one of `riscv-rt`. Saves `mepc` and all registers but `sp`, `gp`
+// and `tp` (which are the same for all tasks) onto the stack of the interrupted task, then calls
+// `harsark_trap` on the trap stack with the frame's address. The frame returned by it is restored.
+//
+// `_setup_interrupts` is overridden as well, so that the Kernel starts with the software
+// interrupt enabled, as PendSV is on Cortex-M.
+global_asm!(
+    "
+    .section .trap, \"ax\"
+    .global _start_trap
+    .align 2
+_start_trap:
+    addi sp, sp, -32*4
+    sw x1, 1*4(sp)
+    sw x5, 5*4(sp)
+    sw x6, 6*4(sp)
+    sw x7, 7*4(sp)
+    sw x8, 8*4(sp)
+    sw x9, 9*4(sp)
+    sw x10, 10*4(sp)
+    sw x11, 11*4(sp)
+    sw x12, 12*4(sp)
+    sw x13, 13*4(sp)
+    sw x14, 14*4(sp)
+    sw x15, 15*4(sp)
+    sw x16, 16*4(sp)
+    sw x17, 17*4(sp)
+    sw x18, 18*4(sp)
+    sw x19, 19*4(sp)
+    sw x20, 20*4(sp)
+    sw x21, 21*4(sp)
+    sw x22, 22*4(sp)
+    sw x23, 23*4(sp)
+    sw x24, 24*4(sp)
+    sw x25, 25*4(sp)
+    sw x26, 26*4(sp)
+    sw x27, 27*4(sp)
+    sw x28, 28*4(sp)
+    sw x29, 29*4(sp)
+    sw x30, 30*4(sp)
+    sw x31, 31*4(sp)
+    csrr t0, mepc
+    sw t0, 0(sp)
+
+    mv a0, sp
+    la sp, harsark_trap_stack_top
+    call harsark_trap
+    mv sp, a0
+
+    lw t0, 0(sp)
+    csrw mepc, t0
+    lw x1, 1*4(sp)
+    lw x5, 5*4(sp)
+    lw x6, 6*4(sp)
+    lw x7, 7*4(sp)
+    lw x8, 8*4(sp)
+    lw x9, 9*4(sp)
+    lw x10, 10*4(sp)
+    lw x11, 11*4(sp)
+    lw x12, 12*4(sp)
+    lw x13, 13*4(sp)
+    lw x14, 14*4(sp)
+    lw x15, 15*4(sp)
+    lw x16, 16*4(sp)
+    lw x17, 17*4(sp)
+    lw x18, 18*4(sp)
+    lw x19, 19*4(sp)
+    lw x20, 20*4(sp)
+    lw x21, 21*4(sp)
+    lw x22, 22*4(sp)
+    lw x23, 23*4(sp)
+    lw x24, 24*4(sp)
+    lw x25, 25*4(sp)
+    lw x26, 26*4(sp)
+    lw x27, 27*4(sp)
+    lw x28, 28*4(sp)
+    lw x29, 29*4(sp)
+    lw x30, 30*4(sp)
+    lw x31, 31*4(sp)
+    addi sp, sp, 32*4
+    mret
+
+    .section .text
+    .global _setup_interrupts
+_setup_interrupts:
+    la t0, _start_trap
+    csrw mtvec, t0
+    li t0, 0x8
+    csrs mie, t0
+    csrs mstatus, t0
+    ret
+
+    .section .bss
+    .align 4
+harsark_trap_stack:
+    .space 2048
+harsark_trap_stack_top:
+    "
+);
+
+/// Clock source of the machine timer, kept for API compatibility with the Cortex-M port.
+pub enum SystClkSource {
+    Core,
+    External,
+}
+
+/// The machine timer of the CLINT, driven through the interface of the Cortex-M SysTick.
+pub struct SYST {
+    _0: (),
+}
+
+impl SYST {
+    /// The machine timer has a fixed clock source.
+    pub fn set_clock_source(&mut self, _clk_source: SystClkSource) {}
+
+    /// Sets the number of machine timer ticks between two Kernel ticks.
+    pub fn set_reload(&mut self, reload_value: u32) {
+        TICK_INTERVAL.store(reload_value, Ordering::SeqCst);
+    }
+
+    /// Arms the machine timer for the first Kernel tick.
+    pub fn enable_counter(&mut self) {
+        set_mtimecmp(get_mtime() + TICK_INTERVAL.load(Ordering::SeqCst) as u64);
+    }
+
+    pub fn enable_interrupt(&mut self) {
+        unsafe { mie::set_mtimer() };
+    }
+}
+
+/// Core peripherals used by the Kernel.
+#[allow(non_snake_case)]
+pub struct Peripherals {
+    pub SYST: SYST,
+}
+
+impl Peripherals {
+    /// Returns the peripherals once, mirroring `cortex_m::Peripherals::take`.
+    pub fn take() -> Option<Self> {
+        if PERIPHERALS_TAKEN.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        Some(Peripherals { SYST: SYST { _0: () } })
+    }
+}
+
+fn get_mtime() -> u64 {
+    loop {
+        let hi = unsafe { read_volatile((CLINT_MTIME + 4) as *const u32) };
+        let lo = unsafe { read_volatile(CLINT_MTIME as *const u32) };
+        if hi == unsafe { read_volatile((CLINT_MTIME + 4) as *const u32) } {
+            return ((hi as u64) << 32) | lo as u64;
+        }
+    }
+}
+
+fn get_mtimecmp() -> u64 {
+    let hi = unsafe { read_volatile((CLINT_MTIMECMP + 4) as *const u32) };
+    let lo = unsafe { read_volatile(CLINT_MTIMECMP as *const u32) };
+    ((hi as u64) << 32) | lo as u64
+}
+
+/// Writes `mtimecmp` without it ever holding a value below both the old and the new one.
+fn set_mtimecmp(val: u64) {
+    unsafe {
+        write_volatile((CLINT_MTIMECMP + 4) as *mut u32, u32::MAX);
+        write_volatile(CLINT_MTIMECMP as *mut u32, val as u32);
+        write_volatile((CLINT_MTIMECMP + 4) as *mut u32, (val >> 32) as u32);
+    }
+}
+
+/// Port for RV32IMAC microcontrollers.
+pub struct Riscv32;
+
+impl Port for Riscv32 {
+    /// Returns the MSB of `val` through a lookup table.
+    fn get_msb(val: u32) -> Option<usize> {
+        get_msb_table(val)
+    }
+
+    /// Writes the initial register frame of a task onto `stack` so that the first `load_context`
+    /// followed by `mret` starts executing `handler` with `cxt` in `a0`.
+    /// Returns the stack pointer to be stored in the task's TCB.
+    fn init_stack(
+        stack: &mut [u32],
+        handler: fn(ContextType) -> !,
+        cxt: *const Context,
+        _uses_fpu: bool,
+    ) -> usize {
+        // The stack pointer has to stay 16 byte aligned.
+        let end = (stack.as_ptr() as usize + stack.len() * 4) & !0xF;
+        let pos = (end - stack.as_ptr() as usize) / 4 - FRAME_SIZE;
+        let frame = &mut stack[pos..pos + FRAME_SIZE];
+
+        for reg in frame.iter_mut() {
+            *reg = 0;
+        }
+        frame[FRAME_MEPC] = handler as usize as u32;
+        frame[FRAME_A0] = cxt as u32;
+
+        frame.as_ptr() as usize
+    }
+
+    /// Stores the stack pointer of the interrupted task's register frame in its TCB.
+    #[inline(always)]
+    fn save_context(task_stack: &TaskControlBlock) {
+        unsafe {
+            asm!(
+                "sw {0}, 0({1})",
+                in(reg) TRAP_SP,
+                in(reg) task_stack,
+            )
+        };
+    }
+
+    /// Makes the trap handler return into the register frame of the task.
+    #[inline(always)]
+    fn load_context(task_stack: &TaskControlBlock) {
+        unsafe {
+            TRAP_SP = task_stack.stack_pointer;
+        }
+    }
+
+    fn set_pendsv() {
+        unsafe { write_volatile(CLINT_MSIP as *mut u32, 1) };
+    }
+
+    fn wait_for_interrupt() {
+        unsafe { riscv::asm::wfi() };
+    }
+
+    /// All tasks run in Machine mode.
+    fn is_privileged() -> bool {
+        true
+    }
+
+    /// All tasks run privileged, hence the system call is dispatched right away.
+    fn syscall(number: usize, args: [usize; 3]) -> usize {
+        dispatch(number, args)
+    }
+
+    /// `mcycle` is not implemented by all cores, hence run time is measured in ticks.
+    fn start_cycle_counter() {}
+
+    fn get_cycles() -> u32 {
+        #[cfg(feature = "timer")]
+        return get_time().ticks() as u32;
+        #[cfg(not(feature = "timer"))]
+        return 0;
+    }
+
+    /// Moves `mtimecmp` out by the ticks to be suppressed. The compare value of the next tick
+    /// must not have been reached yet, else the timer interrupt is already pending.
+    #[cfg(feature = "timer")]
+    fn suppress_ticks(ticks: u32) -> u32 {
+        let interval = TICK_INTERVAL.load(Ordering::SeqCst);
+        let next_tick = get_mtimecmp();
+        if interval == 0 || get_mtime() >= next_tick {
+            return 0;
+        }
+        set_mtimecmp(next_tick + (ticks - 1) as u64 * interval as u64);
+        SUPPRESSED_TICKS.store(ticks, Ordering::SeqCst);
+        ticks
+    }
+
+    /// Counts the whole ticks since the last tick before the sleep and re-arms the timer for the
+    /// next one. If the timer expired, its pending interrupt re-arms it and accounts for the last tick.
+    #[cfg(feature = "timer")]
+    fn resume_ticks() -> u32 {
+        let interval = TICK_INTERVAL.load(Ordering::SeqCst) as u64;
+        let ticks = SUPPRESSED_TICKS.load(Ordering::SeqCst);
+        let compare = get_mtimecmp();
+        let now = get_mtime();
+        if now >= compare {
+            return ticks - 1;
+        }
+        let last_tick = compare - ticks as u64 * interval;
+        let passed = (now - last_tick) / interval;
+        set_mtimecmp(last_tick + (passed + 1) * interval);
+        passed as u32
+    }
+
+    /// External interrupts are left to the application, hence there is nothing to enable.
+    #[cfg(feature = "interrupts")]
+    fn enable_irq(_irqn: u16) {}
+
+    #[cfg(feature = "interrupts")]
+    fn disable_irq(_irqn: u16) {}
+}
+
+/// Entry of all traps, called by `_start_trap` with the register frame of the interrupted task.
+/// Returns the register frame to be restored.
+#[no_mangle]
+extern "C" fn harsark_trap(frame: usize) -> usize {
+    unsafe { TRAP_SP = frame };
+    match mcause::read().cause() {
+        Trap::Interrupt(Interrupt::MachineTimer) => MachineTimer(),
+        Trap::Interrupt(Interrupt::MachineSoft) => MachineSoft(),
+        _ => {
+            extern "C" {
+                fn DefaultHandler();
+            }
+            unsafe { DefaultHandler() }
+        }
+    }
+    unsafe { TRAP_SP }
+}
+
+/// ### Machine timer interrupt handler
+/// Takes the place of the SysTick handler. Re-arms the timer relative to the previous compare
+/// value, so that the tick does not drift with the interrupt latency.
+#[allow(non_snake_case)]
+fn MachineTimer() {
+    set_mtimecmp(get_mtimecmp() + TICK_INTERVAL.load(Ordering::SeqCst) as u64);
+    #[cfg(feature = "timer")]
+    sys_tick();
+}
+
+/// ### Machine software interrupt handler
+/// Takes the place of the PendSV handler and does the actual context switch in the Kernel.
+#[allow(non_snake_case)]
+fn MachineSoft() {
+    unsafe { write_volatile(CLINT_MSIP as *mut u32, 0) };
+    context_switch();
+}
@@ -10,6 +10,9 @@ pub enum KernelError {
     AccessDenied,
     Empty,
     Exists,
+    InvalidRegion,
+    InvalidArgument,
+    TimedOut,
 }
 
 impl fmt::Debug for KernelError {
@@ -21,6 +24,9 @@ impl fmt::Debug for KernelError {
             KernelError::AccessDenied => write!(f, "AccessDenied"),
             KernelError::Empty => write!(f, "Empty"),
             KernelError::Exists => write!(f, "Exists"),
+            KernelError::InvalidRegion => write!(f, "InvalidRegion"),
+            KernelError::InvalidArgument => write!(f, "InvalidArgument"),
+            KernelError::TimedOut => write!(f, "TimedOut"),
         }
     }
 }
@@ -1,10 +1,12 @@
+use crate::system::scheduler::BooleanVector;
+
 /// A helper struct to generate Boolean vector corresponding to an array of TaskIds at compile time.
 pub struct TaskMask<const N: usize> {}
 
 impl<const N: usize> TaskMask<N> {
     /// Takes an array of TaskIds and returns a BooleanVector corresponding to it.
-    pub const fn generate(tasks: [u32; N]) -> u32{
-        let mut task_mask: u32 = 0;
+    pub const fn generate(tasks: [u32; N]) -> BooleanVector{
+        let mut task_mask: BooleanVector = 0;
         let mut i = 0;
         while i < N {
             task_mask |= 1<<tasks[i];
@@ -14,15 +16,50 @@ impl<const N: usize> TaskMask<N> {
     }
 }
 
-
-pub const fn get_msb_const(val: u32) -> usize {
+#[cfg(any(armv6m, target_arch = "riscv32"))]
+const fn get_msb_const(val: BooleanVector) -> usize {
     let mut res = 0;
     let mut i = 0;
-    while i < 32 {
+    while i < core::mem::size_of::<BooleanVector>() * 8 {
         if val & (1<<i) > 0 {
             res = i;
         }
         i += 1;
     }
     return res;
-}
\ No newline at end of file
+}
+
+/// MSB of every byte value, the entry for zero is never looked up.
+#[cfg(any(armv6m, target_arch = "riscv32"))]
+static MSB_TABLE: [u8; 256] = msb_table();
+
+#[cfg(any(armv6m, target_arch = "riscv32"))]
+const fn msb_table() -> [u8; 256] {
+    let mut table = [0; 256];
+    let mut i = 1;
+    while i < 256 {
+        table[i] = get_msb_const(i as BooleanVector) as u8;
+        i += 1;
+    }
+    table
+}
+
+/// Returns the MSB of `val` on targets without a count leading zeros instruction. Narrows `val`
+/// down to its highest non-zero byte and looks up the MSB of that byte in `MSB_TABLE`.
+#[cfg(any(armv6m, target_arch = "riscv32"))]
+pub fn get_msb_table(val: u32) -> Option<usize> {
+    if val == 0 {
+        return None;
+    }
+    let mut val = val;
+    let mut res = 0;
+    if val & 0xFFFF0000 != 0 {
+        val >>= 16;
+        res += 16;
+    }
+    if val & 0xFF00 != 0 {
+        val >>= 8;
+        res += 8;
+    }
+    return Some(res + MSB_TABLE[val as usize] as usize);
+}
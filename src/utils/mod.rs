@@ -4,5 +4,5 @@ pub mod arch;
 pub mod errors;
 pub mod helpers;
 
-#[cfg(feature = "alloc")]
+#[cfg(all(feature = "alloc", not(feature = "std"), target_arch = "arm"))]
 pub mod heap;
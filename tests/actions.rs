@@ -0,0 +1,89 @@
+//! Each action an event can execute, on the host simulation port.
+#![cfg(all(
+    feature = "std",
+    any(feature = "events_16", feature = "events_32", feature = "events_64"),
+    not(feature = "task_monitor")
+))]
+
+use std::sync::Mutex;
+
+use harsark::events::{self, EventAction};
+use harsark::primitives::{Message, Semaphore};
+use harsark::sim;
+use harsark::tasks::*;
+use harsark::timer::{get_time, Duration};
+
+static LOG: Mutex<Vec<(&'static str, u64)>> = Mutex::new(Vec::new());
+/// Signalled for task 2.
+static SEM: Semaphore = Semaphore::new(1 << 2);
+/// Broadcast to task 3.
+static MSG: Message<u64> = Message::new(1 << 3, 1 << 3, 0);
+static VALUE: u64 = 42;
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+static mut STACK3: [u32; 64] = [0; 64];
+
+#[test]
+fn event_actions() {
+    std::thread::spawn(|| {
+        init(|_| {
+            let every = Duration::from_ticks(5);
+            let at = Duration::from_ticks;
+            events::new(true, every, EventAction::Release(1 << 1))?;
+            events::new_with_offset(true, every, at(1), EventAction::Signal(&SEM, 1 << 2))?;
+            events::new_with_offset(true, every, at(2), EventAction::broadcast(&MSG, &VALUE))?;
+            events::new_with_offset(true, every, at(3), EventAction::Handler(|| log("handler")))?;
+            events::new_with_offset(true, every, at(4), EventAction::Log)?;
+            Ok(())
+        })
+        .unwrap();
+        create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| {
+            loop {
+                log("1 released");
+                task_exit();
+            }
+        })
+        .unwrap();
+        create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |cxt| {
+            loop {
+                if SEM.test_and_reset(cxt).unwrap() {
+                    log("2 signalled");
+                }
+                task_exit();
+            }
+        })
+        .unwrap();
+        create_task(3, unsafe { &mut *core::ptr::addr_of_mut!(STACK3) }, |cxt| {
+            loop {
+                if let Some(value) = MSG.receive(cxt) {
+                    LOG.lock().unwrap().push(("3 received", value));
+                }
+                task_exit();
+            }
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    for _ in 0..6 {
+        sim::tick();
+    }
+    // The released tasks run on the tick the event is dispatched, the log action does nothing
+    // without the system logger.
+    assert_eq!(
+        *LOG.lock().unwrap(),
+        vec![
+            ("1 released", 1),
+            ("2 signalled", 2),
+            ("3 received", 42),
+            ("handler", 3),
+            ("1 released", 6),
+        ]
+    );
+}
+
+fn log(name: &'static str) {
+    LOG.lock().unwrap().push((name, get_time().ticks()));
+}
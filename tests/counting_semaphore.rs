@@ -0,0 +1,97 @@
+//! Acquiring counting semaphores with timeouts and priority ordered hand-off on the host
+//! simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::Mutex;
+
+use harsark::primitives::CountingSemaphore;
+use harsark::sim;
+use harsark::tasks::*;
+use harsark::timer::{get_time, Duration};
+use harsark::KernelError;
+
+static LOG: Mutex<Vec<(&'static str, u64)>> = Mutex::new(Vec::new());
+static SEM: CountingSemaphore = CountingSemaphore::new(0, 2);
+/// Never registered, hence unprivileged tasks can not use it.
+static OTHER: CountingSemaphore = CountingSemaphore::new(1, 1);
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+static mut STACK3: [u32; 64] = [0; 64];
+
+fn log(name: &'static str) {
+    LOG.lock().unwrap().push((name, get_time().ticks()));
+}
+
+#[test]
+fn counting_semaphore() {
+    std::thread::spawn(|| {
+        init(|_| SEM.register()).unwrap();
+        create_unprivileged_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| {
+            loop {
+                assert!(matches!(OTHER.release(), Err(KernelError::NotFound)));
+                assert!(!OTHER.try_acquire());
+                assert_eq!(OTHER.count(), 0);
+                log("1 waiting");
+                SEM.acquire().unwrap();
+                log("1");
+                task_exit();
+            }
+        })
+        .unwrap();
+        create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |_| {
+            loop {
+                // Task 2 starts waiting after task 1.
+                sleep(Duration::from_ticks(1));
+                log("2 waiting");
+                SEM.acquire().unwrap();
+                log("2");
+                task_exit();
+            }
+        })
+        .unwrap();
+        create_task(3, unsafe { &mut *core::ptr::addr_of_mut!(STACK3) }, |_| {
+            loop {
+                let res = SEM.acquire_timeout(Duration::from_ticks(3));
+                assert!(matches!(res, Err(KernelError::TimedOut)));
+                log("3 timed out");
+                task_exit();
+            }
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    sim::interrupt(|| release(0b1110));
+    for _ in 0..4 {
+        sim::tick();
+    }
+    assert_eq!(
+        *LOG.lock().unwrap(),
+        vec![("1 waiting", 1), ("2 waiting", 2), ("3 timed out", 4)]
+    );
+
+    // Each token is handed to the highest priority task waiting, rather than the first one.
+    sim::interrupt(|| SEM.release().unwrap());
+    assert_eq!(LOG.lock().unwrap()[3..], [("2", 4)]);
+    sim::interrupt(|| SEM.release().unwrap());
+    assert_eq!(LOG.lock().unwrap()[4..], [("1", 4)]);
+    assert_eq!(SEM.count(), 0);
+
+    // Without waiters the tokens are kept, up to the most the semaphore holds.
+    sim::interrupt(|| {
+        SEM.release().unwrap();
+        SEM.release().unwrap();
+        assert!(matches!(SEM.release(), Err(KernelError::LimitExceeded)));
+    });
+    assert_eq!(SEM.count(), 2);
+
+    // The idle task takes the tokens which are left, but it can not block for another one.
+    sim::interrupt(|| {
+        assert!(SEM.try_acquire());
+        SEM.acquire().unwrap();
+        assert!(matches!(SEM.acquire(), Err(KernelError::AccessDenied)));
+    });
+    assert_eq!(SEM.count(), 0);
+}
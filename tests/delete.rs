@@ -0,0 +1,57 @@
+//! Deletion of a waiting task on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::Mutex;
+
+use harsark::primitives::CountingSemaphore;
+use harsark::sim;
+use harsark::tasks::*;
+
+static LOG: Mutex<Vec<(&'static str, u32)>> = Mutex::new(Vec::new());
+static SEM: CountingSemaphore = CountingSemaphore::new(0, 1);
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+static mut STACK3: [u32; 64] = [0; 64];
+
+#[test]
+fn delete_waiting_task() {
+    std::thread::spawn(|| {
+        init(|_| Ok(())).unwrap();
+        create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| loop {
+            delete_task(2).unwrap();
+            // The new task takes TaskId 2, but it does not wait on the semaphore.
+            let tid =
+                create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK3) }, exit).unwrap();
+            LOG.lock().unwrap().push(("created", tid as u32));
+            SEM.release().unwrap();
+            LOG.lock().unwrap().push(("count", SEM.count()));
+            task_exit();
+        })
+        .unwrap();
+        create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |_| loop {
+            LOG.lock().unwrap().push(("acquiring", 0));
+            SEM.acquire().unwrap();
+            LOG.lock().unwrap().push(("acquired", 0));
+            task_exit();
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    sim::interrupt(|| release(1 << 2));
+    sim::tick();
+    assert_eq!(*LOG.lock().unwrap(), vec![("acquiring", 0)]);
+
+    // The token is kept by the semaphore rather than handed to the deleted task's TaskId.
+    sim::interrupt(|| release(1 << 1));
+    sim::tick();
+    assert_eq!(LOG.lock().unwrap()[1..], [("created", 2), ("count", 1)]);
+}
+
+fn exit(_: ContextType) -> ! {
+    loop {
+        task_exit();
+    }
+}
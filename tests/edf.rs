@@ -0,0 +1,65 @@
+//! Earliest deadline first scheduling on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", feature = "task_monitor"))]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+
+use harsark::sim;
+use harsark::tasks::*;
+use harsark::timer::Duration;
+
+static LOG: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+static MISSES: AtomicU32 = AtomicU32::new(0);
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+static mut STACK3: [u32; 64] = [0; 64];
+
+#[test]
+fn earliest_deadline_first() {
+    std::thread::spawn(|| {
+        init(SchedulingPolicy::EarliestDeadlineFirst, |_| {
+            set_deadline_exceed_handler(|| {
+                MISSES.fetch_add(1, Ordering::SeqCst);
+            });
+            Ok(())
+        })
+        .unwrap();
+        // The lowest priority task has the earliest deadline, which it misses while sleeping.
+        let stack1 = unsafe { &mut *core::ptr::addr_of_mut!(STACK1) };
+        create_task(1, Duration::from_ticks(2), stack1, |_| loop {
+            LOG.lock().unwrap().push("1");
+            sleep(Duration::from_ticks(3));
+            // The missed deadline still precedes the one task 3 is released with.
+            release(1 << 3);
+            LOG.lock().unwrap().push("1 end");
+            task_exit();
+        })
+        .unwrap();
+        let stack2 = unsafe { &mut *core::ptr::addr_of_mut!(STACK2) };
+        create_task(2, Duration::from_ticks(20), stack2, |_| loop {
+            LOG.lock().unwrap().push("2");
+            task_exit();
+        })
+        .unwrap();
+        let stack3 = unsafe { &mut *core::ptr::addr_of_mut!(STACK3) };
+        create_task(3, Duration::from_ticks(10), stack3, |_| loop {
+            LOG.lock().unwrap().push("3");
+            task_exit();
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    sim::interrupt(|| release((1 << 1) | (1 << 2)));
+    sim::tick();
+    assert_eq!(*LOG.lock().unwrap(), vec!["1", "2"]);
+
+    for _ in 0..3 {
+        sim::tick();
+    }
+    assert_eq!(*LOG.lock().unwrap(), vec!["1", "2", "1 end", "3"]);
+    // The miss is reported once, although the deadline is kept until the task exits.
+    assert_eq!(MISSES.load(Ordering::SeqCst), 1);
+}
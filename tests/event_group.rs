@@ -0,0 +1,84 @@
+//! Waiting on event groups on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::Mutex;
+
+use harsark::primitives::{EventGroup, WaitMode};
+use harsark::sim;
+use harsark::tasks::*;
+use harsark::timer::{get_time, Duration};
+use harsark::KernelError;
+
+static LOG: Mutex<Vec<(&'static str, u64)>> = Mutex::new(Vec::new());
+static GROUP: EventGroup = EventGroup::new();
+/// Never registered, hence unprivileged tasks can not use it.
+static OTHER: EventGroup = EventGroup::new();
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+static mut STACK3: [u32; 64] = [0; 64];
+
+#[test]
+fn event_group() {
+    std::thread::spawn(|| {
+        init(|_| GROUP.register()).unwrap();
+        create_unprivileged_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| {
+            loop {
+                let flags = GROUP.wait(0b011, WaitMode::All, true).unwrap();
+                LOG.lock().unwrap().push(("1", flags as u64));
+                task_exit();
+            }
+        })
+        .unwrap();
+        create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |_| {
+            loop {
+                let flags = GROUP.wait(0b100, WaitMode::Any, false).unwrap();
+                LOG.lock().unwrap().push(("2", flags as u64));
+                let res = GROUP.wait_timeout(0b1000, WaitMode::Any, false, Duration::from_ticks(2));
+                assert!(matches!(res, Err(KernelError::TimedOut)));
+                LOG.lock()
+                    .unwrap()
+                    .push(("2 timed out", get_time().ticks()));
+                task_exit();
+            }
+        })
+        .unwrap();
+        create_unprivileged_task(3, unsafe { &mut *core::ptr::addr_of_mut!(STACK3) }, |_| {
+            loop {
+                let res = GROUP.wait(0, WaitMode::Any, false);
+                assert!(matches!(res, Err(KernelError::InvalidArgument)));
+                let res = OTHER.wait(0b1, WaitMode::Any, false);
+                assert!(matches!(res, Err(KernelError::NotFound)));
+                LOG.lock().unwrap().push(("3", GROUP.get() as u64));
+                task_exit();
+            }
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    sim::interrupt(|| release(0b1110));
+    sim::tick();
+    assert_eq!(*LOG.lock().unwrap(), vec![("3", 0)]);
+
+    // Task 1 waits for all of its flags.
+    sim::interrupt(|| GROUP.set(0b001));
+    assert_eq!(LOG.lock().unwrap().len(), 1);
+
+    // A single set satisfies both tasks, the flags of task 1 are cleared once both were checked.
+    sim::interrupt(|| GROUP.set(0b110));
+    assert_eq!(LOG.lock().unwrap()[1..], [("2", 0b111), ("1", 0b111)]);
+    assert_eq!(GROUP.get(), 0b100);
+
+    sim::tick();
+    sim::tick();
+    assert_eq!(LOG.lock().unwrap()[3..], [("2 timed out", 3)]);
+
+    // The idle task gets the flags which are set, but it can not block for the others.
+    sim::interrupt(|| {
+        assert!(matches!(GROUP.wait(0b100, WaitMode::Any, false), Ok(0b100)));
+        let res = GROUP.wait(0b1000, WaitMode::Any, false);
+        assert!(matches!(res, Err(KernelError::AccessDenied)));
+    });
+}
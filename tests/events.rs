@@ -0,0 +1,85 @@
+//! Event offsets, deletion and reuse of their slots on the host simulation port.
+#![cfg(all(
+    feature = "std",
+    any(feature = "events_16", feature = "events_32", feature = "events_64"),
+    not(feature = "task_monitor")
+))]
+
+use std::sync::Mutex;
+
+use harsark::events::{self, EventAction};
+use harsark::sim;
+use harsark::tasks::*;
+use harsark::timer::Duration;
+use harsark::KernelError;
+
+static LOG: Mutex<Vec<(&'static str, u64)>> = Mutex::new(Vec::new());
+static mut STACK1: [u32; 64] = [0; 64];
+
+#[test]
+fn offset_and_delete() {
+    std::thread::spawn(|| {
+        init(|_| {
+            let every = Duration::from_ticks(3);
+            events::new(true, every, EventAction::Handler(|| log("a")))?;
+            events::new_with_offset(
+                true,
+                every,
+                Duration::from_ticks(1),
+                EventAction::Handler(|| log("b")),
+            )?;
+            events::new_with_offset(
+                true,
+                every,
+                Duration::from_ticks(5),
+                EventAction::Handler(|| log("c")),
+            )?;
+            Ok(())
+        })
+        .unwrap();
+        create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| {
+            loop {
+                task_exit();
+            }
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    for _ in 0..7 {
+        sim::tick();
+    }
+    // Events of the same period keep their offsets, the last one not dispatched before it.
+    assert_eq!(
+        *LOG.lock().unwrap(),
+        vec![("a", 0), ("b", 1), ("a", 3), ("b", 4), ("c", 5), ("a", 6)]
+    );
+
+    sim::interrupt(|| {
+        events::delete(1).unwrap();
+        assert!(matches!(events::delete(1), Err(KernelError::NotFound)));
+        assert!(matches!(events::enable(1), Err(KernelError::NotFound)));
+        // The free slot is handed out again before the unused ones.
+        let every = Duration::from_ticks(3);
+        let id = events::new(false, every, EventAction::Handler(|| log("d"))).unwrap();
+        assert_eq!(id, 1);
+    });
+    for _ in 0..3 {
+        sim::tick();
+    }
+    assert_eq!(LOG.lock().unwrap()[6..], [("c", 8), ("a", 9)]);
+
+    sim::interrupt(|| events::enable(1).unwrap());
+    for _ in 0..3 {
+        sim::tick();
+    }
+    assert_eq!(LOG.lock().unwrap()[8..], [("c", 11), ("a", 12), ("d", 12)]);
+}
+
+fn log(name: &'static str) {
+    LOG.lock()
+        .unwrap()
+        .push((name, harsark::timer::get_time().ticks()));
+}
@@ -0,0 +1,48 @@
+//! The idle task running while a resource is locked on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::Mutex;
+
+use harsark::primitives::Resource;
+use harsark::sim;
+use harsark::tasks::*;
+use harsark::timer::{get_time, Duration};
+
+static LOG: Mutex<Vec<(&'static str, u64)>> = Mutex::new(Vec::new());
+/// Only accessible to task 1, hence its ceiling is the priority of task 1.
+static RES: Resource<u32> = Resource::new(0, 1 << 1);
+static mut STACK1: [u32; 64] = [0; 64];
+
+fn log(event: &'static str) {
+    LOG.lock().unwrap().push((event, get_time().ticks()));
+}
+
+#[test]
+fn idle_while_locked() {
+    std::thread::spawn(|| {
+        init(|_| Ok(())).unwrap();
+        create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |cxt| loop {
+            RES.acquire(cxt, |_| {
+                log("1 locked");
+                // The idle task is not blocked by the lock, so it runs while the holder sleeps.
+                sleep(Duration::from_ticks(2));
+                log("1 woke");
+            })
+            .unwrap();
+            task_exit();
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    sim::interrupt(|| release(1 << 1));
+    sim::tick();
+    assert_eq!(*LOG.lock().unwrap(), vec![("1 locked", 1)]);
+
+    for _ in 0..2 {
+        sim::tick();
+    }
+    assert_eq!(*LOG.lock().unwrap(), vec![("1 locked", 1), ("1 woke", 3)]);
+}
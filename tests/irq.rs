@@ -0,0 +1,103 @@
+//! Dispatch of bound interrupts and their hand-off latency on the host simulation port.
+#![cfg(all(
+    feature = "std",
+    feature = "timer",
+    feature = "interrupts",
+    not(feature = "task_monitor")
+))]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+
+use harsark::interrupts::{self, EventAction};
+use harsark::primitives::{OverflowPolicy, Queue};
+use harsark::sim;
+use harsark::tasks::*;
+
+/// The cycle counter injected into the port, each read advances it by `STEP` cycles. Hence the
+/// hand-off latency is `STEP` when the released task is switched to by the interrupt itself.
+static CYCLES: AtomicU32 = AtomicU32::new(0);
+static STEP: AtomicU32 = AtomicU32::new(10);
+static LOG: Mutex<Vec<(&'static str, u32)>> = Mutex::new(Vec::new());
+/// Received from by task 2.
+static QUEUE: Queue<u32, 2> = Queue::new(OverflowPolicy::Reject);
+static VALUE: u32 = 42;
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+
+#[test]
+fn bound_interrupts() {
+    sim::set_cycle_counter(|| CYCLES.fetch_add(STEP.load(Ordering::SeqCst), Ordering::SeqCst));
+    std::thread::spawn(|| {
+        init(|_| {
+            interrupts::bind(3, EventAction::Release(1 << 1))?;
+            interrupts::bind(4, EventAction::post(&QUEUE, &VALUE, 1 << 2))?;
+            interrupts::bind(5, EventAction::Log)?;
+            interrupts::set_default_handler(|irqn| {
+                LOG.lock().unwrap().push(("default", irqn as u32))
+            });
+            Ok(())
+        })
+        .unwrap();
+        create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| {
+            loop {
+                LOG.lock().unwrap().push(("1", 0));
+                task_exit();
+            }
+        })
+        .unwrap();
+        create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |_| {
+            loop {
+                let value = QUEUE.receive().unwrap();
+                LOG.lock().unwrap().push(("2 received", value));
+            }
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    // Task 2 waits on the queue.
+    sim::interrupt(|| release(1 << 2));
+    sim::tick();
+
+    sim::irq(3);
+    sim::irq(4);
+    sim::irq(5);
+    assert_eq!(*LOG.lock().unwrap(), vec![("1", 0), ("2 received", 42)]);
+    let stats = interrupts::stats(3).unwrap();
+    assert_eq!(
+        (stats.count, stats.last_latency, stats.max_latency),
+        (1, 10, 10)
+    );
+    let stats = interrupts::stats(4).unwrap();
+    assert_eq!(
+        (stats.count, stats.last_latency, stats.max_latency),
+        (1, 10, 10)
+    );
+    // The log action releases no task, hence it has no latency.
+    let stats = interrupts::stats(5).unwrap();
+    assert_eq!(
+        (stats.count, stats.last_latency, stats.max_latency),
+        (1, 0, 0)
+    );
+
+    // The latest and the longest latency are kept apart.
+    STEP.store(25, Ordering::SeqCst);
+    sim::irq(3);
+    STEP.store(10, Ordering::SeqCst);
+    sim::irq(3);
+    let stats = interrupts::stats(3).unwrap();
+    assert_eq!(
+        (stats.count, stats.last_latency, stats.max_latency),
+        (3, 10, 25)
+    );
+
+    // Interrupts which are not bound, also once unbound, are passed on to the default handler.
+    sim::irq(6);
+    sim::interrupt(|| interrupts::unbind(5).unwrap());
+    sim::irq(5);
+    assert_eq!(LOG.lock().unwrap()[4..], [("default", 6), ("default", 5)]);
+    assert!(interrupts::stats(5).is_err());
+}
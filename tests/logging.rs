@@ -0,0 +1,38 @@
+//! System logger entries on the host simulation port.
+#![cfg(all(feature = "std", feature = "system_logger", not(feature = "task_monitor")))]
+
+use std::sync::Mutex;
+
+use harsark::sim;
+use harsark::tasks::*;
+
+static LOG: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static mut STACK1: [u32; 64] = [0; 64];
+
+#[test]
+fn logging() {
+    std::thread::spawn(|| {
+        init(|_| Ok(())).unwrap();
+        create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| loop {
+            task_exit();
+        })
+        .unwrap();
+        harsark::logging::set_release(true);
+        harsark::logging::set_task_exit(true);
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    sim::interrupt(|| release(1 << 1));
+    sim::tick();
+    harsark::logging::process(|entry| {
+        LOG.lock()
+            .unwrap()
+            .push(format!("{:?} at {}", entry.event_type, entry.timestamp.ticks()))
+    });
+    assert_eq!(
+        *LOG.lock().unwrap(),
+        vec!["Tasks Released: 2 at 0", "TaskExit at 1"]
+    );
+}
@@ -0,0 +1,62 @@
+//! Hand-off through semaphores and messages on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::Mutex;
+
+use harsark::primitives::{Message, Semaphore};
+use harsark::sim;
+use harsark::tasks::*;
+
+static LOG: Mutex<Vec<(&'static str, u32)>> = Mutex::new(Vec::new());
+/// Releases task 2 and notifies it.
+static SEM: Semaphore = Semaphore::new(1 << 2);
+/// Releases task 3, which receives it.
+static MSG: Message<u32> = Message::new(1 << 3, 1 << 3, 0);
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+static mut STACK3: [u32; 64] = [0; 64];
+
+#[test]
+fn semaphore_and_message() {
+    std::thread::spawn(|| {
+        init(|_| Ok(())).unwrap();
+        create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| loop {
+            SEM.signal_and_release(1 << 2);
+            LOG.lock().unwrap().push(("1 signalled", 0));
+            task_exit();
+        })
+        .unwrap();
+        create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |cxt| loop {
+            if SEM.test_and_reset(cxt).unwrap() {
+                LOG.lock().unwrap().push(("2 notified", 0));
+            }
+            // The flag is reset by the test above.
+            assert!(!SEM.test_and_reset(cxt).unwrap());
+            MSG.broadcast(42);
+            task_exit();
+        })
+        .unwrap();
+        create_task(3, unsafe { &mut *core::ptr::addr_of_mut!(STACK3) }, |cxt| loop {
+            if let Some(msg) = MSG.receive(cxt) {
+                LOG.lock().unwrap().push(("3 received", msg));
+            }
+            assert!(MSG.receive(cxt).is_none());
+            task_exit();
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    sim::interrupt(|| release(1 << 1));
+    sim::tick();
+    assert_eq!(
+        *LOG.lock().unwrap(),
+        vec![("2 notified", 0), ("3 received", 42), ("1 signalled", 0)]
+    );
+
+    // A broadcast from an interrupt handler is received just the same.
+    sim::interrupt(|| MSG.broadcast(7));
+    assert_eq!(LOG.lock().unwrap()[3..], [("3 received", 7)]);
+}
@@ -0,0 +1,115 @@
+//! Scheduling driven through a mock port registered in place of the simulated CPU.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+
+use harsark::sim::{self, Port, TaskControlBlock};
+use harsark::tasks::*;
+use harsark::timer::Duration;
+
+/// Stack pointer of the task whose context was loaded last.
+static LOADED: AtomicUsize = AtomicUsize::new(0);
+static PENDSV: AtomicBool = AtomicBool::new(false);
+static CYCLES: AtomicU32 = AtomicU32::new(0);
+static NEXT_STACK_POINTER: AtomicUsize = AtomicUsize::new(1);
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+
+struct MockPort;
+
+impl Port for MockPort {
+    fn get_msb(val: u32) -> Option<usize> {
+        val.checked_ilog2().map(|msb| msb as usize)
+    }
+
+    /// Hands out a number per task in place of a stack pointer.
+    fn init_stack(_: &mut [u32], _: fn(ContextType) -> !, _: *const Context, _: bool) -> usize {
+        NEXT_STACK_POINTER.fetch_add(1, Ordering::SeqCst)
+    }
+
+    fn save_context(_: &TaskControlBlock) {}
+
+    fn load_context(task_stack: &TaskControlBlock) {
+        LOADED.store(task_stack.stack_pointer(), Ordering::SeqCst);
+    }
+
+    fn set_pendsv() {
+        PENDSV.store(true, Ordering::SeqCst);
+    }
+
+    fn wait_for_interrupt() {}
+
+    fn is_privileged() -> bool {
+        true
+    }
+
+    fn syscall(number: usize, args: [usize; 3]) -> usize {
+        sim::dispatch_syscall(number, args)
+    }
+
+    fn start_cycle_counter() {}
+
+    fn get_cycles() -> u32 {
+        CYCLES.load(Ordering::SeqCst)
+    }
+
+    fn suppress_ticks(_: u32) -> u32 {
+        0
+    }
+
+    fn resume_ticks() -> u32 {
+        0
+    }
+
+    #[cfg(feature = "interrupts")]
+    fn enable_irq(_: u16) {}
+
+    #[cfg(feature = "interrupts")]
+    fn disable_irq(_: u16) {}
+}
+
+/// Carries out the pended context switch, returns the stack pointer of the task loaded.
+fn switch() -> usize {
+    PENDSV.store(false, Ordering::SeqCst);
+    sim::run_pendsv();
+    LOADED.load(Ordering::SeqCst)
+}
+
+fn task(_: ContextType) -> ! {
+    unreachable!("the mock port never runs tasks");
+}
+
+#[test]
+fn mock_port() {
+    sim::register_port::<MockPort>();
+    init(|_| Ok(())).unwrap();
+    create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, task).unwrap();
+    create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, task).unwrap();
+    // The idle task was created first, then tasks 1 and 2.
+    let (idle, task1, task2) = (1, 2, 3);
+    assert_eq!(switch(), idle);
+
+    release((1 << 1) | (1 << 2));
+    assert_eq!(switch(), task2);
+    assert_eq!(get_curr_tid(), 2);
+
+    // The test acts as the running task from here on.
+    CYCLES.store(100, Ordering::SeqCst);
+    task_exit();
+    assert!(PENDSV.load(Ordering::SeqCst));
+    assert_eq!(switch(), task1);
+    assert_eq!(stats(2).unwrap().run_time, 100);
+
+    suspend(1).unwrap();
+    assert_eq!(switch(), idle);
+    resume(1).unwrap();
+    assert_eq!(switch(), task1);
+
+    sleep(Duration::from_ticks(2));
+    assert_eq!(switch(), idle);
+    sim::run_systick();
+    assert_eq!(switch(), idle);
+    sim::run_systick();
+    assert!(PENDSV.load(Ordering::SeqCst));
+    assert_eq!(switch(), task1);
+}
@@ -0,0 +1,59 @@
+//! Periodic tasks on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::Mutex;
+
+use harsark::sim;
+use harsark::tasks::*;
+use harsark::timer::{get_time, Duration};
+use harsark::KernelError;
+
+static LOG: Mutex<Vec<(&'static str, u64)>> = Mutex::new(Vec::new());
+static OVERRUNS: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+
+fn log(event: &'static str) {
+    LOG.lock().unwrap().push((event, get_time().ticks()));
+}
+
+#[test]
+fn periodic_release() {
+    std::thread::spawn(|| {
+        init(|_| Ok(())).unwrap();
+        let stack1 = unsafe { &mut *core::ptr::addr_of_mut!(STACK1) };
+        let period = Duration::from_ticks(3);
+        let offset = Duration::from_ticks(1);
+        create_unprivileged_periodic_task(1, period, offset, Duration::ZERO, stack1, |_| loop {
+            // Task 1 runs unprivileged, hence it can not create tasks.
+            let res = create_task(3, &mut [], |_| loop {});
+            assert!(matches!(res, Err(KernelError::AccessDenied)));
+            log("1");
+            task_exit();
+        })
+        .unwrap();
+        // Task 2 runs for longer than its period, so every other release finds it still active.
+        let stack2 = unsafe { &mut *core::ptr::addr_of_mut!(STACK2) };
+        let period = Duration::from_ticks(2);
+        create_periodic_task(2, period, Duration::from_ticks(1), Duration::ZERO, stack2, |_| loop {
+            log("2");
+            sleep(Duration::from_ticks(3));
+            task_exit();
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    for _ in 0..7 {
+        sim::tick();
+    }
+    // The releases of task 1 keep to its period, those of task 2 at 3 and 7 were overruns.
+    assert_eq!(
+        *LOG.lock().unwrap(),
+        vec![("2", 1), ("1", 1), ("1", 4), ("2", 5), ("1", 7)]
+    );
+    sim::interrupt(|| OVERRUNS.lock().unwrap().extend([get_overruns(1), get_overruns(2)]));
+    assert_eq!(*OVERRUNS.lock().unwrap(), vec![0, 2]);
+}
@@ -0,0 +1,54 @@
+//! Injected cycle counter and unprivileged tasks on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+
+use harsark::sim;
+use harsark::tasks::*;
+use harsark::KernelError;
+
+static LOG: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+static CYCLES: AtomicU32 = AtomicU32::new(0);
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+static mut STACK3: [u32; 64] = [0; 64];
+
+fn cycles() -> u32 {
+    CYCLES.load(Ordering::SeqCst)
+}
+
+#[test]
+fn port() {
+    std::thread::spawn(|| {
+        init(|_| Ok(())).unwrap();
+        create_unprivileged_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| loop {
+            // Privileged routines are denied, the others are executed through system calls.
+            let res = create_task(3, unsafe { &mut *core::ptr::addr_of_mut!(STACK3) }, |_| loop {
+                task_exit();
+            });
+            assert!(matches!(res, Err(KernelError::AccessDenied)));
+            release(1 << 2);
+            LOG.lock().unwrap().push("unprivileged");
+            task_exit();
+        })
+        .unwrap();
+        create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |_| loop {
+            CYCLES.fetch_add(100, Ordering::SeqCst);
+            LOG.lock().unwrap().push("privileged");
+            task_exit();
+        })
+        .unwrap();
+        sim::set_cycle_counter(cycles);
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    sim::interrupt(|| release(1 << 1));
+    sim::tick();
+    assert_eq!(*LOG.lock().unwrap(), vec!["unprivileged", "privileged"]);
+    // The run time of task 2 is measured by the injected counter.
+    assert_eq!(stats(2).unwrap().run_time, 100);
+    assert_eq!(stats(1).unwrap().run_time, 0);
+}
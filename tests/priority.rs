@@ -0,0 +1,64 @@
+//! Runtime priority changes on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::Mutex;
+
+use harsark::primitives::Resource;
+use harsark::sim;
+use harsark::tasks::*;
+use harsark::KernelError;
+
+static LOG: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+static RES: Resource<u32> = Resource::new(0, (1 << 1) | (1 << 2));
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+static mut STACK3: [u32; 64] = [0; 64];
+
+#[test]
+fn priority_changes() {
+    std::thread::spawn(|| {
+        init(|_| Ok(())).unwrap();
+        create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |cxt| loop {
+            RES.acquire(cxt, |_| {
+                // The tasks blocked by the lock were picked by the current priorities.
+                let res = set_priority(3, 4);
+                assert!(matches!(res, Err(KernelError::AccessDenied)));
+                LOG.lock().unwrap().push("1 rejected");
+            })
+            .unwrap();
+            // Raised above both other tasks, neither of them preempts this task.
+            set_priority(1, 5).unwrap();
+            release((1 << 2) | (1 << 3));
+            LOG.lock().unwrap().push("1 end");
+            task_exit();
+        })
+        .unwrap();
+        create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |_| loop {
+            LOG.lock().unwrap().push("2");
+            task_exit();
+        })
+        .unwrap();
+        create_task(3, unsafe { &mut *core::ptr::addr_of_mut!(STACK3) }, |_| loop {
+            LOG.lock().unwrap().push("3");
+            task_exit();
+        })
+        .unwrap();
+        // Priority zero is reserved for the idle task.
+        assert!(matches!(set_priority(2, 0), Err(KernelError::InvalidArgument)));
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    sim::interrupt(|| release(1 << 1));
+    sim::tick();
+    assert_eq!(*LOG.lock().unwrap(), vec!["1 rejected", "1 end", "3", "2"]);
+
+    // Task 2 now takes precedence over task 3.
+    sim::interrupt(|| {
+        set_priority(2, 4).unwrap();
+        release((1 << 2) | (1 << 3));
+    });
+    sim::tick();
+    assert_eq!(LOG.lock().unwrap()[4..], ["2", "3"]);
+}
@@ -0,0 +1,118 @@
+//! Sending and receiving through message queues on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::{Arc, Mutex};
+
+use harsark::primitives::{OverflowPolicy, Queue};
+use harsark::sim;
+use harsark::tasks::*;
+use harsark::timer::{get_time, Duration};
+use harsark::KernelError;
+
+static LOG: Mutex<Vec<(&'static str, u64)>> = Mutex::new(Vec::new());
+static QUEUE: Queue<u32, 2> = Queue::new(OverflowPolicy::Reject);
+static RING: Queue<u32, 2> = Queue::new(OverflowPolicy::OverwriteOldest);
+/// Never registered, hence unprivileged tasks can not use it.
+static OTHER: Queue<u32, 2> = Queue::new(OverflowPolicy::Reject);
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+static mut STACK3: [u32; 64] = [0; 64];
+
+fn log(name: &'static str, value: u64) {
+    LOG.lock().unwrap().push((name, value));
+}
+
+#[test]
+fn queue() {
+    std::thread::spawn(|| {
+        init(|_| QUEUE.register()).unwrap();
+        create_unprivileged_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| {
+            loop {
+                let res = QUEUE.send_timeout(3, Duration::from_ticks(1));
+                assert!(matches!(res, Err(KernelError::TimedOut)));
+                log("1 timed out", get_time().ticks());
+                QUEUE.send(4).unwrap();
+                log("1 sent", 4);
+                task_exit();
+            }
+        })
+        .unwrap();
+        create_unprivileged_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |_| {
+            loop {
+                assert!(matches!(OTHER.try_send(1), Err(KernelError::NotFound)));
+                assert!(matches!(OTHER.receive(), Err(KernelError::NotFound)));
+                assert_eq!(OTHER.len(), 0);
+                let msg = QUEUE.receive().unwrap();
+                log("2", msg as u64);
+                task_exit();
+            }
+        })
+        .unwrap();
+        create_task(3, unsafe { &mut *core::ptr::addr_of_mut!(STACK3) }, |_| {
+            loop {
+                let res = QUEUE.receive_timeout(Duration::from_ticks(2));
+                assert!(matches!(res, Err(KernelError::TimedOut)));
+                log("3 timed out", get_time().ticks());
+                let msg = QUEUE.receive().unwrap();
+                log("3", msg as u64);
+                task_exit();
+            }
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    sim::interrupt(|| release(0b1100));
+    for _ in 0..3 {
+        sim::tick();
+    }
+    assert_eq!(*LOG.lock().unwrap(), vec![("3 timed out", 3)]);
+
+    // Each message is handed to the highest priority task waiting for one.
+    sim::interrupt(|| QUEUE.try_send(10).unwrap());
+    sim::interrupt(|| QUEUE.try_send(20).unwrap());
+    assert_eq!(LOG.lock().unwrap()[1..], [("3", 10), ("2", 20)]);
+
+    // A full queue rejects messages or overwrites the oldest one, as given by its policy.
+    sim::interrupt(|| {
+        QUEUE.try_send(1).unwrap();
+        QUEUE.try_send(2).unwrap();
+        assert!(matches!(QUEUE.try_send(3), Err(KernelError::LimitExceeded)));
+        for msg in 1..=3 {
+            RING.try_send(msg).unwrap();
+        }
+        assert!(matches!(RING.try_receive(), Ok(2)));
+        assert!(matches!(RING.try_receive(), Ok(3)));
+        assert!(matches!(RING.try_receive(), Err(KernelError::Empty)));
+    });
+    assert_eq!(QUEUE.len(), 2);
+
+    // The sender blocks while the queue is full, and sends once a message was received.
+    sim::interrupt(|| release(1 << 1));
+    sim::tick();
+    sim::tick();
+    assert_eq!(LOG.lock().unwrap()[3..], [("1 timed out", 5)]);
+    sim::interrupt(|| assert!(matches!(QUEUE.try_receive(), Ok(1))));
+    assert_eq!(LOG.lock().unwrap()[4..], [("1 sent", 4)]);
+
+    // The idle task receives the messages held, but it can not block for another one.
+    sim::interrupt(|| {
+        assert!(matches!(QUEUE.receive(), Ok(2)));
+        assert!(matches!(QUEUE.receive(), Ok(4)));
+        assert!(matches!(QUEUE.receive(), Err(KernelError::AccessDenied)));
+    });
+
+    // The messages still held by a queue are dropped along with it.
+    let msg = Arc::new(());
+    let sent = msg.clone();
+    sim::interrupt(move || {
+        let queue: &'static Queue<Arc<()>, 2> =
+            Box::leak(Box::new(Queue::new(OverflowPolicy::Reject)));
+        queue.try_send(sent.clone()).unwrap();
+        queue.try_send(sent).unwrap();
+        drop(unsafe { Box::from_raw(queue as *const Queue<Arc<()>, 2> as *mut Queue<Arc<()>, 2>) });
+    });
+    assert_eq!(Arc::strong_count(&msg), 1);
+}
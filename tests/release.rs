@@ -0,0 +1,51 @@
+//! Release and preemption of tasks on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::Mutex;
+
+use harsark::primitives::Semaphore;
+use harsark::sim;
+use harsark::tasks::*;
+
+static LOG: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+/// Releases task 2, signalling it reschedules right away.
+static SEM: Semaphore = Semaphore::new(1 << 2);
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+
+#[test]
+fn release_and_preempt() {
+    std::thread::spawn(|| {
+        init(|_| Ok(())).unwrap();
+        create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| loop {
+            LOG.lock().unwrap().push("low start");
+            // Task 2 preempts this task as soon as it is released.
+            SEM.signal_and_release(0);
+            LOG.lock().unwrap().push("low end");
+            task_exit();
+        })
+        .unwrap();
+        create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |_| loop {
+            LOG.lock().unwrap().push("high");
+            task_exit();
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    // Tasks released by an interrupt are dispatched on the next tick.
+    sim::interrupt(|| release(1 << 1));
+    assert!(LOG.lock().unwrap().is_empty());
+    sim::tick();
+    assert_eq!(*LOG.lock().unwrap(), vec!["low start", "high", "low end"]);
+
+    // Released together, the higher priority task runs first.
+    sim::interrupt(|| release((1 << 1) | (1 << 2)));
+    sim::tick();
+    assert_eq!(
+        LOG.lock().unwrap()[3..],
+        ["high", "low start", "high", "low end"]
+    );
+}
@@ -0,0 +1,57 @@
+//! Priority ceilings of resources on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::Mutex;
+
+use harsark::primitives::{Resource, Semaphore};
+use harsark::sim;
+use harsark::tasks::*;
+
+static LOG: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+/// Shared by tasks 1 and 3, hence its ceiling is the priority of task 3.
+static RES: Resource<u32> = Resource::new(0, (1 << 1) | (1 << 3));
+/// Releases tasks 2 and 3, signalling it reschedules right away.
+static SEM: Semaphore = Semaphore::new((1 << 2) | (1 << 3));
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+static mut STACK3: [u32; 64] = [0; 64];
+
+#[test]
+fn resource_ceiling() {
+    std::thread::spawn(|| {
+        init(|_| Ok(())).unwrap();
+        create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |cxt| loop {
+            RES.acquire(cxt, |_| {
+                LOG.lock().unwrap().push("1 locked");
+                // Both tasks are up to the ceiling, hence neither preempts the lock holder.
+                SEM.signal_and_release(0);
+                LOG.lock().unwrap().push("1 unlocking");
+            })
+            .unwrap();
+            LOG.lock().unwrap().push("1 end");
+            task_exit();
+        })
+        .unwrap();
+        create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |_| loop {
+            LOG.lock().unwrap().push("2");
+            task_exit();
+        })
+        .unwrap();
+        create_task(3, unsafe { &mut *core::ptr::addr_of_mut!(STACK3) }, |cxt| loop {
+            RES.acquire(cxt, |_| LOG.lock().unwrap().push("3 locked"))
+                .unwrap();
+            task_exit();
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    sim::interrupt(|| release(1 << 1));
+    sim::tick();
+    assert_eq!(
+        *LOG.lock().unwrap(),
+        vec!["1 locked", "1 unlocking", "3 locked", "2", "1 end"]
+    );
+}
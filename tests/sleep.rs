@@ -0,0 +1,67 @@
+//! Sleeping tasks on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::Mutex;
+
+use harsark::sim;
+use harsark::tasks::*;
+use harsark::timer::{get_time, Duration};
+
+static LOG: Mutex<Vec<(&'static str, u64)>> = Mutex::new(Vec::new());
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+static mut STACK3: [u32; 64] = [0; 64];
+
+fn log(event: &'static str) {
+    LOG.lock().unwrap().push((event, get_time().ticks()));
+}
+
+#[test]
+fn sleep_and_wake_up() {
+    std::thread::spawn(|| {
+        init(|_| Ok(())).unwrap();
+        create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| loop {
+            log("1 sleeps");
+            // The time has already been reached, so the task does not sleep.
+            sleep_until(get_time());
+            sleep(Duration::from_ticks(2));
+            log("1 woke");
+            task_exit();
+        })
+        .unwrap();
+        create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |_| loop {
+            log("2 sleeps");
+            sleep(Duration::from_ticks(1));
+            log("2 woke");
+            task_exit();
+        })
+        .unwrap();
+        create_task(3, unsafe { &mut *core::ptr::addr_of_mut!(STACK3) }, |_| loop {
+            log("3 sleeps");
+            sleep_until(get_time() + Duration::from_ticks(3));
+            log("3 woke");
+            task_exit();
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    sim::interrupt(|| release((1 << 1) | (1 << 2) | (1 << 3)));
+    sim::tick();
+    // Each task sleeps in turn, letting the lower priority tasks run.
+    assert_eq!(
+        *LOG.lock().unwrap(),
+        vec![("3 sleeps", 1), ("2 sleeps", 1), ("1 sleeps", 1)]
+    );
+
+    // The tasks wake up in the order of their wake-up times, whatever their priorities.
+    for _ in 0..3 {
+        sim::tick();
+    }
+    assert_eq!(
+        LOG.lock().unwrap()[3..],
+        [("2 woke", 2), ("1 woke", 3), ("3 woke", 4)]
+    );
+}
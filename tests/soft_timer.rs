@@ -0,0 +1,65 @@
+//! Deletion of software timers and reuse of their slots on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::Mutex;
+
+use harsark::sim;
+use harsark::soft_timer::{self, TimerMode};
+use harsark::tasks::*;
+use harsark::timer::Duration;
+use harsark::KernelError;
+
+static LOG: Mutex<Vec<(&'static str, u64)>> = Mutex::new(Vec::new());
+static mut STACK1: [u32; 64] = [0; 64];
+
+#[test]
+fn delete_and_reuse() {
+    std::thread::spawn(|| {
+        init(|_| {
+            soft_timer::create(TimerMode::AutoReload, Duration::from_ticks(2), || log("a"))?;
+            soft_timer::create(TimerMode::AutoReload, Duration::from_ticks(3), || log("b"))?;
+            soft_timer::start(0)?;
+            soft_timer::start(1)
+        })
+        .unwrap();
+        create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| loop {
+            task_exit();
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    for _ in 0..3 {
+        sim::tick();
+    }
+    assert_eq!(*LOG.lock().unwrap(), vec![("a", 2), ("b", 3)]);
+
+    // The running timer 0 is taken out of the list, timer 1 keeps its expiry.
+    sim::interrupt(|| soft_timer::delete(0).unwrap());
+    sim::interrupt(|| {
+        assert!(matches!(soft_timer::delete(0), Err(KernelError::NotFound)));
+        assert!(matches!(soft_timer::start(0), Err(KernelError::NotFound)));
+    });
+    for _ in 0..3 {
+        sim::tick();
+    }
+    assert_eq!(LOG.lock().unwrap()[2..], [("b", 6)]);
+
+    // The free slot is handed out again before the unused ones.
+    sim::interrupt(|| {
+        let timer =
+            soft_timer::create(TimerMode::OneShot, Duration::from_ticks(1), || log("c")).unwrap();
+        assert_eq!(timer, 0);
+        soft_timer::start(timer).unwrap();
+    });
+    sim::tick();
+    sim::tick();
+    assert_eq!(LOG.lock().unwrap()[3..], [("c", 7)]);
+    sim::interrupt(|| assert!(matches!(soft_timer::is_active(0), Ok(false))));
+}
+
+fn log(name: &'static str) {
+    LOG.lock().unwrap().push((name, harsark::timer::get_time().ticks()));
+}
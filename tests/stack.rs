@@ -0,0 +1,62 @@
+//! Stack high-water marks and overflow detection on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::Mutex;
+
+use harsark::sim;
+use harsark::tasks::*;
+use harsark::KernelError;
+
+static LOG: Mutex<Vec<(&'static str, u32)>> = Mutex::new(Vec::new());
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+
+#[test]
+fn stack_usage() {
+    std::thread::spawn(|| {
+        init(|_| Ok(())).unwrap();
+        // Host tasks run on the stacks of their threads, so the tasks write to their stacks
+        // themselves to simulate their usage.
+        create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| loop {
+            unsafe { (&mut *core::ptr::addr_of_mut!(STACK1))[54..].fill(0) };
+            LOG.lock().unwrap().push(("1", 0));
+            task_exit();
+        })
+        .unwrap();
+        create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |_| loop {
+            // Overwrites the canary at the bottom of the stack.
+            unsafe { (*core::ptr::addr_of_mut!(STACK2))[0] = 0 };
+            LOG.lock().unwrap().push(("2", 0));
+            task_exit();
+        })
+        .unwrap();
+        set_stack_overflow_handler(|tid| LOG.lock().unwrap().push(("overflow", tid)));
+        assert_eq!(stack_high_water_mark(1).unwrap(), 0);
+        assert!(matches!(stack_high_water_mark(3), Err(KernelError::NotFound)));
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    sim::interrupt(|| release(1 << 1));
+    sim::tick();
+    assert_eq!(call(|| stack_high_water_mark(1).unwrap()), 10);
+
+    // The overflow is detected as the task is switched out, and it is not scheduled again.
+    sim::interrupt(|| release(1 << 2));
+    sim::tick();
+    sim::interrupt(|| release((1 << 1) | (1 << 2)));
+    sim::tick();
+    assert_eq!(
+        *LOG.lock().unwrap(),
+        vec![("1", 0), ("2", 0), ("overflow", 2), ("1", 0)]
+    );
+    assert_eq!(call(|| stack_high_water_mark(2).unwrap()), 64);
+}
+
+/// Returns the result of `f` run on the simulated CPU.
+fn call(f: fn() -> usize) -> usize {
+    static RESULT: Mutex<usize> = Mutex::new(0);
+    sim::interrupt(move || *RESULT.lock().unwrap() = f());
+    *RESULT.lock().unwrap()
+}
@@ -0,0 +1,64 @@
+//! Run time accounting on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+
+use harsark::primitives::Semaphore;
+use harsark::sim;
+use harsark::tasks::*;
+
+/// The cycle counter injected into the port, which the tasks advance as they run.
+static CYCLES: AtomicU32 = AtomicU32::new(0);
+static STATS: Mutex<Vec<(u64, u32, u32)>> = Mutex::new(Vec::new());
+/// Releases task 2, signalling it reschedules right away.
+static SEM: Semaphore = Semaphore::new(1 << 2);
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+
+fn run(cycles: u32) {
+    CYCLES.fetch_add(cycles, Ordering::SeqCst);
+}
+
+#[test]
+fn run_time_accounting() {
+    sim::set_cycle_counter(|| CYCLES.load(Ordering::SeqCst));
+    std::thread::spawn(|| {
+        init(|_| Ok(())).unwrap();
+        create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| loop {
+            run(10);
+            SEM.signal_and_release(0);
+            run(5);
+            task_exit();
+        })
+        .unwrap();
+        create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |_| loop {
+            run(20);
+            task_exit();
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    sim::interrupt(|| release(1 << 1));
+    sim::tick();
+    // The idle task runs for 35 cycles before task 1 is released again.
+    run(35);
+    sim::interrupt(|| release(1 << 1));
+    sim::tick();
+    sim::interrupt(|| {
+        let mut stats = STATS.lock().unwrap();
+        for tid in 0..3 {
+            let task = harsark::tasks::stats(tid).unwrap();
+            stats.push((task.run_time, task.context_switches, task.preemptions));
+        }
+        stats.push((utilisation() as u64, 0, 0));
+    });
+    // Task 1 was preempted by task 2 on each of its runs, the idle task is always ready.
+    assert_eq!(
+        *STATS.lock().unwrap(),
+        vec![(35, 3, 2), (30, 4, 2), (40, 2, 0), (66, 0, 0)]
+    );
+}
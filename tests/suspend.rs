@@ -0,0 +1,55 @@
+//! Suspending and resuming tasks on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::Mutex;
+
+use harsark::sim;
+use harsark::tasks::*;
+use harsark::KernelError;
+
+static LOG: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+
+#[test]
+fn suspend_and_resume() {
+    std::thread::spawn(|| {
+        init(|_| Ok(())).unwrap();
+        create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| loop {
+            LOG.lock().unwrap().push("1 suspends");
+            suspend(1).unwrap();
+            LOG.lock().unwrap().push("1 resumed");
+            task_exit();
+        })
+        .unwrap();
+        create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |_| loop {
+            LOG.lock().unwrap().push("2");
+            task_exit();
+        })
+        .unwrap();
+        // The idle task can not be suspended, and there is no task 3.
+        assert!(matches!(suspend(0), Err(KernelError::AccessDenied)));
+        assert!(matches!(suspend(3), Err(KernelError::NotFound)));
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    // The released task 2 is not scheduled while it is suspended, task 1 suspends itself.
+    sim::interrupt(|| {
+        suspend(2).unwrap();
+        release((1 << 1) | (1 << 2));
+    });
+    sim::tick();
+    assert_eq!(*LOG.lock().unwrap(), vec!["1 suspends"]);
+
+    // Task 2 stayed released, so it runs once resumed.
+    sim::interrupt(|| resume(2).unwrap());
+    sim::tick();
+    assert_eq!(LOG.lock().unwrap()[1..], ["2"]);
+
+    // Task 1 continues where it suspended itself.
+    sim::interrupt(|| resume(1).unwrap());
+    sim::tick();
+    assert_eq!(LOG.lock().unwrap()[2..], ["1 resumed"]);
+}
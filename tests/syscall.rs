@@ -0,0 +1,100 @@
+//! Kernel objects used by unprivileged tasks through system calls on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::Mutex;
+
+use harsark::primitives::{Message, Resource, Semaphore};
+use harsark::sim;
+use harsark::soft_timer::{self, TimerMode};
+use harsark::tasks::*;
+use harsark::timer::{get_time, Duration};
+use harsark::KernelError;
+
+static LOG: Mutex<Vec<(&'static str, u32)>> = Mutex::new(Vec::new());
+/// Shared by tasks 1 and 3, hence its ceiling is the priority of task 3.
+static RES: Resource<u32> = Resource::new(5, (1 << 1) | (1 << 3));
+/// Only accessible to task 2.
+static PRIVATE: Resource<u32> = Resource::new(0, 1 << 2);
+/// Accessible to task 1, but never registered.
+static UNREGISTERED: Resource<u32> = Resource::new(0, 1 << 1);
+static SEM: Semaphore = Semaphore::new((1 << 2) | (1 << 3));
+static MSG: Message<u32> = Message::new(1 << 3, 1 << 3, 0);
+/// Would release task 3, but it is never registered.
+static OTHER: Message<u32> = Message::new(1 << 3, 1 << 3, 0);
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+static mut STACK3: [u32; 64] = [0; 64];
+
+#[test]
+fn syscalls() {
+    std::thread::spawn(|| {
+        init(|_| {
+            RES.register()?;
+            PRIVATE.register()?;
+            SEM.register()?;
+            MSG.register()?;
+            let timer = soft_timer::create(TimerMode::OneShot, Duration::from_ticks(10), || {})?;
+            soft_timer::start(timer)
+        })
+        .unwrap();
+        create_unprivileged_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |cxt| loop {
+            assert_eq!(get_curr_tid(), 1);
+            assert!(get_time().ticks() > 0);
+            assert!(matches!(soft_timer::is_active(0), Ok(true)));
+            // Only privileged code can register objects.
+            assert!(matches!(UNREGISTERED.register(), Err(KernelError::AccessDenied)));
+            let res = UNREGISTERED.acquire(cxt, |_| ());
+            assert!(matches!(res, Err(KernelError::NotFound)));
+            // A resource forged by the task claims every task, but it is not registered.
+            let forged = Resource::new(0, !0);
+            assert!(matches!(forged.acquire(cxt, |_| ()), Err(KernelError::NotFound)));
+            // The ceiling is the one recorded on registration, so only tasks up to task 3 are
+            // blocked.
+            let res = PRIVATE.acquire(cxt, |_| ());
+            assert!(matches!(res, Err(KernelError::AccessDenied)));
+            RES.acquire(cxt, |value| {
+                LOG.lock().unwrap().push(("1 locked", *value));
+                SEM.signal_and_release(0);
+                LOG.lock().unwrap().push(("1 unlocking", 0));
+            })
+            .unwrap();
+            OTHER.broadcast(7);
+            MSG.broadcast(42);
+            LOG.lock().unwrap().push(("1 end", 0));
+            task_exit();
+        })
+        .unwrap();
+        create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |_| loop {
+            LOG.lock().unwrap().push(("2", 0));
+            task_exit();
+        })
+        .unwrap();
+        create_unprivileged_task(3, unsafe { &mut *core::ptr::addr_of_mut!(STACK3) }, |cxt| loop {
+            assert!(!SEM.test_and_reset(cxt).unwrap());
+            match MSG.receive(cxt) {
+                Some(msg) => LOG.lock().unwrap().push(("3 received", msg)),
+                None => LOG.lock().unwrap().push(("3", 0)),
+            }
+            task_exit();
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    sim::tick();
+    sim::interrupt(|| release(1 << 1));
+    sim::tick();
+    assert_eq!(
+        *LOG.lock().unwrap(),
+        vec![
+            ("1 locked", 5),
+            ("1 unlocking", 0),
+            ("3", 0),
+            ("2", 0),
+            ("3 received", 42),
+            ("1 end", 0)
+        ]
+    );
+}
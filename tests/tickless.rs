@@ -0,0 +1,76 @@
+//! Tick accounting in tickless idle mode on the host simulation port.
+#![cfg(all(feature = "std", feature = "timer", not(feature = "task_monitor")))]
+
+use std::sync::Mutex;
+
+use harsark::sim;
+use harsark::tasks::*;
+use harsark::timer::{get_time, set_sleep_hooks, set_tickless, Duration};
+
+static LOG: Mutex<Vec<(&'static str, u64)>> = Mutex::new(Vec::new());
+static mut STACK1: [u32; 64] = [0; 64];
+static mut STACK2: [u32; 64] = [0; 64];
+
+fn log(event: &'static str) {
+    LOG.lock().unwrap().push((event, get_time().ticks()));
+}
+
+#[test]
+fn tickless_idle() {
+    std::thread::spawn(|| {
+        init(|_| {
+            set_tickless(true);
+            set_sleep_hooks(
+                |ticks| LOG.lock().unwrap().push(("sleep", ticks as u64)),
+                |ticks| LOG.lock().unwrap().push(("slept", ticks as u64)),
+            );
+            Ok(())
+        })
+        .unwrap();
+        create_task(1, unsafe { &mut *core::ptr::addr_of_mut!(STACK1) }, |_| loop {
+            sleep(Duration::from_ticks(5));
+            log("1 woke");
+            sleep(Duration::from_ticks(10));
+            log("1 woke");
+            task_exit();
+        })
+        .unwrap();
+        create_task(2, unsafe { &mut *core::ptr::addr_of_mut!(STACK2) }, |_| loop {
+            log("2");
+            task_exit();
+        })
+        .unwrap();
+        let mut peripherals = sim::Peripherals::take().unwrap();
+        harsark::timer::start_timer(&mut peripherals, 80000);
+        start_kernel();
+    });
+
+    // With nothing to do the CPU sleeps until it is interrupted, then task 1 goes to sleep.
+    sim::interrupt(|| release(1 << 1));
+    sim::tick();
+    assert_eq!(
+        *LOG.lock().unwrap(),
+        vec![("sleep", u32::MAX as u64), ("slept", 0), ("sleep", 5)]
+    );
+
+    // The first four ticks pass while the CPU sleeps, the fifth is the stretched SysTick firing.
+    for _ in 0..5 {
+        sim::tick();
+    }
+    assert_eq!(
+        LOG.lock().unwrap()[3..],
+        [("slept", 4), ("1 woke", 6), ("sleep", 10)]
+    );
+
+    // Another interrupt ends the sleep early, the ticks which passed until then are accounted.
+    // The task it released runs on the next tick, then the CPU sleeps until task 1 wakes up.
+    for _ in 0..3 {
+        sim::tick();
+    }
+    sim::interrupt(|| release(1 << 2));
+    sim::tick();
+    assert_eq!(
+        LOG.lock().unwrap()[6..],
+        [("slept", 3), ("2", 10), ("sleep", 6)]
+    );
+}
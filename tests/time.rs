@@ -0,0 +1,42 @@
+//! Arithmetic and conversions of the Kernel time types.
+#![cfg(all(feature = "std", feature = "timer"))]
+
+use harsark::timer::{Duration, Instant, TICK_RATE_HZ};
+
+#[test]
+fn instant_arithmetic() {
+    let instant = Instant::from_ticks(10);
+    assert_eq!(instant + Duration::from_ticks(5), Instant::from_ticks(15));
+    assert_eq!(instant - Duration::from_ticks(4), Instant::from_ticks(6));
+    // Both differences saturate instead of underflowing.
+    assert_eq!(instant - Duration::from_ticks(11), Instant::from_ticks(0));
+    assert_eq!(instant - Instant::from_ticks(11), Duration::ZERO);
+    assert_eq!(Instant::from_ticks(15) - instant, Duration::from_ticks(5));
+    // Neither do the sums overflow.
+    let mut instant = Instant::from_ticks(u64::MAX - 1);
+    instant += Duration::from_ticks(2);
+    assert_eq!(instant, Instant::from_ticks(u64::MAX));
+}
+
+#[test]
+fn duration_arithmetic() {
+    let mut duration = Duration::from_ticks(3) + Duration::from_ticks(4);
+    assert_eq!(duration, Duration::from_ticks(7));
+    duration -= Duration::from_ticks(2);
+    assert_eq!(duration - Duration::from_ticks(6), Duration::ZERO);
+    duration -= Duration::from_ticks(6);
+    assert_eq!(duration, Duration::ZERO);
+    let mut duration = Duration::from_ticks(u64::MAX) + Duration::from_ticks(1);
+    duration += Duration::from_ticks(1);
+    assert_eq!(duration, Duration::from_ticks(u64::MAX));
+}
+
+#[test]
+fn duration_conversions() {
+    assert_eq!(Duration::from_secs(2).ticks(), 2 * TICK_RATE_HZ);
+    assert_eq!(Duration::from_secs(3).as_millis(), 3_000);
+    // A tick and a bit is rounded up to two ticks, but back to the whole ticks.
+    let micros = 1_000_000 / TICK_RATE_HZ + 1;
+    assert_eq!(Duration::from_micros(micros).ticks(), 2);
+    assert_eq!(Duration::from_ticks(1).as_micros(), 1_000_000 / TICK_RATE_HZ);
+}